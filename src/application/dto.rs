@@ -1,7 +1,11 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
+use serde::Serialize;
 use std::path::PathBuf;
 
+use crate::domain::diff::ChangeKind;
+use crate::domain::LocalChanges;
+
 /// DTO for initializing a new configuration
 pub struct InitConfigDto {
     pub config_path: PathBuf,
@@ -12,9 +16,12 @@ pub struct InitConfigDto {
 pub struct AddDependencyDto {
     pub name: String,
     pub repository_url: String,
-    pub revision: String,
+    /// Revision, or `None` for the default of the source type
+    pub revision: Option<String>,
     pub repository_type: String,
     pub target_location: String,
+    /// Options of the source type, as `key=value`
+    pub options: Vec<String>,
 }
 
 /// DTO for including paths in a dependency
@@ -23,14 +30,176 @@ pub struct IncludePathsDto {
     pub paths: Vec<String>,
 }
 
+/// DTO for removing a dependency
+pub struct RemoveDependencyDto {
+    pub config_path: PathBuf,
+    pub dependency_name: String,
+}
+
+/// DTO for a dependency that was removed
+#[derive(Debug, Serialize)]
+pub struct RemovedDependencyDto {
+    pub name: String,
+    pub repository_url: String,
+    pub revision: String,
+    /// Commit that was vendored, if the dependency was locked
+    pub commit: Option<String>,
+    pub target_location: String,
+}
+
+/// DTO for turning local modifications into a patch
+pub struct CreatePatchDto {
+    pub config_path: PathBuf,
+    pub dependency_name: String,
+    /// Path of the patch file, relative to the configuration file unless absolute
+    pub output: Option<PathBuf>,
+}
+
+/// DTO for a patch created from local modifications
+#[derive(Debug, Serialize)]
+pub struct CreatedPatchDto {
+    pub name: String,
+    /// Path of the patch file, as listed in the source's `patches`
+    pub path: String,
+    /// Files the patch changes, relative to the target
+    pub files: Vec<String>,
+}
+
+/// DTO for exporting the changes made to a dependency as a patch series
+pub struct ExportChangesDto {
+    pub config_path: PathBuf,
+    pub dependency_name: String,
+    /// Author of the patches, as `Name <email>`
+    pub author: String,
+    /// Directory the series is written to, or `None` to only return it
+    pub output_dir: Option<PathBuf>,
+}
+
+/// DTO for a patch series exported from a dependency
+#[derive(Debug, Serialize)]
+pub struct ExportedChangesDto {
+    pub name: String,
+    pub repository_url: String,
+    /// Upstream commit the series applies to
+    pub base_commit: String,
+    pub patches: Vec<ExportedPatchDto>,
+}
+
+/// DTO for one patch of an exported series
+#[derive(Debug, Serialize)]
+pub struct ExportedPatchDto {
+    /// File name of the patch, or its path when written
+    pub path: String,
+    pub subject: String,
+    /// Upstream paths the patch changes
+    pub files: Vec<String>,
+    /// The patch itself, when printed rather than written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
 /// DTO for updating dependencies
 pub struct UpdateDependenciesDto {
     pub config_path: PathBuf,
     pub dependencies: Option<Vec<String>>,
-    pub force: bool,
+    /// What to do with vendored files modified since the last update
+    pub local_changes: LocalChanges,
+    /// Fail before any target changes when kept local modifications would
+    /// conflict, rather than leaving them between conflict markers
+    pub refuse_conflicts: bool,
+}
+
+/// DTO for a dependency that was updated
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdatedDependencyDto {
+    pub name: String,
+    pub repository_url: String,
+    /// Mirror the content was fetched from, if not `repository_url`
+    pub mirror: Option<String>,
+    pub revision: String,
+    pub commit: Option<String>,
+    pub target_location: String,
+    pub files: usize,
+    /// Locally modified files carried forward, relative to the target
+    pub kept_local: Vec<String>,
+    /// Files left with conflict markers or local versions conflicting with upstream
+    pub conflicts: Vec<String>,
+}
+
+/// DTO for progress reported while updating dependencies
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum UpdateEventDto {
+    DependencyStarted {
+        name: String,
+    },
+    DependencyUpdated(UpdatedDependencyDto),
+    Cancelled,
+    Finished {
+        dependencies: Vec<UpdatedDependencyDto>,
+    },
+}
+
+/// DTO for previewing dependency changes
+pub struct DiffDependenciesDto {
+    pub config_path: PathBuf,
+    pub dependencies: Option<Vec<String>>,
+    pub text_diffs: bool,
+}
+
+/// DTO for a single file change in a preview
+#[derive(Debug, Serialize)]
+pub struct FileChangeDto {
+    pub path: String,
+    pub change: ChangeKind,
+    pub diff: Option<String>,
+}
+
+/// DTO for the previewed changes of one dependency
+#[derive(Debug, Serialize)]
+pub struct DependencyDiffDto {
+    pub name: String,
+    pub target_location: String,
+    pub changes: Vec<FileChangeDto>,
+    /// Transform steps applied to the upstream content, in order
+    pub transforms: Vec<TransformStepDto>,
+}
+
+/// DTO for one transform step and the files it changed
+#[derive(Debug, Serialize)]
+pub struct TransformStepDto {
+    pub step: String,
+    pub files: Vec<String>,
+}
+
+/// DTO for listing upstream commits of a dependency
+pub struct LogDependencyDto {
+    pub config_path: PathBuf,
+    pub dependency_name: String,
+    pub to: Option<String>,
+}
+
+/// DTO for a single upstream commit
+#[derive(Debug, Serialize)]
+pub struct CommitDto {
+    pub id: String,
+    pub author: String,
+    pub date: String,
+    pub subject: String,
+}
+
+/// DTO for the upstream changelog of a dependency
+#[derive(Debug, Serialize)]
+pub struct DependencyLogDto {
+    pub name: String,
+    pub repository_url: String,
+    pub from: String,
+    pub to: String,
+    pub commits: Vec<CommitDto>,
 }
 
 /// DTO for dependency status
+#[derive(Debug, Serialize)]
 pub struct DependencyStatusDto {
     pub name: String,
     pub repository_url: String,
@@ -38,4 +207,56 @@ pub struct DependencyStatusDto {
     pub target_location: String,
     pub sparse_paths: Vec<String>,
     pub status: String,
+    pub states: Vec<String>,
+    pub locked_commit: Option<String>,
+    pub upstream_commit: Option<String>,
+    pub provenance: Option<ProvenanceDto>,
+    pub details: Vec<String>,
+}
+
+/// DTO for the provenance file found in a target directory
+#[derive(Debug, Serialize)]
+pub struct ProvenanceDto {
+    pub repository_url: String,
+    pub revision: String,
+    pub commit: Option<String>,
+    pub mirror: Option<String>,
+    pub tool_version: String,
+    pub updated_at: Option<String>,
+}
+
+/// DTO for the result of verifying vendored content
+#[derive(Debug, Serialize)]
+pub struct VerificationReportDto {
+    pub verified: bool,
+    pub failed: Vec<String>,
+    pub dependencies: Vec<DependencyStatusDto>,
+}
+
+/// DTO for a dependency whose upstream revision moved
+#[derive(Debug, Serialize)]
+pub struct OutdatedDependencyDto {
+    pub name: String,
+    pub repository_url: String,
+    pub revision: String,
+    pub locked_commit: Option<String>,
+    pub upstream_commit: Option<String>,
+}
+
+/// DTO for the outcome of a single CI check
+#[derive(Debug, Serialize)]
+pub struct CheckResultDto {
+    /// Failure class: `config`, `lock`, `integrity` or `upstream`
+    pub class: String,
+    /// Dependency the check applies to, or `configuration`
+    pub name: String,
+    pub passed: bool,
+    pub message: String,
+}
+
+/// DTO for the result of all CI checks
+#[derive(Debug, Serialize)]
+pub struct CheckReportDto {
+    pub passed: bool,
+    pub checks: Vec<CheckResultDto>,
 }
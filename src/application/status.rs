@@ -3,36 +3,79 @@
 use anyhow::{Context, Result};
 use std::path::Path;
 
-use crate::application::dto::DependencyStatusDto;
-use crate::domain::repositories::ConfigurationRepository;
-use crate::domain::DomainError;
+use crate::application::dto::{
+    CheckReportDto, CheckResultDto, DependencyStatusDto, OutdatedDependencyDto, ProvenanceDto,
+    VerificationReportDto,
+};
+use crate::domain::gitattributes;
+use crate::domain::manifest;
+use crate::domain::mirrors::Mirrors;
+use crate::domain::patch;
+use crate::domain::provenance::{Provenance, PROVENANCE_FILE};
+use crate::domain::repositories::{ConfigurationRepository, LockFileRepository, RepositoryFetcher};
+use crate::domain::validation::validate_configuration;
+use crate::domain::{
+    Dependency, DependencyState, DomainError, LockFile, LockedDependency, LockedPatch,
+};
 
 /// Query for showing dependency status
-pub struct GetDependencyStatusQuery<C: ConfigurationRepository> {
+pub struct GetDependencyStatusQuery<C, L, R>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    R: RepositoryFetcher,
+{
     config_repo: C,
+    lock_repo: L,
+    repository_fetcher: R,
+    mirrors: Mirrors,
 }
 
-impl<C: ConfigurationRepository> GetDependencyStatusQuery<C> {
-    pub fn new(config_repo: C) -> Self {
-        Self { config_repo }
+impl<C, L, R> GetDependencyStatusQuery<C, L, R>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    R: RepositoryFetcher,
+{
+    pub fn new(config_repo: C, lock_repo: L, repository_fetcher: R) -> Self {
+        Self {
+            config_repo,
+            lock_repo,
+            repository_fetcher,
+            mirrors: Mirrors::default(),
+        }
+    }
+
+    /// Resolve upstream revisions through the configured mirrors first
+    pub fn with_mirrors(mut self, mirrors: Mirrors) -> Self {
+        self.mirrors = mirrors;
+        self
     }
 
     /// Get the status of all dependencies
-    pub fn get_all_statuses(&self, config_path: &Path) -> Result<Vec<DependencyStatusDto>> {
+    ///
+    /// When `check_remote` is set, the configured revisions are also resolved
+    /// upstream to detect dependencies whose upstream has moved.
+    pub fn get_all_statuses(
+        &self,
+        config_path: &Path,
+        check_remote: bool,
+    ) -> Result<Vec<DependencyStatusDto>> {
+        self.statuses(config_path, check_remote, None)
+    }
+
+    /// Get the status of the named dependencies, or of all of them
+    ///
+    /// Dependencies left out are not evaluated, so their upstream is not
+    /// resolved.
+    fn statuses(
+        &self,
+        config_path: &Path,
+        check_remote: bool,
+        dependencies: Option<&[String]>,
+    ) -> Result<Vec<DependencyStatusDto>> {
         // Convert the config_path to an absolute path if it's relative
-        let absolute_config_path = if config_path.is_absolute() {
-            config_path.to_path_buf()
-        } else {
-            // Join with current directory to get absolute path
-            std::env::current_dir()
-                .map_err(|e| {
-                    DomainError::ConfigurationError(format!(
-                        "Failed to get current directory: {}",
-                        e
-                    ))
-                })?
-                .join(config_path)
-        };
+        let absolute_config_path = absolute_path(config_path)?;
 
         // Load the configuration
         let config = self
@@ -40,6 +83,12 @@ impl<C: ConfigurationRepository> GetDependencyStatusQuery<C> {
             .load(&absolute_config_path)
             .context("Failed to load configuration")?;
 
+        // Load the lock file recording the last update
+        let lock = self
+            .lock_repo
+            .load(&LockFile::path_for(&absolute_config_path))
+            .context("Failed to load lock file")?;
+
         // Get the repository root - if we have a parent directory, use it, otherwise use the current directory
         let repo_root = if let Some(parent) = absolute_config_path.parent() {
             parent.to_path_buf()
@@ -53,16 +102,21 @@ impl<C: ConfigurationRepository> GetDependencyStatusQuery<C> {
         // Create status DTOs for each dependency
         let mut statuses = Vec::new();
 
-        for dep in &config.dependencies {
-            // Determine the absolute target path
-            let target_path = repo_root.join(&dep.target_location);
-
-            // Determine status
-            let status = if !target_path.exists() {
-                "Not fetched".to_string()
-            } else {
-                "Fetched".to_string()
-            };
+        let selected = config
+            .dependencies
+            .iter()
+            .filter(|dep| dependencies.is_none_or(|names| names.contains(&dep.name)));
+        for dep in selected {
+            let locked = lock.get(&dep.name);
+            let evaluation = self.evaluate(dep, locked, &repo_root, check_remote)?;
+
+            // The first state is the most significant one
+            let status = evaluation
+                .states
+                .first()
+                .copied()
+                .unwrap_or(DependencyState::UpToDate)
+                .to_string();
 
             // Create the DTO
             let dto = DependencyStatusDto {
@@ -72,6 +126,18 @@ impl<C: ConfigurationRepository> GetDependencyStatusQuery<C> {
                 target_location: dep.target_location.to_string_lossy().to_string(),
                 sparse_paths: dep.sparse_paths.clone(),
                 status,
+                states: evaluation.states.iter().map(|s| s.to_string()).collect(),
+                locked_commit: locked.and_then(|l| l.commit.clone()),
+                upstream_commit: evaluation.upstream_commit,
+                provenance: evaluation.provenance.map(|p| ProvenanceDto {
+                    repository_url: p.repository_url,
+                    revision: p.revision,
+                    commit: p.commit,
+                    mirror: p.mirror,
+                    tool_version: p.tool_version,
+                    updated_at: Some(p.updated_at).filter(|time| !time.is_empty()),
+                }),
+                details: evaluation.details,
             };
 
             statuses.push(dto);
@@ -79,4 +145,387 @@ impl<C: ConfigurationRepository> GetDependencyStatusQuery<C> {
 
         Ok(statuses)
     }
+
+    /// Verify that vendored content matches the lock file, without network access
+    ///
+    /// Missing, unlocked and locally modified dependencies fail verification.
+    pub fn verify(
+        &self,
+        config_path: &Path,
+        dependencies: Option<&[String]>,
+    ) -> Result<VerificationReportDto> {
+        let statuses = self.statuses(config_path, false, dependencies)?;
+
+        let failing = [
+            DependencyState::Missing,
+            DependencyState::Stale,
+            DependencyState::LocallyModified,
+        ]
+        .map(|s| s.to_string());
+
+        let failed: Vec<String> = statuses
+            .iter()
+            .filter(|s| s.states.iter().any(|state| failing.contains(state)))
+            .map(|s| s.name.clone())
+            .collect();
+
+        Ok(VerificationReportDto {
+            verified: failed.is_empty(),
+            failed,
+            dependencies: statuses,
+        })
+    }
+
+    /// List dependencies whose configured revision moved upstream
+    pub fn outdated(
+        &self,
+        config_path: &Path,
+        dependencies: Option<&[String]>,
+    ) -> Result<Vec<OutdatedDependencyDto>> {
+        let statuses = self.statuses(config_path, true, dependencies)?;
+        let moved = DependencyState::UpstreamMoved.to_string();
+
+        Ok(statuses
+            .into_iter()
+            .filter(|s| s.states.contains(&moved))
+            .map(|s| OutdatedDependencyDto {
+                name: s.name,
+                repository_url: s.repository_url,
+                revision: s.revision,
+                locked_commit: s.locked_commit,
+                upstream_commit: s.upstream_commit,
+            })
+            .collect())
+    }
+
+    /// Run all CI checks: configuration validity, lock consistency and manifest integrity
+    ///
+    /// Network access is only needed when `check_remote` is set, which adds
+    /// an upstream freshness check per dependency.
+    pub fn check(&self, config_path: &Path, check_remote: bool) -> Result<CheckReportDto> {
+        let mut checks = Vec::new();
+        let mut record = |class: &str, name: &str, failures: Vec<String>, ok: &str| {
+            checks.push(CheckResultDto {
+                class: class.to_string(),
+                name: name.to_string(),
+                passed: failures.is_empty(),
+                message: if failures.is_empty() {
+                    ok.to_string()
+                } else {
+                    failures.join("; ")
+                },
+            });
+        };
+
+        // Configuration validity
+        let absolute_config_path = absolute_path(config_path)?;
+        let config = match self.config_repo.load(&absolute_config_path) {
+            Ok(config) => config,
+            Err(e) => {
+                record("config", "configuration", vec![e.to_string()], "");
+                return Ok(CheckReportDto {
+                    passed: false,
+                    checks,
+                });
+            }
+        };
+
+        let issues = validate_configuration(&config, &self.repository_fetcher);
+        record(
+            "config",
+            "configuration",
+            issues
+                .iter()
+                .filter(|i| i.dependency.is_none())
+                .map(|i| i.message.clone())
+                .collect(),
+            "configuration is valid",
+        );
+        for dep in &config.dependencies {
+            record(
+                "config",
+                &dep.name,
+                issues
+                    .iter()
+                    .filter(|i| i.dependency.as_deref() == Some(dep.name.as_str()))
+                    .map(|i| i.message.clone())
+                    .collect(),
+                "configuration is valid",
+            );
+        }
+
+        // Lock entries for dependencies that no longer exist
+        let lock = match self
+            .lock_repo
+            .load(&LockFile::path_for(&absolute_config_path))
+        {
+            Ok(lock) => lock,
+            Err(e) => {
+                record("lock", "lock-file", vec![e.to_string()], "");
+                return Ok(CheckReportDto {
+                    passed: false,
+                    checks,
+                });
+            }
+        };
+        record(
+            "lock",
+            "lock-file",
+            lock.dependencies
+                .iter()
+                .filter(|l| !config.dependencies.iter().any(|d| d.name == l.name))
+                .map(|l| format!("lock entry '{}' has no matching source", l.name))
+                .collect(),
+            "lock file has no orphaned entries",
+        );
+
+        // The acdm block in .gitattributes, when acdm maintains one
+        if config.gitattributes.manage {
+            let dir = absolute_config_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."));
+            let failures = match gitattributes::is_current(dir, &config) {
+                Ok(true) => vec![],
+                Ok(false) => vec![format!(
+                    "the acdm block in {} does not match the configured targets",
+                    gitattributes::GITATTRIBUTES_FILE
+                )],
+                Err(e) => vec![e.to_string()],
+            };
+            record(
+                "config",
+                "gitattributes",
+                failures,
+                "gitattributes block matches the configured targets",
+            );
+        }
+
+        // Per-dependency lock consistency, integrity and freshness
+        let statuses = self.get_all_statuses(config_path, check_remote)?;
+        for status in &statuses {
+            let failures = |states: &[DependencyState]| -> Vec<String> {
+                let names: Vec<String> = states.iter().map(|s| s.to_string()).collect();
+                status
+                    .details
+                    .iter()
+                    .filter(|d| names.iter().any(|n| d.starts_with(&format!("{}:", n))))
+                    .cloned()
+                    .collect()
+            };
+
+            record(
+                "lock",
+                &status.name,
+                failures(&[DependencyState::Stale, DependencyState::ConfigChanged]),
+                "lock entry matches the configuration",
+            );
+            record(
+                "integrity",
+                &status.name,
+                failures(&[DependencyState::Missing, DependencyState::LocallyModified]),
+                "vendored files match the lock file",
+            );
+            if check_remote {
+                record(
+                    "upstream",
+                    &status.name,
+                    failures(&[DependencyState::UpstreamMoved]),
+                    "locked commit matches upstream",
+                );
+            }
+        }
+
+        Ok(CheckReportDto {
+            passed: checks.iter().all(|c| c.passed),
+            checks,
+        })
+    }
+
+    /// Determines every state that applies to a dependency, most significant first
+    fn evaluate(
+        &self,
+        dep: &Dependency,
+        locked: Option<&LockedDependency>,
+        repo_root: &Path,
+        check_remote: bool,
+    ) -> Result<Evaluation> {
+        let mut states = Vec::new();
+        let mut details = Vec::new();
+        let mut upstream_commit = None;
+        let mut provenance = None;
+        let mut provenance_error = None;
+
+        let target_path = repo_root.join(&dep.target_location);
+        if !target_path.exists() {
+            states.push(DependencyState::Missing);
+            details.push(format!(
+                "missing: {} does not exist; run 'acdm update {}'",
+                dep.target_location.display(),
+                dep.name
+            ));
+        } else {
+            match Provenance::read(&target_path) {
+                Ok(found) => provenance = found,
+                Err(e) => provenance_error = Some(e.to_string()),
+            }
+        }
+
+        let locked = match locked {
+            Some(locked) => locked,
+            None => {
+                states.push(DependencyState::Stale);
+                details.push(
+                    "stale: no entry in the lock file; run 'acdm update' to record one".to_string(),
+                );
+                return Ok(Evaluation {
+                    states,
+                    details,
+                    upstream_commit,
+                    provenance,
+                });
+            }
+        };
+
+        let patches = patch::resolve(repo_root, &dep.patches)
+            .and_then(|files| patch::fingerprint(repo_root, &files))
+            .context("Failed to resolve patches")?;
+        if !locked.matches_config(dep, &patches) {
+            states.push(DependencyState::ConfigChanged);
+            details.push(format!(
+                "config-changed: acdm.toml differs from the last update ({})",
+                describe_config_changes(dep, locked, &patches).join(", ")
+            ));
+        }
+
+        if target_path.exists() {
+            let drift = manifest::compare_manifest(&target_path, &locked.files)?;
+            if !drift.is_clean() {
+                states.push(DependencyState::LocallyModified);
+                details.push(format!(
+                    "locally-modified: {} modified, {} added, {} removed since the last update",
+                    drift.modified.len(),
+                    drift.added.len(),
+                    drift.removed.len()
+                ));
+                for path in &drift.modified {
+                    details.push(format!("  M {}", path));
+                }
+                for path in &drift.added {
+                    details.push(format!("  A {}", path));
+                }
+                for path in &drift.removed {
+                    details.push(format!("  D {}", path));
+                }
+            }
+        }
+
+        // The provenance file must describe the locked update; content from
+        // elsewhere can match the manifest while the record gives it away
+        let provenance_conflicts = match (&provenance, provenance_error) {
+            (Some(provenance), _) => provenance.conflicts_with(locked),
+            (None, Some(error)) => vec![error],
+            (None, None) => Vec::new(),
+        };
+        if !provenance_conflicts.is_empty() {
+            if !states.contains(&DependencyState::LocallyModified) {
+                states.push(DependencyState::LocallyModified);
+            }
+            details.push(format!(
+                "locally-modified: {} does not match the lock file: {}",
+                PROVENANCE_FILE,
+                provenance_conflicts.join(", ")
+            ));
+        }
+
+        // Only sources tracking revisions have an upstream that can move
+        if check_remote && self.repository_fetcher.tracks_revisions(dep) {
+            let (remote, _) = self
+                .mirrors
+                .try_each(&dep.repository_url, |url| {
+                    self.repository_fetcher.resolve_remote_dependency(dep, url)
+                })
+                .context("Failed to resolve upstream revision")?;
+
+            if let (Some(remote), Some(commit)) = (remote.as_ref(), locked.commit.as_ref()) {
+                if remote != commit {
+                    states.push(DependencyState::UpstreamMoved);
+                    details.push(format!(
+                        "upstream-moved: {} now points to {} (locked {})",
+                        dep.revision, remote, commit
+                    ));
+                }
+            }
+            upstream_commit = remote;
+        }
+
+        if states.is_empty() {
+            details.push(format!(
+                "up-to-date: matches the lock file at {}",
+                locked.commit.as_deref().unwrap_or("an unknown commit")
+            ));
+        }
+
+        Ok(Evaluation {
+            states,
+            details,
+            upstream_commit,
+            provenance,
+        })
+    }
+}
+
+/// Outcome of evaluating a single dependency
+struct Evaluation {
+    /// Applicable states, most significant first
+    states: Vec<DependencyState>,
+    details: Vec<String>,
+    upstream_commit: Option<String>,
+
+    /// Provenance file of the target directory, if it has one
+    provenance: Option<Provenance>,
+}
+
+/// Converts a path to an absolute path, relative to the current directory
+fn absolute_path(path: &Path) -> Result<std::path::PathBuf> {
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+
+    // Join with current directory to get absolute path
+    Ok(std::env::current_dir()
+        .map_err(|e| {
+            DomainError::ConfigurationError(format!("Failed to get current directory: {}", e))
+        })?
+        .join(path))
+}
+
+/// Lists the configuration fields that differ from a lock entry
+fn describe_config_changes(
+    dep: &Dependency,
+    locked: &LockedDependency,
+    patches: &[LockedPatch],
+) -> Vec<&'static str> {
+    let mut changes = Vec::new();
+    if dep.repository_url != locked.repository_url {
+        changes.push("repo");
+    }
+    if dep.revision != locked.revision {
+        changes.push("rev");
+    }
+    if dep.sparse_paths != locked.sparse_paths {
+        changes.push("sparse_paths");
+    }
+    if dep.target_location != locked.target_location {
+        changes.push("target");
+    }
+    if patches != locked.patches {
+        changes.push("patches");
+    }
+    if dep.transforms != locked.transforms {
+        changes.push("transforms");
+    }
+    if dep.options != locked.options {
+        changes.push("options");
+    }
+    changes
 }
@@ -1,16 +1,29 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
 use anyhow::{Context, Result};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
 
 use crate::application::dto::{
-    AddDependencyDto, IncludePathsDto, InitConfigDto, UpdateDependenciesDto,
+    AddDependencyDto, CommitDto, CreatePatchDto, CreatedPatchDto, DependencyDiffDto,
+    DependencyLogDto, DiffDependenciesDto, ExportChangesDto, ExportedChangesDto, ExportedPatchDto,
+    FileChangeDto, IncludePathsDto, InitConfigDto, LogDependencyDto, RemoveDependencyDto,
+    RemovedDependencyDto, TransformStepDto, UpdateDependenciesDto, UpdateEventDto,
+    UpdatedDependencyDto,
 };
+use crate::domain::export;
+use crate::domain::gitattributes;
+use crate::domain::manifest;
+use crate::domain::mirrors::Mirrors;
+use crate::domain::patch;
+use crate::domain::provenance::{Provenance, PROVENANCE_FILE};
 use crate::domain::repositories::{
-    ConfigurationRepository, FileSystemManager, GitOperations, RepositoryFetcher,
+    ConfigurationRepository, FileSystemManager, GitOperations, LockFileRepository,
+    RepositoryFetcher, RepositoryHistory,
 };
-use crate::domain::services::DependencyManager;
-use crate::domain::{Dependency, RepositoryType};
+use crate::domain::services::{DependencyManager, UpdateOutcome, UpdateProgress};
+use crate::domain::validation::target_problem;
+use crate::domain::{Configuration, Dependency, LocalChanges, LockFile, SourceOptions};
 
 /// Use case for initializing a new configuration
 pub struct InitConfigUseCase<C: ConfigurationRepository> {
@@ -47,13 +60,17 @@ impl<C: ConfigurationRepository> InitConfigUseCase<C> {
 }
 
 /// Use case for adding a new dependency
-pub struct AddDependencyUseCase<C: ConfigurationRepository> {
+pub struct AddDependencyUseCase<C: ConfigurationRepository, R: RepositoryFetcher> {
     config_repo: C,
+    sources: R,
 }
 
-impl<C: ConfigurationRepository> AddDependencyUseCase<C> {
-    pub fn new(config_repo: C) -> Self {
-        Self { config_repo }
+impl<C: ConfigurationRepository, R: RepositoryFetcher> AddDependencyUseCase<C, R> {
+    pub fn new(config_repo: C, sources: R) -> Self {
+        Self {
+            config_repo,
+            sources,
+        }
     }
 
     pub fn execute(&self, config_path: &Path, dto: AddDependencyDto) -> Result<()> {
@@ -71,27 +88,42 @@ impl<C: ConfigurationRepository> AddDependencyUseCase<C> {
             ));
         }
 
-        // Parse the repository type
-        let repo_type = match dto.repository_type.to_lowercase().as_str() {
-            "git" => RepositoryType::Git,
-            _ => {
-                return Err(anyhow::anyhow!(
-                    "Unsupported repository type: {}",
-                    dto.repository_type
-                ))
-            }
-        };
+        if let Some(problem) = target_problem(Path::new(&dto.target_location)) {
+            return Err(anyhow::anyhow!(
+                "Invalid source '{}': {}",
+                dto.name,
+                problem
+            ));
+        }
 
         // Create a new dependency
-        let dependency = Dependency {
+        let mut dependency = Dependency {
             name: dto.name,
             repository_url: dto.repository_url,
-            revision: dto.revision,
-            repository_type: repo_type,
+            revision: dto.revision.clone().unwrap_or_default(),
+            repository_type: dto.repository_type.to_lowercase(),
             sparse_paths: Vec::new(),
             target_location: PathBuf::from(dto.target_location),
+            options: parse_option_assignments(&dto.options)?,
+            ..Default::default()
         };
 
+        // Sources tracking revisions follow "main" unless told otherwise
+        if dto.revision.is_none() && self.sources.tracks_revisions(&dependency) {
+            dependency.revision = DEFAULT_REVISION.to_string();
+        }
+
+        // The source type checks its options before anything is saved
+        let issues = self.sources.validate_dependency(&dependency);
+        if !issues.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Invalid {} source '{}': {}",
+                dependency.repository_type,
+                dependency.name,
+                issues.join("; ")
+            ));
+        }
+
         // Add the dependency to the configuration
         config.dependencies.push(dependency);
 
@@ -100,8 +132,40 @@ impl<C: ConfigurationRepository> AddDependencyUseCase<C> {
             .save(&config, config_path)
             .context("Failed to save configuration")?;
 
-        Ok(())
+        sync_gitattributes(config_path, &config)
+    }
+}
+
+/// Brings the acdm block in the `.gitattributes` next to the configuration
+/// file in line with its targets
+fn sync_gitattributes(config_path: &Path, config: &Configuration) -> Result<()> {
+    let dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    gitattributes::sync(dir, config).context("Failed to update .gitattributes")?;
+    Ok(())
+}
+
+/// Revision of new sources tracking revisions when none is given
+const DEFAULT_REVISION: &str = "main";
+
+/// Parses `key=value` options given on the command line
+///
+/// Values are TOML values, so numbers, booleans, arrays and inline tables keep
+/// their type; anything else is taken as a plain string.
+fn parse_option_assignments(assignments: &[String]) -> Result<SourceOptions> {
+    let mut options = SourceOptions::new();
+    for assignment in assignments {
+        let (key, value) = assignment.split_once('=').ok_or_else(|| {
+            anyhow::anyhow!("Option '{}' is not of the form key=value", assignment)
+        })?;
+
+        let value = format!("value = {}", value)
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string()));
+        options.insert(key.trim().to_string(), value);
     }
+    Ok(options)
 }
 
 /// Use case for including paths in a dependency
@@ -144,33 +208,134 @@ impl<C: ConfigurationRepository> IncludePathsUseCase<C> {
     }
 }
 
+/// Use case for removing a dependency along with its vendored content
+pub struct RemoveDependencyUseCase<C, L, F>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    F: FileSystemManager,
+{
+    config_repo: C,
+    lock_repo: L,
+    file_system_manager: F,
+}
+
+impl<C, L, F> RemoveDependencyUseCase<C, L, F>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    F: FileSystemManager,
+{
+    pub fn new(config_repo: C, lock_repo: L, file_system_manager: F) -> Self {
+        Self {
+            config_repo,
+            lock_repo,
+            file_system_manager,
+        }
+    }
+
+    pub fn execute(&self, dto: RemoveDependencyDto) -> Result<RemovedDependencyDto> {
+        // Load the configuration
+        let mut config = self
+            .config_repo
+            .load(&dto.config_path)
+            .context("Failed to load configuration")?;
+
+        let dep_idx = config
+            .dependencies
+            .iter()
+            .position(|d| d.name == dto.dependency_name)
+            .ok_or_else(|| anyhow::anyhow!("Dependency '{}' not found", dto.dependency_name))?;
+        let dependency = config.dependencies.remove(dep_idx);
+
+        // Drop the lock entry, remembering what was vendored
+        let lock_path = LockFile::path_for(&dto.config_path);
+        let mut lock = self
+            .lock_repo
+            .load(&lock_path)
+            .context("Failed to load lock file")?;
+        let locked = lock
+            .dependencies
+            .iter()
+            .position(|l| l.name == dependency.name)
+            .map(|idx| lock.dependencies.remove(idx));
+
+        // Only the files acdm vendored go, so anything else in the target stays
+        let mut files = vec![PathBuf::from(PROVENANCE_FILE)];
+        for file in locked.iter().flat_map(|l| l.files.keys()) {
+            let path = PathBuf::from(file);
+            if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+                return Err(anyhow::anyhow!(
+                    "Lock entry of '{}' lists '{}' outside its target",
+                    dependency.name,
+                    file
+                ));
+            }
+            files.push(path);
+        }
+
+        // Targets are relative to the configuration file
+        let repo_root = dto
+            .config_path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Failed to determine repository root"))?;
+        self.file_system_manager
+            .remove_files(&repo_root.join(&dependency.target_location), &files)
+            .context("Failed to remove vendored content")?;
+
+        self.config_repo
+            .save(&config, &dto.config_path)
+            .context("Failed to save configuration")?;
+        if locked.is_some() {
+            self.lock_repo
+                .save(&lock, &lock_path)
+                .context("Failed to save lock file")?;
+        }
+
+        sync_gitattributes(&dto.config_path, &config)?;
+
+        Ok(RemovedDependencyDto {
+            name: dependency.name,
+            repository_url: dependency.repository_url,
+            revision: dependency.revision,
+            commit: locked.and_then(|l| l.commit),
+            target_location: dependency.target_location.to_string_lossy().to_string(),
+        })
+    }
+}
+
 /// Use case for updating dependencies
-pub struct UpdateDependenciesUseCase<C, R, F, G>
+pub struct UpdateDependenciesUseCase<C, L, R, F, G>
 where
     C: ConfigurationRepository,
+    L: LockFileRepository,
     R: RepositoryFetcher,
     F: FileSystemManager,
     G: GitOperations,
 {
     config_repo: C,
+    lock_repo: L,
     dependency_manager: DependencyManager<R, F, G>,
 }
 
-impl<C, R, F, G> UpdateDependenciesUseCase<C, R, F, G>
+impl<C, L, R, F, G> UpdateDependenciesUseCase<C, L, R, F, G>
 where
     C: ConfigurationRepository,
+    L: LockFileRepository,
     R: RepositoryFetcher,
     F: FileSystemManager,
     G: GitOperations,
 {
     pub fn new(
         config_repo: C,
+        lock_repo: L,
         repository_fetcher: R,
         file_system_manager: F,
         git_operations: G,
     ) -> Self {
         Self {
             config_repo,
+            lock_repo,
             dependency_manager: DependencyManager::new(
                 repository_fetcher,
                 file_system_manager,
@@ -179,15 +344,186 @@ where
         }
     }
 
-    pub fn execute(&self, dto: UpdateDependenciesDto) -> Result<()> {
-        // Load the configuration
+    /// Fetch through the configured mirrors before the canonical URLs
+    pub fn with_mirrors(mut self, mirrors: Mirrors) -> Self {
+        self.dependency_manager = self.dependency_manager.with_mirrors(mirrors);
+        self
+    }
+
+    pub fn execute(&self, dto: UpdateDependenciesDto) -> Result<Vec<UpdatedDependencyDto>> {
+        self.execute_with_progress(dto, |_| {})
+    }
+
+    /// Updates dependencies, reporting progress events as each one is processed
+    pub fn execute_with_progress(
+        &self,
+        dto: UpdateDependenciesDto,
+        mut on_event: impl FnMut(UpdateEventDto),
+    ) -> Result<Vec<UpdatedDependencyDto>> {
+        let (config, dependencies_to_update, mut lock) = self.load(&dto)?;
+
+        // Get the repository root (the directory containing the config file)
+        let repo_root = dto
+            .config_path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Failed to determine repository root"))?;
+        let lock_path = LockFile::path_for(&dto.config_path);
+
+        // Update all dependencies
+        let outcomes = self
+            .dependency_manager
+            .update_all(
+                &dependencies_to_update,
+                repo_root,
+                &lock,
+                dto.local_changes,
+                dto.refuse_conflicts,
+                &mut |progress| {
+                    on_event(match progress {
+                        UpdateProgress::Started(dep) => UpdateEventDto::DependencyStarted {
+                            name: dep.name.clone(),
+                        },
+                        UpdateProgress::Finished(outcome) => {
+                            UpdateEventDto::DependencyUpdated(updated_dto(outcome))
+                        }
+                    })
+                },
+            )
+            .map_err(|e| anyhow::anyhow!("Failed to update dependencies: {}", e))?;
+        let updated = outcomes.iter().map(updated_dto).collect();
+
+        // Record the resolved revisions, dropping entries for removed dependencies
+        for outcome in outcomes {
+            lock.upsert(outcome.locked);
+        }
+        lock.dependencies
+            .retain(|l| config.dependencies.iter().any(|d| d.name == l.name));
+
+        self.lock_repo
+            .save(&lock, &lock_path)
+            .context("Failed to save lock file")?;
+
+        // Targets may have been edited or removed in the configuration since
+        sync_gitattributes(&dto.config_path, &config)?;
+
+        Ok(updated)
+    }
+
+    /// Fails when the update would leave kept local modifications in
+    /// conflict with upstream changes, without changing anything
+    pub fn check_conflicts(&self, dto: &UpdateDependenciesDto) -> Result<()> {
+        if dto.local_changes != LocalChanges::Keep {
+            return Ok(());
+        }
+
+        let (_, dependencies, lock) = self.load(dto)?;
+        let repo_root = dto
+            .config_path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Failed to determine repository root"))?;
+        self.dependency_manager
+            .check_conflicts(&dependencies, repo_root, &lock)
+            .map_err(|e| anyhow::anyhow!("Failed to update dependencies: {}", e))
+    }
+
+    /// Loads the configuration, the dependencies selected by `dto` and the
+    /// lock file, against whose manifests local modifications are detected
+    fn load(
+        &self,
+        dto: &UpdateDependenciesDto,
+    ) -> Result<(Configuration, Vec<Dependency>, LockFile)> {
         let config = self
             .config_repo
             .load(&dto.config_path)
             .context("Failed to load configuration")?;
 
         // Get the dependencies to update
-        let dependencies_to_update = if let Some(dep_names) = dto.dependencies {
+        let dependencies = match &dto.dependencies {
+            Some(dep_names) => config
+                .dependencies
+                .iter()
+                .filter(|d| dep_names.contains(&d.name))
+                .cloned()
+                .collect::<Vec<_>>(),
+            None => config.dependencies.clone(),
+        };
+        if dependencies.is_empty() {
+            return Err(anyhow::anyhow!("No dependencies found to update"));
+        }
+
+        let lock = self
+            .lock_repo
+            .load(&LockFile::path_for(&dto.config_path))
+            .context("Failed to load lock file")?;
+        Ok((config, dependencies, lock))
+    }
+}
+
+fn updated_dto(outcome: &UpdateOutcome) -> UpdatedDependencyDto {
+    let locked = &outcome.locked;
+    UpdatedDependencyDto {
+        name: locked.name.clone(),
+        repository_url: locked.repository_url.clone(),
+        mirror: locked.mirror.clone(),
+        revision: locked.revision.clone(),
+        commit: locked.commit.clone(),
+        target_location: locked.target_location.to_string_lossy().to_string(),
+        files: locked.files.len(),
+        kept_local: outcome.kept_local.clone(),
+        conflicts: outcome.conflicts.clone(),
+    }
+}
+
+/// Use case for previewing the changes an update would make
+pub struct DiffDependenciesUseCase<C, R, F, G>
+where
+    C: ConfigurationRepository,
+    R: RepositoryFetcher,
+    F: FileSystemManager,
+    G: GitOperations,
+{
+    config_repo: C,
+    dependency_manager: DependencyManager<R, F, G>,
+}
+
+impl<C, R, F, G> DiffDependenciesUseCase<C, R, F, G>
+where
+    C: ConfigurationRepository,
+    R: RepositoryFetcher,
+    F: FileSystemManager,
+    G: GitOperations,
+{
+    pub fn new(
+        config_repo: C,
+        repository_fetcher: R,
+        file_system_manager: F,
+        git_operations: G,
+    ) -> Self {
+        Self {
+            config_repo,
+            dependency_manager: DependencyManager::new(
+                repository_fetcher,
+                file_system_manager,
+                git_operations,
+            ),
+        }
+    }
+
+    /// Fetch through the configured mirrors before the canonical URLs
+    pub fn with_mirrors(mut self, mirrors: Mirrors) -> Self {
+        self.dependency_manager = self.dependency_manager.with_mirrors(mirrors);
+        self
+    }
+
+    pub fn execute(&self, dto: DiffDependenciesDto) -> Result<Vec<DependencyDiffDto>> {
+        // Load the configuration
+        let config = self
+            .config_repo
+            .load(&dto.config_path)
+            .context("Failed to load configuration")?;
+
+        // Get the dependencies to preview
+        let dependencies_to_preview = if let Some(dep_names) = dto.dependencies {
             config
                 .dependencies
                 .iter()
@@ -198,8 +534,8 @@ where
             config.dependencies.clone()
         };
 
-        if dependencies_to_update.is_empty() {
-            return Err(anyhow::anyhow!("No dependencies found to update"));
+        if dependencies_to_preview.is_empty() {
+            return Err(anyhow::anyhow!("No dependencies found to preview"));
         }
 
         // Get the repository root (the directory containing the config file)
@@ -208,11 +544,447 @@ where
             .parent()
             .ok_or_else(|| anyhow::anyhow!("Failed to determine repository root"))?;
 
-        // Update all dependencies
-        self.dependency_manager
-            .update_all(&dependencies_to_update, repo_root, dto.force)
-            .map_err(|e| anyhow::anyhow!("Failed to update dependencies: {}", e))?;
+        let diffs = self
+            .dependency_manager
+            .preview_all(&dependencies_to_preview, repo_root, dto.text_diffs)
+            .map_err(|e| anyhow::anyhow!("Failed to preview dependencies: {}", e))?;
+
+        Ok(diffs
+            .into_iter()
+            .map(|diff| DependencyDiffDto {
+                name: diff.name,
+                target_location: diff.target_location.to_string_lossy().to_string(),
+                changes: diff
+                    .changes
+                    .into_iter()
+                    .map(|change| FileChangeDto {
+                        path: change.path.to_string_lossy().to_string(),
+                        change: change.kind,
+                        diff: change.text_diff,
+                    })
+                    .collect(),
+                transforms: diff
+                    .transforms
+                    .into_iter()
+                    .map(|report| TransformStepDto {
+                        step: report.step,
+                        files: report
+                            .files
+                            .iter()
+                            .map(|file| file.to_string_lossy().to_string())
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+}
 
-        Ok(())
+/// Directory of the patches `patch create` writes for a dependency by default
+pub const PATCHES_DIR: &str = "patches";
+
+/// Use case for turning the local modifications of a target into a patch
+pub struct CreatePatchUseCase<C, L, R, F, G>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    R: RepositoryFetcher,
+    F: FileSystemManager,
+    G: GitOperations,
+{
+    config_repo: C,
+    lock_repo: L,
+    dependency_manager: DependencyManager<R, F, G>,
+}
+
+impl<C, L, R, F, G> CreatePatchUseCase<C, L, R, F, G>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    R: RepositoryFetcher,
+    F: FileSystemManager,
+    G: GitOperations,
+{
+    pub fn new(
+        config_repo: C,
+        lock_repo: L,
+        repository_fetcher: R,
+        file_system_manager: F,
+        git_operations: G,
+    ) -> Self {
+        Self {
+            config_repo,
+            lock_repo,
+            dependency_manager: DependencyManager::new(
+                repository_fetcher,
+                file_system_manager,
+                git_operations,
+            ),
+        }
+    }
+
+    /// Fetch through the configured mirrors before the canonical URLs
+    pub fn with_mirrors(mut self, mirrors: Mirrors) -> Self {
+        self.dependency_manager = self.dependency_manager.with_mirrors(mirrors);
+        self
+    }
+
+    pub fn execute(&self, dto: CreatePatchDto) -> Result<CreatedPatchDto> {
+        // Load the configuration and the lock file
+        let mut config = self
+            .config_repo
+            .load(&dto.config_path)
+            .context("Failed to load configuration")?;
+        let lock_path = LockFile::path_for(&dto.config_path);
+        let mut lock = self
+            .lock_repo
+            .load(&lock_path)
+            .context("Failed to load lock file")?;
+
+        let dep_idx = config
+            .dependencies
+            .iter()
+            .position(|d| d.name == dto.dependency_name)
+            .ok_or_else(|| anyhow::anyhow!("Dependency '{}' not found", dto.dependency_name))?;
+        let dependency = config.dependencies[dep_idx].clone();
+        let mut locked = lock.get(&dependency.name).cloned().ok_or_else(|| {
+            anyhow::anyhow!(
+                "'{}' has not been vendored yet. Run 'acdm update {}' first.",
+                dependency.name,
+                dependency.name
+            )
+        })?;
+
+        // Patch files are relative to the configuration file
+        let repo_root = dto
+            .config_path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Failed to determine repository root"))?;
+
+        let patch_text = self
+            .dependency_manager
+            .create_patch(&dependency, &locked, repo_root)
+            .context("Failed to create patch")?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "'{}' has no local modifications to turn into a patch",
+                    dependency.name
+                )
+            })?;
+
+        let relative = match dto.output {
+            Some(output) => output,
+            None => next_patch_path(repo_root, &dependency.name)?,
+        };
+        let patch_path = repo_root.join(&relative);
+        if patch_path.exists() {
+            return Err(anyhow::anyhow!(
+                "Patch file {} already exists",
+                patch_path.display()
+            ));
+        }
+        if let Some(parent) = patch_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&patch_path, &patch_text)
+            .with_context(|| format!("Failed to write {}", patch_path.display()))?;
+
+        // List the patch unless the source's patterns already match it
+        let relative = patch_path
+            .strip_prefix(repo_root)
+            .unwrap_or(&relative)
+            .to_string_lossy()
+            .to_string();
+        let listed = patch::resolve(repo_root, &dependency.patches)
+            .context("Failed to resolve patches")?
+            .contains(&patch_path);
+        if !listed {
+            config.dependencies[dep_idx].patches.push(relative.clone());
+            self.config_repo
+                .save(&config, &dto.config_path)
+                .context("Failed to save configuration")?;
+        }
+
+        // The provenance file lists the new patch with the others
+        let target_path = repo_root.join(&dependency.target_location);
+        let patches: Vec<PathBuf> =
+            patch::resolve(repo_root, &config.dependencies[dep_idx].patches)
+                .context("Failed to resolve patches")?
+                .iter()
+                .map(|path| path.strip_prefix(repo_root).unwrap_or(path).to_path_buf())
+                .collect();
+        Provenance::new(&config.dependencies[dep_idx], &locked)
+            .with_patches(&patches)
+            .write(&target_path)
+            .context("Failed to update the provenance file")?;
+
+        // The target now holds what the patches produce, so it is no longer modified
+        locked.patches = patch::fingerprint(repo_root, &patches)?;
+        let files = locked.files.clone();
+        locked.files = manifest::compute_manifest(&target_path)?;
+        let changed = locked
+            .files
+            .iter()
+            .filter(|(path, hash)| files.get(*path) != Some(*hash))
+            .map(|(path, _)| path.clone())
+            .chain(
+                files
+                    .keys()
+                    .filter(|path| !locked.files.contains_key(*path))
+                    .cloned(),
+            )
+            .collect();
+        lock.upsert(locked);
+        self.lock_repo
+            .save(&lock, &lock_path)
+            .context("Failed to save lock file")?;
+
+        Ok(CreatedPatchDto {
+            name: dependency.name,
+            path: relative,
+            files: changed,
+        })
+    }
+}
+
+/// Next free `patches/<name>/NNNN.patch`, relative to the repository root
+fn next_patch_path(repo_root: &Path, name: &str) -> Result<PathBuf> {
+    let dir = Path::new(PATCHES_DIR).join(name);
+    let mut last = 0;
+    if let Ok(entries) = std::fs::read_dir(repo_root.join(&dir)) {
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let number = file_name
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|digits| digits.parse::<u32>().ok());
+            if let Some(number) = number {
+                last = last.max(number);
+            }
+        }
+    }
+    Ok(dir.join(format!("{:04}.patch", last + 1)))
+}
+
+/// Use case for exporting the changes made to a dependency as a patch series
+pub struct ExportChangesUseCase<C, L, R, F, G>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    R: RepositoryFetcher,
+    F: FileSystemManager,
+    G: GitOperations,
+{
+    config_repo: C,
+    lock_repo: L,
+    dependency_manager: DependencyManager<R, F, G>,
+}
+
+impl<C, L, R, F, G> ExportChangesUseCase<C, L, R, F, G>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    R: RepositoryFetcher,
+    F: FileSystemManager,
+    G: GitOperations,
+{
+    pub fn new(
+        config_repo: C,
+        lock_repo: L,
+        repository_fetcher: R,
+        file_system_manager: F,
+        git_operations: G,
+    ) -> Self {
+        Self {
+            config_repo,
+            lock_repo,
+            dependency_manager: DependencyManager::new(
+                repository_fetcher,
+                file_system_manager,
+                git_operations,
+            ),
+        }
+    }
+
+    /// Fetch through the configured mirrors before the canonical URLs
+    pub fn with_mirrors(mut self, mirrors: Mirrors) -> Self {
+        self.dependency_manager = self.dependency_manager.with_mirrors(mirrors);
+        self
+    }
+
+    pub fn execute(&self, dto: ExportChangesDto) -> Result<ExportedChangesDto> {
+        // Load the configuration and the lock file
+        let config = self
+            .config_repo
+            .load(&dto.config_path)
+            .context("Failed to load configuration")?;
+        let lock = self
+            .lock_repo
+            .load(&LockFile::path_for(&dto.config_path))
+            .context("Failed to load lock file")?;
+
+        let dependency = config
+            .dependencies
+            .iter()
+            .find(|d| d.name == dto.dependency_name)
+            .ok_or_else(|| anyhow::anyhow!("Dependency '{}' not found", dto.dependency_name))?;
+        let locked = lock.get(&dependency.name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "'{}' has not been vendored yet. Run 'acdm update {}' first.",
+                dependency.name,
+                dependency.name
+            )
+        })?;
+        let base_commit = locked.commit.clone().ok_or_else(|| {
+            anyhow::anyhow!(
+                "'{}' has no locked upstream commit to export changes against",
+                dependency.name
+            )
+        })?;
+
+        let repo_root = dto
+            .config_path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Failed to determine repository root"))?;
+
+        let exported = self
+            .dependency_manager
+            .export_changes(dependency, locked, repo_root)
+            .context("Failed to export changes")?;
+        let series = export::format_series(&exported, &dto.author, &base_commit, SystemTime::now());
+
+        if let Some(output_dir) = &dto.output_dir {
+            std::fs::create_dir_all(output_dir)
+                .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+        }
+
+        let mut patches = Vec::new();
+        for (patch, (file_name, content)) in exported.iter().zip(series) {
+            let (path, content) = match &dto.output_dir {
+                Some(output_dir) => {
+                    let path = output_dir.join(&file_name);
+                    std::fs::write(&path, &content)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    (path.to_string_lossy().to_string(), None)
+                }
+                None => (file_name, Some(content)),
+            };
+            patches.push(ExportedPatchDto {
+                path,
+                subject: patch.subject.clone(),
+                files: patch
+                    .files
+                    .iter()
+                    .map(|file| file.to_string_lossy().to_string())
+                    .collect(),
+                content,
+            });
+        }
+
+        Ok(ExportedChangesDto {
+            name: dependency.name.clone(),
+            repository_url: dependency.repository_url.clone(),
+            base_commit,
+            patches,
+        })
+    }
+}
+
+/// Use case for listing upstream commits between the vendored and a new revision
+pub struct LogDependencyUseCase<C, L, H>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    H: RepositoryHistory + RepositoryFetcher,
+{
+    config_repo: C,
+    lock_repo: L,
+    history: H,
+    mirrors: Mirrors,
+}
+
+impl<C, L, H> LogDependencyUseCase<C, L, H>
+where
+    C: ConfigurationRepository,
+    L: LockFileRepository,
+    H: RepositoryHistory + RepositoryFetcher,
+{
+    pub fn new(config_repo: C, lock_repo: L, history: H) -> Self {
+        Self {
+            config_repo,
+            lock_repo,
+            history,
+            mirrors: Mirrors::default(),
+        }
+    }
+
+    /// Read the history through the configured mirrors before the canonical URL
+    pub fn with_mirrors(mut self, mirrors: Mirrors) -> Self {
+        self.mirrors = mirrors;
+        self
+    }
+
+    pub fn execute(&self, dto: LogDependencyDto) -> Result<DependencyLogDto> {
+        // Load the configuration and the lock file
+        let config = self
+            .config_repo
+            .load(&dto.config_path)
+            .context("Failed to load configuration")?;
+        let lock = self
+            .lock_repo
+            .load(&LockFile::path_for(&dto.config_path))
+            .context("Failed to load lock file")?;
+
+        // Find the dependency by name
+        let dependency = config
+            .dependencies
+            .iter()
+            .find(|d| d.name == dto.dependency_name)
+            .ok_or_else(|| anyhow::anyhow!("Dependency '{}' not found", dto.dependency_name))?;
+
+        if !self.history.tracks_revisions(dependency) {
+            return Err(anyhow::anyhow!(
+                "'{}' is a {} source; upstream history is only available for sources tracking revisions, such as git",
+                dependency.name,
+                dependency.repository_type
+            ));
+        }
+
+        // The currently vendored revision comes from the lock file
+        let from = lock
+            .get(&dependency.name)
+            .and_then(|l| l.commit.clone())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No locked commit for '{}'. Run 'acdm update {}' first.",
+                    dependency.name,
+                    dependency.name
+                )
+            })?;
+        let to = dto.to.unwrap_or_else(|| dependency.revision.clone());
+
+        let (commits, _) = self
+            .mirrors
+            .try_each(&dependency.repository_url, |url| {
+                self.history.log(url, &from, &to, &dependency.sparse_paths)
+            })
+            .map_err(|e| anyhow::anyhow!("Failed to read upstream history: {}", e))?;
+
+        Ok(DependencyLogDto {
+            name: dependency.name.clone(),
+            repository_url: dependency.repository_url.clone(),
+            from,
+            to,
+            commits: commits
+                .into_iter()
+                .map(|c| CommitDto {
+                    id: c.id,
+                    author: c.author,
+                    date: c.date,
+                    subject: c.subject,
+                })
+                .collect(),
+        })
     }
 }
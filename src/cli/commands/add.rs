@@ -5,7 +5,9 @@ use clap::Args;
 use log::{debug, info};
 use std::path::Path;
 
-use crate::interfaces::cli::CliAdapter;
+use crate::domain::auth::AuthenticationService;
+use crate::domain::DEFAULT_SOURCE_TYPE;
+use crate::interfaces::cli::{CliAdapter, SafetyOverrides};
 
 /// Add a new dependency
 #[derive(Args)]
@@ -17,23 +19,36 @@ pub struct AddCommand {
     #[clap(long)]
     name: String,
 
-    /// Revision (branch, tag, or commit)
-    #[clap(long, default_value = "main")]
-    rev: String,
+    /// Revision (branch, tag, or commit); defaults to "main" for types tracking revisions
+    #[clap(long)]
+    rev: Option<String>,
 
     /// Target location for the dependency
     #[clap(long)]
     target: String,
+
+    /// Source type, such as 'git', 'path', 'archive' or 'http'
+    #[clap(long = "type", default_value = DEFAULT_SOURCE_TYPE)]
+    source_type: String,
+
+    /// Option of the source type as KEY=VALUE, written to its [sources.<type>] block
+    #[clap(long = "option", value_name = "KEY=VALUE")]
+    options: Vec<String>,
+
+    /// Commit the configuration, lock file and affected targets afterwards
+    #[clap(long)]
+    commit: bool,
 }
 
 impl AddCommand {
-    pub fn execute(&self, config_path: &Path, force: bool) -> Result<()> {
+    pub fn execute(&self, config_path: &Path, overrides: SafetyOverrides) -> Result<()> {
         info!(
             "Adding dependency '{}' from {}",
-            self.name, self.repository_url
+            self.name,
+            AuthenticationService::redact_url(&self.repository_url)
         );
-        debug!("Using revision: {}, target: {}", self.rev, self.target);
-        debug!("Force mode: {}", force);
+        debug!("Using revision: {:?}, target: {}", self.rev, self.target);
+        debug!("Safety overrides: {:?}", overrides);
 
         let adapter = CliAdapter::new(config_path.to_path_buf());
         adapter.add_dependency(
@@ -41,7 +56,10 @@ impl AddCommand {
             self.repository_url.clone(),
             self.rev.clone(),
             self.target.clone(),
-            force,
+            self.source_type.clone(),
+            self.options.clone(),
+            overrides,
+            self.commit,
         )?;
 
         info!("Dependency added successfully");
@@ -0,0 +1,34 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use anyhow::Result;
+use clap::Args;
+use log::info;
+use std::path::{Path, PathBuf};
+
+use crate::interfaces::cli::CliAdapter;
+use crate::interfaces::output::OutputFormat;
+
+/// Check configuration, lock file and vendored content for consistency
+#[derive(Args)]
+pub struct CheckCommand {
+    /// Exit with a distinct code per failure class
+    #[clap(long)]
+    ci: bool,
+
+    /// Write the results as JUnit XML to this path
+    #[clap(long)]
+    junit: Option<PathBuf>,
+
+    /// Also check whether configured revisions moved upstream (requires network)
+    #[clap(long)]
+    remote: bool,
+}
+
+impl CheckCommand {
+    pub fn execute(&self, config_path: &Path, format: OutputFormat) -> Result<()> {
+        info!("Checking dependencies");
+
+        let adapter = CliAdapter::new(config_path.to_path_buf()).with_output_format(format);
+        adapter.check(self.ci, self.junit.clone(), self.remote)
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use anyhow::Result;
+use clap::Args;
+use log::{debug, info};
+use std::path::Path;
+
+use crate::interfaces::cli::CliAdapter;
+use crate::interfaces::output::OutputFormat;
+
+/// Preview the changes an update would make
+#[derive(Args)]
+pub struct DiffCommand {
+    /// Dependencies to preview (all if not specified)
+    dependencies: Vec<String>,
+
+    /// Show unified text diffs for changed files
+    #[clap(long)]
+    text: bool,
+}
+
+impl DiffCommand {
+    pub fn execute(&self, config_path: &Path, format: OutputFormat) -> Result<()> {
+        info!("Previewing dependency changes");
+        debug!("Using config file: {}", config_path.display());
+
+        let adapter = CliAdapter::new(config_path.to_path_buf()).with_output_format(format);
+        let deps = if self.dependencies.is_empty() {
+            None
+        } else {
+            Some(self.dependencies.clone())
+        };
+
+        adapter.diff_dependencies(deps, self.text)?;
+
+        Ok(())
+    }
+}
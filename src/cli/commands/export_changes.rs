@@ -0,0 +1,54 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use anyhow::Result;
+use clap::Args;
+use log::{debug, info};
+use std::path::{Path, PathBuf};
+
+use crate::interfaces::cli::CliAdapter;
+use crate::interfaces::output::OutputFormat;
+
+/// Export the changes made to vendored content as a patch series for upstream
+#[derive(Args)]
+pub struct ExportChangesCommand {
+    /// Name of the dependency
+    dependency_name: String,
+
+    /// Directory to write the patches to (defaults to the current directory)
+    #[clap(short, long, conflicts_with = "stdout")]
+    output_dir: Option<PathBuf>,
+
+    /// Print the series instead of writing patch files
+    #[clap(long)]
+    stdout: bool,
+
+    /// Author of the patches, as "Name <email>" (defaults to the git identity)
+    #[clap(long)]
+    author: Option<String>,
+}
+
+impl ExportChangesCommand {
+    pub fn execute(&self, config_path: &Path, format: OutputFormat) -> Result<()> {
+        info!("Exporting changes to '{}'", self.dependency_name);
+        debug!("Output directory: {:?}", self.output_dir);
+
+        let output_dir = if self.stdout {
+            None
+        } else {
+            Some(
+                self.output_dir
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(".")),
+            )
+        };
+
+        let adapter = CliAdapter::new(config_path.to_path_buf()).with_output_format(format);
+        adapter.export_changes(
+            self.dependency_name.clone(),
+            output_dir,
+            self.author.clone(),
+        )?;
+
+        Ok(())
+    }
+}
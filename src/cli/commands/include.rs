@@ -5,7 +5,7 @@ use clap::Args;
 use log::{debug, info};
 use std::path::Path;
 
-use crate::interfaces::cli::CliAdapter;
+use crate::interfaces::cli::{CliAdapter, SafetyOverrides};
 
 /// Include paths in a dependency
 #[derive(Args)]
@@ -15,16 +15,25 @@ pub struct IncludeCommand {
 
     /// Paths to include
     paths: Vec<String>,
+
+    /// Commit the configuration afterwards
+    #[clap(long)]
+    commit: bool,
 }
 
 impl IncludeCommand {
-    pub fn execute(&self, config_path: &Path, force: bool) -> Result<()> {
+    pub fn execute(&self, config_path: &Path, overrides: SafetyOverrides) -> Result<()> {
         info!("Including paths in dependency '{}'", self.dependency_name);
         debug!("Paths: {:?}", self.paths);
-        debug!("Force mode: {}", force);
+        debug!("Safety overrides: {:?}", overrides);
 
         let adapter = CliAdapter::new(config_path.to_path_buf());
-        adapter.include_paths(self.dependency_name.clone(), self.paths.clone(), force)?;
+        adapter.include_paths(
+            self.dependency_name.clone(),
+            self.paths.clone(),
+            overrides,
+            self.commit,
+        )?;
 
         info!("Paths included successfully");
         Ok(())
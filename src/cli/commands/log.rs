@@ -0,0 +1,36 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use anyhow::Result;
+use clap::Args;
+use log::{debug, info};
+use std::path::Path;
+
+use crate::interfaces::cli::CliAdapter;
+use crate::interfaces::output::OutputFormat;
+
+/// Show upstream commits between the vendored and a new revision
+#[derive(Args)]
+pub struct LogCommand {
+    /// Name of the dependency
+    dependency_name: String,
+
+    /// Target revision (defaults to the configured revision)
+    #[clap(long)]
+    to: Option<String>,
+
+    /// Emit a markdown block suitable for commit messages
+    #[clap(long)]
+    markdown: bool,
+}
+
+impl LogCommand {
+    pub fn execute(&self, config_path: &Path, _force: bool, format: OutputFormat) -> Result<()> {
+        info!("Showing upstream log for '{}'", self.dependency_name);
+        debug!("Target revision: {:?}", self.to);
+
+        let adapter = CliAdapter::new(config_path.to_path_buf()).with_output_format(format);
+        adapter.log_dependency(self.dependency_name.clone(), self.to.clone(), self.markdown)?;
+
+        Ok(())
+    }
+}
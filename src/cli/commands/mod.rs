@@ -1,7 +1,15 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
 pub mod add;
+pub mod check;
+pub mod diff;
+pub mod export_changes;
 pub mod include;
 pub mod init;
+pub mod log;
+pub mod outdated;
+pub mod patch;
+pub mod remove;
 pub mod status;
 pub mod update;
+pub mod verify;
@@ -0,0 +1,31 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use anyhow::Result;
+use clap::Args;
+use log::info;
+use std::path::Path;
+
+use crate::interfaces::cli::CliAdapter;
+use crate::interfaces::output::OutputFormat;
+
+/// List dependencies whose upstream revision has moved
+#[derive(Args)]
+pub struct OutdatedCommand {
+    /// Dependencies to check (all if not specified)
+    dependencies: Vec<String>,
+}
+
+impl OutdatedCommand {
+    pub fn execute(&self, config_path: &Path, format: OutputFormat) -> Result<()> {
+        info!("Checking for outdated dependencies");
+
+        let adapter = CliAdapter::new(config_path.to_path_buf()).with_output_format(format);
+        let deps = if self.dependencies.is_empty() {
+            None
+        } else {
+            Some(self.dependencies.clone())
+        };
+
+        adapter.show_outdated(deps)
+    }
+}
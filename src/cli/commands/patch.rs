@@ -0,0 +1,49 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use log::{debug, info};
+use std::path::{Path, PathBuf};
+
+use crate::interfaces::cli::CliAdapter;
+use crate::interfaces::output::OutputFormat;
+
+/// Manage patches applied on top of vendored content
+#[derive(Args)]
+pub struct PatchCommand {
+    #[clap(subcommand)]
+    action: PatchAction,
+}
+
+#[derive(Subcommand)]
+enum PatchAction {
+    /// Turn the local modifications of a dependency into a new patch file
+    Create {
+        /// Name of the dependency
+        dependency_name: String,
+
+        /// Patch file to write, relative to the configuration file
+        /// (defaults to patches/<name>/NNNN.patch)
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+impl PatchCommand {
+    pub fn execute(&self, config_path: &Path, format: OutputFormat) -> Result<()> {
+        match &self.action {
+            PatchAction::Create {
+                dependency_name,
+                output,
+            } => {
+                info!("Creating patch for '{}'", dependency_name);
+                debug!("Patch output: {:?}", output);
+
+                let adapter = CliAdapter::new(config_path.to_path_buf()).with_output_format(format);
+                adapter.create_patch(dependency_name.clone(), output.clone())?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use anyhow::Result;
+use clap::Args;
+use log::{debug, info};
+use std::path::Path;
+
+use crate::interfaces::cli::{CliAdapter, SafetyOverrides};
+
+/// Remove a dependency and its vendored content
+#[derive(Args)]
+pub struct RemoveCommand {
+    /// Name of the dependency
+    dependency_name: String,
+
+    /// Commit the configuration, lock file and removed target afterwards
+    #[clap(long)]
+    commit: bool,
+}
+
+impl RemoveCommand {
+    pub fn execute(&self, config_path: &Path, overrides: SafetyOverrides) -> Result<()> {
+        info!("Removing dependency '{}'", self.dependency_name);
+        debug!("Safety overrides: {:?}", overrides);
+
+        let adapter = CliAdapter::new(config_path.to_path_buf());
+        adapter.remove_dependency(self.dependency_name.clone(), overrides, self.commit)?;
+
+        info!("Dependency removed successfully");
+        Ok(())
+    }
+}
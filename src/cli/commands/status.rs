@@ -6,6 +6,7 @@ use log::info;
 use std::path::Path;
 
 use crate::interfaces::cli::CliAdapter;
+use crate::interfaces::output::OutputFormat;
 
 /// Display dependency status
 #[derive(Args)]
@@ -16,20 +17,24 @@ pub struct StatusCommand {
     /// Show details about each dependency
     #[clap(short, long)]
     detailed: bool,
+
+    /// Check whether the configured revisions moved upstream (requires network)
+    #[clap(long)]
+    remote: bool,
 }
 
 impl StatusCommand {
-    pub fn execute(&self, config_path: &Path, _force: bool) -> Result<()> {
+    pub fn execute(&self, config_path: &Path, _force: bool, format: OutputFormat) -> Result<()> {
         info!("Displaying dependency status");
 
-        let adapter = CliAdapter::new(config_path.to_path_buf());
+        let adapter = CliAdapter::new(config_path.to_path_buf()).with_output_format(format);
         let deps = if self.dependencies.is_empty() {
             None
         } else {
             Some(self.dependencies.clone())
         };
 
-        adapter.show_dependency_status(deps, self.detailed)?;
+        adapter.show_dependency_status(deps, self.detailed, self.remote)?;
 
         Ok(())
     }
@@ -4,18 +4,49 @@ use anyhow::Result;
 use clap::Args;
 use log::{debug, info};
 use std::path::Path;
+use std::time::SystemTime;
 
-use crate::interfaces::cli::CliAdapter;
+use crate::domain::time::{civil_date, unix_seconds};
+use crate::domain::LocalChanges;
+use crate::interfaces::cli::{CliAdapter, SafetyOverrides};
+use crate::interfaces::output::OutputFormat;
 
 /// Update dependencies
 #[derive(Args)]
 pub struct UpdateCommand {
     /// Dependencies to update (all if not specified)
     dependencies: Vec<String>,
+
+    /// Show what would change without touching the working tree
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Commit the configuration, lock file and updated targets afterwards
+    #[clap(long, conflicts_with = "dry_run")]
+    commit: bool,
+
+    /// Switch to a new branch first and commit each dependency on it
+    /// (default name: acdm/update-<date>)
+    #[clap(long, value_name = "NAME", num_args = 0..=1, conflicts_with = "dry_run")]
+    branch: Option<Option<String>>,
+
+    /// Carry local modifications of vendored files forward, merging them with
+    /// upstream changes and marking conflicts
+    #[clap(long, conflicts_with = "dry_run")]
+    keep_local: bool,
+
+    /// Overwrite local modifications of vendored files with the upstream content
+    #[clap(long, conflicts_with_all = ["dry_run", "keep_local"])]
+    discard_local: bool,
 }
 
 impl UpdateCommand {
-    pub fn execute(&self, config_path: &Path, force: bool) -> Result<()> {
+    pub fn execute(
+        &self,
+        config_path: &Path,
+        overrides: SafetyOverrides,
+        format: OutputFormat,
+    ) -> Result<()> {
         if self.dependencies.is_empty() {
             info!("Updating all dependencies");
         } else {
@@ -23,19 +54,48 @@ impl UpdateCommand {
         }
 
         debug!("Using config file: {}", config_path.display());
-        debug!("Force mode: {}", force);
+        debug!("Safety overrides: {:?}", overrides);
 
-        let adapter = CliAdapter::new(config_path.to_path_buf());
+        let adapter = CliAdapter::new(config_path.to_path_buf()).with_output_format(format);
         let deps = if self.dependencies.is_empty() {
             None
         } else {
             Some(self.dependencies.clone())
         };
 
-        adapter.update_dependencies(deps, force)?;
+        let branch = self.branch.clone().map(|name| {
+            name.unwrap_or_else(|| {
+                let seconds = unix_seconds(SystemTime::now());
+                let (year, month, day) = civil_date(seconds.div_euclid(86_400));
+                format!("acdm/update-{:04}-{:02}-{:02}", year, month, day)
+            })
+        });
+
+        let local_changes = if self.keep_local {
+            LocalChanges::Keep
+        } else if self.discard_local {
+            LocalChanges::Discard
+        } else {
+            LocalChanges::Refuse
+        };
+
+        adapter.update_dependencies(
+            deps,
+            overrides,
+            self.dry_run,
+            self.commit,
+            branch,
+            local_changes,
+        )?;
+
+        if self.dry_run {
+            return Ok(());
+        }
 
         info!("Dependencies updated successfully");
-        info!("Remember to review and commit your changes with git");
+        if !self.commit && self.branch.is_none() {
+            info!("Remember to review and commit your changes with git");
+        }
         Ok(())
     }
 }
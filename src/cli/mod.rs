@@ -9,9 +9,13 @@ use log::debug;
 use std::path::PathBuf;
 
 use crate::cli::commands::{
-    add::AddCommand, include::IncludeCommand, init::InitCommand, status::StatusCommand,
-    update::UpdateCommand,
+    add::AddCommand, check::CheckCommand, diff::DiffCommand, export_changes::ExportChangesCommand,
+    include::IncludeCommand, init::InitCommand, log::LogCommand, outdated::OutdatedCommand,
+    patch::PatchCommand, remove::RemoveCommand, status::StatusCommand, update::UpdateCommand,
+    verify::VerifyCommand,
 };
+use crate::interfaces::cli::SafetyOverrides;
+use crate::interfaces::output::OutputFormat;
 
 /// Wrale Agnostic Content Dependency Manager
 #[derive(Parser)]
@@ -25,10 +29,22 @@ struct Cli {
     #[clap(short, long, global = true)]
     quiet: bool,
 
-    /// Force operations without prompting
+    /// Force operations without prompting; implies --allow-dirty and --yes
     #[clap(short, long, global = true)]
     force: bool,
 
+    /// Proceed even when paths acdm modifies have uncommitted changes
+    #[clap(long, global = true)]
+    allow_dirty: bool,
+
+    /// Answer yes to confirmation prompts
+    #[clap(short, long, global = true)]
+    yes: bool,
+
+    /// Output format for command results
+    #[clap(long, value_enum, default_value = "text", global = true)]
+    format: OutputFormat,
+
     /// Subcommand to run
     #[clap(subcommand)]
     command: Commands,
@@ -45,11 +61,35 @@ enum Commands {
     /// Include paths in a dependency
     Include(IncludeCommand),
 
+    /// Remove a dependency and its vendored content
+    Remove(RemoveCommand),
+
     /// Update dependencies
     Update(UpdateCommand),
 
     /// Show status of dependencies
     Status(StatusCommand),
+
+    /// Preview the changes an update would make
+    Diff(DiffCommand),
+
+    /// Show upstream commits between the vendored and a new revision
+    Log(LogCommand),
+
+    /// Manage patches applied on top of vendored content
+    Patch(PatchCommand),
+
+    /// Export the changes made to vendored content as a patch series for upstream
+    ExportChanges(ExportChangesCommand),
+
+    /// Verify vendored content against the lock file
+    Verify(VerifyCommand),
+
+    /// List dependencies whose upstream revision has moved
+    Outdated(OutdatedCommand),
+
+    /// Check configuration, lock file and vendored content for consistency
+    Check(CheckCommand),
 }
 
 // Function moved to CliAdapter implementation
@@ -68,11 +108,21 @@ pub fn run() -> Result<()> {
 
     debug!("Starting acdm with config path: {}", cli.config.display());
 
+    let overrides = SafetyOverrides::from_flags(cli.force, cli.allow_dirty, cli.yes);
+
     match &cli.command {
         Commands::Init(cmd) => cmd.execute(&cli.config, cli.force),
-        Commands::Add(cmd) => cmd.execute(&cli.config, cli.force),
-        Commands::Include(cmd) => cmd.execute(&cli.config, cli.force),
-        Commands::Update(cmd) => cmd.execute(&cli.config, cli.force),
-        Commands::Status(cmd) => cmd.execute(&cli.config, cli.force),
+        Commands::Add(cmd) => cmd.execute(&cli.config, overrides),
+        Commands::Include(cmd) => cmd.execute(&cli.config, overrides),
+        Commands::Remove(cmd) => cmd.execute(&cli.config, overrides),
+        Commands::Update(cmd) => cmd.execute(&cli.config, overrides, cli.format),
+        Commands::Status(cmd) => cmd.execute(&cli.config, cli.force, cli.format),
+        Commands::Diff(cmd) => cmd.execute(&cli.config, cli.format),
+        Commands::Log(cmd) => cmd.execute(&cli.config, cli.force, cli.format),
+        Commands::Patch(cmd) => cmd.execute(&cli.config, cli.format),
+        Commands::ExportChanges(cmd) => cmd.execute(&cli.config, cli.format),
+        Commands::Verify(cmd) => cmd.execute(&cli.config, cli.format),
+        Commands::Outdated(cmd) => cmd.execute(&cli.config, cli.format),
+        Commands::Check(cmd) => cmd.execute(&cli.config, cli.format),
     }
 }
@@ -0,0 +1,104 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use crate::domain::auth::AuthenticationService;
+
+/// Trailer key recording each source a commit changed
+pub const SOURCE_TRAILER: &str = "Acdm-Source";
+
+/// Number of commit hash characters shown in the message body
+const SHORT_COMMIT_LENGTH: usize = 12;
+
+/// A source changed by a commit, with its locked commit before and after
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceChange {
+    pub name: String,
+    pub repository_url: String,
+    pub revision: String,
+    pub old_commit: Option<String>,
+    pub new_commit: Option<String>,
+}
+
+/// Message of a commit made by acdm
+///
+/// The body lists every changed source with its old and new commit, and a
+/// trailer per source repeats them in full for tools auditing the history:
+///
+/// ```text
+/// Acdm-Source: name=upstream repo=https://example.com/upstream.git rev=main old=<sha> new=<sha>
+/// ```
+///
+/// Missing values are written as `none`, and passwords in repository URLs
+/// are masked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitMessage {
+    subject: String,
+    changes: Vec<SourceChange>,
+}
+
+impl CommitMessage {
+    pub fn new(subject: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Records a source changed by the commit
+    pub fn with_change(mut self, change: SourceChange) -> Self {
+        self.changes.push(change);
+        self
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Formats the full message
+    pub fn render(&self) -> String {
+        let mut message = format!("{}\n", self.subject);
+        if self.changes.is_empty() {
+            return message;
+        }
+
+        message.push('\n');
+        for change in &self.changes {
+            let revision = if change.revision.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", change.revision)
+            };
+            message.push_str(&format!(
+                "- {}{}: {} -> {}\n",
+                change.name,
+                revision,
+                short(change.old_commit.as_deref()),
+                short(change.new_commit.as_deref())
+            ));
+        }
+
+        message.push('\n');
+        for change in &self.changes {
+            message.push_str(&format!(
+                "{}: name={} repo={} rev={} old={} new={}\n",
+                SOURCE_TRAILER,
+                change.name,
+                AuthenticationService::redact_url(&change.repository_url),
+                or_none(Some(change.revision.as_str()).filter(|r| !r.is_empty())),
+                or_none(change.old_commit.as_deref()),
+                or_none(change.new_commit.as_deref())
+            ));
+        }
+        message
+    }
+}
+
+fn short(commit: Option<&str>) -> String {
+    match commit {
+        Some(commit) => commit.chars().take(SHORT_COMMIT_LENGTH).collect(),
+        None => "none".to_string(),
+    }
+}
+
+fn or_none(value: Option<&str>) -> &str {
+    value.unwrap_or("none")
+}
@@ -0,0 +1,183 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use serde::Serialize;
+use similar::TextDiff;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::domain::error::DomainError;
+use crate::domain::provenance::PROVENANCE_FILE;
+use crate::domain::transform::TransformReport;
+
+/// Kind of change an update would make to a single file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A single file-level change between the vendored and the staged content
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    /// Path relative to the target location
+    pub path: PathBuf,
+
+    /// What happens to the file
+    pub kind: ChangeKind,
+
+    /// Unified text diff, when requested and the file is text
+    pub text_diff: Option<String>,
+}
+
+/// All changes an update would make to a dependency's target location
+#[derive(Debug, Clone)]
+pub struct DependencyDiff {
+    pub name: String,
+    pub target_location: PathBuf,
+    pub changes: Vec<FileChange>,
+
+    /// Transform steps applied to the staged content, in order
+    pub transforms: Vec<TransformReport>,
+}
+
+/// Compares a staged directory against the current target directory
+///
+/// Files present only in `staging` are reported as added, files present only
+/// in `target` as removed and files whose contents differ as modified. A
+/// missing target directory is treated as empty.
+pub fn compare_directories(
+    staging: &Path,
+    target: &Path,
+    with_text: bool,
+) -> Result<Vec<FileChange>, DomainError> {
+    let staged_files = list_files(staging)?;
+    let current_files = list_files(target)?;
+
+    let mut changes = Vec::new();
+
+    for path in staged_files.union(&current_files) {
+        let new_path = staging.join(path);
+        let old_path = target.join(path);
+
+        let kind = match (current_files.contains(path), staged_files.contains(path)) {
+            (false, true) => ChangeKind::Added,
+            (true, false) => ChangeKind::Removed,
+            _ => {
+                if read_file(&old_path)? == read_file(&new_path)? {
+                    continue;
+                }
+                ChangeKind::Modified
+            }
+        };
+
+        let text_diff = if with_text {
+            let old = if kind == ChangeKind::Added {
+                None
+            } else {
+                Some(old_path.as_path())
+            };
+            let new = if kind == ChangeKind::Removed {
+                None
+            } else {
+                Some(new_path.as_path())
+            };
+            Some(unified_diff(path, old, new)?)
+        } else {
+            None
+        };
+
+        changes.push(FileChange {
+            path: path.clone(),
+            kind,
+            text_diff,
+        });
+    }
+
+    Ok(changes)
+}
+
+/// Renders a unified diff between two versions of a file
+///
+/// `None` stands for a file that does not exist on that side. Binary content
+/// is summarised in a single line, as git does.
+pub fn unified_diff(
+    path: &Path,
+    old: Option<&Path>,
+    new: Option<&Path>,
+) -> Result<String, DomainError> {
+    let old_bytes = old.map(read_file).transpose()?.unwrap_or_default();
+    let new_bytes = new.map(read_file).transpose()?.unwrap_or_default();
+
+    let display = path.to_string_lossy();
+    let old_header = match old {
+        Some(_) => format!("a/{}", display),
+        None => "/dev/null".to_string(),
+    };
+    let new_header = match new {
+        Some(_) => format!("b/{}", display),
+        None => "/dev/null".to_string(),
+    };
+
+    let (old_text, new_text) = match (as_text(&old_bytes), as_text(&new_bytes)) {
+        (Some(o), Some(n)) => (o, n),
+        _ => {
+            return Ok(format!(
+                "Binary files {} and {} differ\n",
+                old_header, new_header
+            ))
+        }
+    };
+
+    Ok(TextDiff::from_lines(old_text, new_text)
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_header, &new_header)
+        .to_string())
+}
+
+pub(crate) fn as_text(bytes: &[u8]) -> Option<&str> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(bytes).ok()
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, DomainError> {
+    fs::read(path).map_err(|e| {
+        DomainError::FileSystemError(format!("Failed to read {}: {}", path.display(), e))
+    })
+}
+
+/// Lists all files below `root` as paths relative to it, except its provenance file
+fn list_files(root: &Path) -> Result<BTreeSet<PathBuf>, DomainError> {
+    let mut files = BTreeSet::new();
+
+    if !root.exists() {
+        return Ok(files);
+    }
+
+    for entry in WalkDir::new(root).min_depth(1) {
+        let entry = entry.map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to walk directory: {}", e))
+        })?;
+
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .map_err(|e| DomainError::FileSystemError(format!("Failed to strip prefix: {}", e)))?;
+        if relative == Path::new(PROVENANCE_FILE) {
+            continue;
+        }
+        files.insert(relative.to_path_buf());
+    }
+
+    Ok(files)
+}
@@ -20,6 +20,18 @@ pub enum DomainError {
     #[error("Git operation failed: {0}")]
     GitError(String),
 
+    #[error("Local modifications would be overwritten: {0}")]
+    LocalModificationsError(String),
+
+    #[error("Local modifications conflict with upstream changes: {0}")]
+    ConflictError(String),
+
+    #[error("Patch error: {0}")]
+    PatchError(String),
+
+    #[error("Transform error: {0}")]
+    TransformError(String),
+
     #[error("Path pattern error: {0}")]
     PathPatternError(String),
 
@@ -60,7 +60,22 @@ impl AuthHelper {
     pub fn format_credentials(&self, auth: &RepositoryAuth) -> String {
         match auth.auth_type {
             AuthType::None => "No authentication".to_string(),
-            AuthType::Ssh => "SSH key authentication".to_string(),
+            AuthType::Ssh => {
+                let key = match &auth.ssh.key {
+                    Some(key) => format!("SSH key {}", key.display()),
+                    None => "SSH agent".to_string(),
+                };
+                let known_hosts = match &auth.ssh.known_hosts {
+                    Some(path) => format!(", known hosts {}", path.display()),
+                    None => String::new(),
+                };
+                let checking = if auth.ssh.strict_host_key_checking.unwrap_or(true) {
+                    ""
+                } else {
+                    ", host key checking disabled"
+                };
+                format!("{}{}{}", key, known_hosts, checking)
+            }
             AuthType::HttpsBasic => {
                 if let Some(creds) = &auth.credentials {
                     // Mask the password part for security
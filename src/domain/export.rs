@@ -0,0 +1,338 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::domain::diff::{self, as_text, ChangeKind};
+use crate::domain::error::DomainError;
+use crate::domain::merge;
+use crate::domain::time::{civil_date, unix_seconds};
+
+/// Commit `git format-patch` writes on the `From` line of every patch
+const NO_COMMIT: &str = "0000000000000000000000000000000000000000";
+
+/// Longest file name stem derived from a subject, as `git format-patch` uses
+const MAX_NAME_LENGTH: usize = 52;
+
+/// One change of a series to contribute upstream
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedPatch {
+    pub subject: String,
+
+    /// Commit message body, empty when there is none
+    pub body: String,
+
+    /// Upstream paths the change touches
+    pub files: Vec<PathBuf>,
+
+    /// The change in `git diff` format, with upstream paths
+    pub diff: String,
+}
+
+/// Renders the changes from `old` to `new` as a `git diff`
+///
+/// Paths are relative to both directories. Returns the diff and the
+/// changed paths, an empty diff when the directories do not differ.
+pub fn git_diff(old: &Path, new: &Path) -> Result<(String, Vec<PathBuf>), DomainError> {
+    let mut text = String::new();
+    let mut files = Vec::new();
+
+    for change in diff::compare_directories(new, old, true)? {
+        let text_diff = change.text_diff.unwrap_or_default();
+        if text_diff.starts_with("Binary files ") {
+            return Err(DomainError::PatchError(format!(
+                "{} is binary and cannot be exported",
+                change.path.display()
+            )));
+        }
+
+        let path = change.path.to_string_lossy().to_string();
+        text.push_str(&format!("diff --git a/{} b/{}\n", path, path));
+        let (old_mode, new_mode) = (
+            git_mode(&old.join(&change.path)),
+            git_mode(&new.join(&change.path)),
+        );
+        match change.kind {
+            ChangeKind::Added => text.push_str(&format!("new file mode {}\n", new_mode)),
+            ChangeKind::Removed => text.push_str(&format!("deleted file mode {}\n", old_mode)),
+            ChangeKind::Modified if old_mode != new_mode => {
+                text.push_str(&format!("old mode {}\nnew mode {}\n", old_mode, new_mode))
+            }
+            ChangeKind::Modified => {}
+        }
+        text.push_str(&text_diff);
+        files.push(change.path);
+    }
+
+    Ok((text, files))
+}
+
+/// Carries the changes from `before` to `after` over to `upstream`, the
+/// content `before` was transformed from
+///
+/// Files renamed by the transforms are found at their upstream paths in
+/// `renamed`. The lines a change touches take their new text and the rest
+/// of a file keeps its upstream text, so that the changes apply upstream.
+/// Fails when a change overlaps text the transforms rewrote.
+pub fn untransform(
+    before: &Path,
+    after: &Path,
+    upstream: &Path,
+    renamed: &BTreeMap<PathBuf, PathBuf>,
+) -> Result<(), DomainError> {
+    for change in diff::compare_directories(after, before, false)? {
+        let after_path = after.join(&change.path);
+        let upstream_path = upstream.join(renamed.get(&change.path).unwrap_or(&change.path));
+        let content = match change.kind {
+            ChangeKind::Removed => {
+                fs::remove_file(&upstream_path).map_err(|e| {
+                    DomainError::FileSystemError(format!(
+                        "Failed to remove {}: {}",
+                        upstream_path.display(),
+                        e
+                    ))
+                })?;
+                continue;
+            }
+            ChangeKind::Added => read(&after_path)?,
+            ChangeKind::Modified => upstream_content(
+                &read(&before.join(&change.path))?,
+                &read(&after_path)?,
+                &read(&upstream_path)?,
+            )
+            .ok_or_else(|| {
+                DomainError::PatchError(format!(
+                    "{} changed where the transforms rewrote it, so it cannot be exported",
+                    change.path.display()
+                ))
+            })?,
+        };
+
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = upstream_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&upstream_path, &content)?;
+            fs::set_permissions(&upstream_path, fs::metadata(&after_path)?.permissions())
+        };
+        write().map_err(|e| {
+            DomainError::FileSystemError(format!(
+                "Failed to write {}: {}",
+                upstream_path.display(),
+                e
+            ))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Content of a file upstream once changed from `before` to `after`, where
+/// `before` is the file transformed from `upstream`
+///
+/// Returns `None` when the change cannot be told apart from the transforms.
+fn upstream_content(before: &[u8], after: &[u8], upstream: &[u8]) -> Option<Vec<u8>> {
+    if before == upstream {
+        return Some(after.to_vec());
+    }
+
+    // Line endings are set aside, so that converting them does not make
+    // every line of the file conflict
+    let lf = |bytes: &[u8]| as_text(bytes).map(|text| text.replace("\r\n", "\n"));
+    let merged = merge::merge(
+        lf(before)?.as_bytes(),
+        lf(after)?.as_bytes(),
+        lf(upstream)?.as_bytes(),
+    )?;
+    if merged.conflicted {
+        return None;
+    }
+    let text = String::from_utf8(merged.content).ok()?;
+    if as_text(upstream)?.contains("\r\n") {
+        Some(text.replace('\n', "\r\n").into_bytes())
+    } else {
+        Some(text.into_bytes())
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, DomainError> {
+    fs::read(path).map_err(|e| {
+        DomainError::FileSystemError(format!("Failed to read {}: {}", path.display(), e))
+    })
+}
+
+/// Mode git records for a file, which only tells executables apart
+#[cfg(unix)]
+fn git_mode(path: &Path) -> &'static str {
+    use std::os::unix::fs::PermissionsExt;
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.permissions().mode() & 0o111 != 0 => "100755",
+        _ => "100644",
+    }
+}
+
+#[cfg(not(unix))]
+fn git_mode(_path: &Path) -> &'static str {
+    "100644"
+}
+
+/// Subject and body of the change a patch file makes
+///
+/// A patch written by `git format-patch` keeps its subject and message.
+/// Otherwise the subject comes from the file name, and any text before the
+/// diff becomes the body.
+pub fn describe(patch_file: &Path, contents: &str) -> (String, String) {
+    let lines: Vec<&str> = contents.lines().collect();
+    let diff_start = lines
+        .iter()
+        .position(|line| line.starts_with("diff --git ") || line.starts_with("--- "))
+        .unwrap_or(lines.len());
+    let mut preamble = &lines[..diff_start];
+
+    let mut subject = None;
+    if preamble
+        .first()
+        .is_some_and(|line| line.starts_with("From "))
+    {
+        let header_end = preamble
+            .iter()
+            .position(|line| line.is_empty())
+            .unwrap_or(preamble.len());
+        subject = preamble[..header_end]
+            .iter()
+            .find_map(|line| line.strip_prefix("Subject: "))
+            .map(strip_patch_prefix);
+        preamble = &preamble[header_end..];
+
+        // The message ends where the diffstat starts
+        if let Some(end) = preamble.iter().position(|line| *line == "---") {
+            preamble = &preamble[..end];
+        }
+    }
+
+    let subject = subject.unwrap_or_else(|| subject_from_file_name(patch_file));
+    let body = preamble.join("\n").trim().to_string();
+    (subject, body)
+}
+
+/// Formats a series as `git format-patch` does, returning file names and contents
+///
+/// `author` is `Name <email>`, and `base_commit` the upstream commit the
+/// series applies to.
+pub fn format_series(
+    patches: &[ExportedPatch],
+    author: &str,
+    base_commit: &str,
+    date: SystemTime,
+) -> Vec<(String, String)> {
+    let date = mail_date(date);
+    let total = patches.len();
+
+    patches
+        .iter()
+        .enumerate()
+        .map(|(index, patch)| {
+            let number = index + 1;
+            let prefix = if total == 1 {
+                "[PATCH]".to_string()
+            } else {
+                format!("[PATCH {}/{}]", number, total)
+            };
+
+            let mut mail = format!(
+                "From {} Mon Sep 17 00:00:00 2001\nFrom: {}\nDate: {}\nSubject: {} {}\n\n",
+                NO_COMMIT, author, date, prefix, patch.subject
+            );
+            if !patch.body.is_empty() {
+                mail.push_str(&patch.body);
+                mail.push('\n');
+            }
+            mail.push_str("---\n");
+            for file in &patch.files {
+                mail.push_str(&format!(" {}\n", file.to_string_lossy()));
+            }
+            mail.push('\n');
+            mail.push_str(&patch.diff);
+            mail.push_str(&format!(
+                "\nbase-commit: {}\n-- \nacdm {}\n\n",
+                base_commit,
+                env!("CARGO_PKG_VERSION")
+            ));
+
+            (
+                format!("{:04}-{}.patch", number, file_name_stem(&patch.subject)),
+                mail,
+            )
+        })
+        .collect()
+}
+
+/// Removes the `[PATCH n/m]` prefix of a subject
+fn strip_patch_prefix(subject: &str) -> String {
+    let subject = subject.trim();
+    match subject
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+    {
+        Some((tag, rest)) if tag.starts_with("PATCH") => rest.trim().to_string(),
+        _ => subject.to_string(),
+    }
+}
+
+/// Subject for a patch file without one, from its name less any number prefix
+fn subject_from_file_name(patch_file: &Path) -> String {
+    let stem = patch_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let words = stem
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim_start_matches(['-', '_'])
+        .replace(['-', '_'], " ");
+
+    if words.trim().is_empty() {
+        format!("Apply {}", patch_file.display())
+    } else {
+        words.trim().to_string()
+    }
+}
+
+/// File name stem for a subject, as `git format-patch` derives it
+fn file_name_stem(subject: &str) -> String {
+    let mut stem = String::new();
+    for c in subject.chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+            stem.push(c);
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    stem.truncate(MAX_NAME_LENGTH);
+    stem.trim_end_matches(['-', '.']).to_string()
+}
+
+/// Formats a point in time as an RFC 2822 date in UTC, as mail headers use
+fn mail_date(time: SystemTime) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let seconds = unix_seconds(time);
+    let days = seconds.div_euclid(86_400);
+    let (year, month, day) = civil_date(days);
+    let time_of_day = seconds.rem_euclid(86_400);
+
+    format!(
+        "{}, {} {} {} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[days.rem_euclid(7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        time_of_day / 3_600,
+        time_of_day % 3_600 / 60,
+        time_of_day % 60
+    )
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::fs;
+use std::path::{Component, Path};
+
+use crate::domain::error::DomainError;
+use crate::domain::Configuration;
+
+/// Name of the attributes file next to the configuration file
+pub const GITATTRIBUTES_FILE: &str = ".gitattributes";
+
+const BEGIN_MARKER: &str = "# BEGIN acdm managed block: do not edit, run acdm to update";
+const END_MARKER: &str = "# END acdm managed block";
+
+/// Renders the acdm block for a configuration
+///
+/// Returns `None` when the block should not exist: the setting is off or there
+/// are no targets to mark.
+pub fn managed_block(config: &Configuration) -> Option<String> {
+    if !config.gitattributes.manage {
+        return None;
+    }
+
+    let attributes = if config.gitattributes.diff {
+        "linguist-vendored"
+    } else {
+        "linguist-vendored -diff"
+    };
+
+    let mut patterns: Vec<String> = config
+        .dependencies
+        .iter()
+        .filter_map(|dep| target_pattern(&dep.target_location))
+        .collect();
+    patterns.sort();
+    patterns.dedup();
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let mut block = format!("{}\n", BEGIN_MARKER);
+    for pattern in patterns {
+        block.push_str(&format!("{} {}\n", pattern, attributes));
+    }
+    block.push_str(END_MARKER);
+    block.push('\n');
+    Some(block)
+}
+
+/// Pattern matching everything below a target location
+///
+/// Targets are relative to the configuration file; ones outside its
+/// directory cannot be described from its `.gitattributes` and are skipped.
+fn target_pattern(target: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in target.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+
+    // Glob characters in names match literally once escaped, and whitespace,
+    // which separates a pattern from its attributes, is matched as a class
+    let mut path = String::new();
+    for c in parts.join("/").chars() {
+        match c {
+            '*' | '?' | '[' | '\\' => {
+                path.push('\\');
+                path.push(c);
+            }
+            ' ' => path.push_str("[[:space:]]"),
+            c => path.push(c),
+        }
+    }
+    Some(format!("/{}/**", path))
+}
+
+/// Replaces the acdm block in the contents of an attributes file
+///
+/// Lines outside the block are kept; without a `block` the existing one is
+/// removed along with the blank line separating it from the rest.
+pub fn apply_block(existing: &str, block: Option<&str>) -> String {
+    let lines: Vec<&str> = existing.lines().collect();
+    let start = lines.iter().position(|l| l.trim_end() == BEGIN_MARKER);
+    let end = start.and_then(|start| {
+        lines[start..]
+            .iter()
+            .position(|l| l.trim_end() == END_MARKER)
+            .map(|offset| start + offset)
+    });
+    let (before, after) = match (start, end, block) {
+        (Some(start), Some(end), _) => (&lines[..start], &lines[end + 1..]),
+        // Files acdm has no business with are left exactly as they are
+        (_, _, None) => return existing.to_string(),
+        _ => (&lines[..], &lines[lines.len()..]),
+    };
+
+    let before = before.join("\n");
+    let after = after.join("\n");
+    let parts: Vec<&str> = [
+        before.trim_end(),
+        block.map(str::trim_end).unwrap_or_default(),
+        after.trim_matches('\n'),
+    ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect();
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", parts.join("\n\n"))
+    }
+}
+
+/// Whether the attributes file in `dir` holds the block `config` calls for
+pub fn is_current(dir: &Path, config: &Configuration) -> Result<bool, DomainError> {
+    let existing = read(dir)?;
+    Ok(apply_block(&existing, managed_block(config).as_deref()) == existing)
+}
+
+/// Brings the acdm block of the attributes file in `dir` up to date
+///
+/// The file is removed when nothing but the block was in it. Returns whether
+/// the file changed.
+pub fn sync(dir: &Path, config: &Configuration) -> Result<bool, DomainError> {
+    let existing = read(dir)?;
+    let updated = apply_block(&existing, managed_block(config).as_deref());
+    if updated == existing {
+        return Ok(false);
+    }
+
+    let path = dir.join(GITATTRIBUTES_FILE);
+    let result = if updated.is_empty() {
+        fs::remove_file(&path)
+    } else {
+        fs::write(&path, updated)
+    };
+    result.map_err(|e| {
+        DomainError::FileSystemError(format!("Failed to write {}: {}", path.display(), e))
+    })?;
+    Ok(true)
+}
+
+fn read(dir: &Path) -> Result<String, DomainError> {
+    let path = dir.join(GITATTRIBUTES_FILE);
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    fs::read_to_string(&path).map_err(|e| {
+        DomainError::FileSystemError(format!("Failed to read {}: {}", path.display(), e))
+    })
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::domain::error::DomainError;
+use crate::domain::provenance::PROVENANCE_FILE;
+
+/// Content hashes of vendored files, keyed by `/`-separated relative path
+pub type Manifest = BTreeMap<String, String>;
+
+/// Differences between a directory and its recorded manifest
+#[derive(Debug, Clone, Default)]
+pub struct ManifestDrift {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ManifestDrift {
+    pub fn is_clean(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Computes the SHA-256 hex digest of a file
+pub fn hash_file(path: &Path) -> Result<String, DomainError> {
+    let bytes = fs::read(path).map_err(|e| {
+        DomainError::FileSystemError(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    Ok(hash_bytes(&bytes))
+}
+
+/// Computes the SHA-256 hex digest of file content
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Hashes every file below `root`, except its provenance file
+pub fn compute_manifest(root: &Path) -> Result<Manifest, DomainError> {
+    let mut manifest = Manifest::new();
+
+    if !root.exists() {
+        return Ok(manifest);
+    }
+
+    for entry in WalkDir::new(root).min_depth(1) {
+        let entry = entry.map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to walk directory: {}", e))
+        })?;
+
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .map_err(|e| DomainError::FileSystemError(format!("Failed to strip prefix: {}", e)))?;
+        if relative == Path::new(PROVENANCE_FILE) {
+            continue;
+        }
+        let key = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        manifest.insert(key, hash_file(entry.path())?);
+    }
+
+    Ok(manifest)
+}
+
+/// Compares the files below `root` against a recorded manifest
+pub fn compare_manifest(root: &Path, expected: &Manifest) -> Result<ManifestDrift, DomainError> {
+    let actual = compute_manifest(root)?;
+    let mut drift = ManifestDrift::default();
+
+    for (path, hash) in &actual {
+        match expected.get(path) {
+            None => drift.added.push(path.clone()),
+            Some(expected_hash) if expected_hash != hash => drift.modified.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+
+    drift.removed = expected
+        .keys()
+        .filter(|path| !actual.contains_key(*path))
+        .cloned()
+        .collect();
+
+    Ok(drift)
+}
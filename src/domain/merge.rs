@@ -0,0 +1,114 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+use crate::domain::diff::as_text;
+
+/// Marker opening the local side of a conflict
+pub const CONFLICT_START: &str = "<<<<<<< local";
+
+/// Marker separating the local from the upstream side of a conflict
+pub const CONFLICT_SEPARATOR: &str = "=======";
+
+/// Marker closing the upstream side of a conflict
+pub const CONFLICT_END: &str = ">>>>>>> upstream";
+
+/// Result of merging local and upstream changes to a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedFile {
+    pub content: Vec<u8>,
+
+    /// Whether the content holds conflict markers
+    pub conflicted: bool,
+}
+
+/// Merges the changes from `base` to `local` and from `base` to `upstream`
+///
+/// Lines are merged as by `git merge-file`: a region changed on one side
+/// only takes that side, a region changed the same way on both sides is
+/// taken once, and a region changed differently on both sides is written
+/// with conflict markers around both versions. A missing base is treated
+/// as empty. Returns `None` when any version is binary.
+pub fn merge(base: &[u8], local: &[u8], upstream: &[u8]) -> Option<MergedFile> {
+    let base: Vec<&str> = as_text(base)?.split_inclusive('\n').collect();
+    let local: Vec<&str> = as_text(local)?.split_inclusive('\n').collect();
+    let upstream: Vec<&str> = as_text(upstream)?.split_inclusive('\n').collect();
+
+    let local_matches = matching_lines(&base, &local);
+    let upstream_matches = matching_lines(&base, &upstream);
+
+    let mut merged = String::new();
+    let mut conflicted = false;
+    let (mut b, mut l, mut u) = (0, 0, 0);
+
+    loop {
+        // The next base line kept unchanged on both sides anchors the regions before it
+        let anchor = (b..base.len()).find_map(|i| {
+            Some((i, local_matches[i]?, upstream_matches[i]?))
+                .filter(|&(_, lj, uj)| lj >= l && uj >= u)
+        });
+        let (bi, li, ui) = anchor.unwrap_or((base.len(), local.len(), upstream.len()));
+
+        let base_region = &base[b..bi];
+        let local_region = &local[l..li];
+        let upstream_region = &upstream[u..ui];
+        if local_region == base_region || local_region == upstream_region {
+            merged.extend(upstream_region.iter().copied());
+        } else if upstream_region == base_region {
+            merged.extend(local_region.iter().copied());
+        } else {
+            conflicted = true;
+            push_side(&mut merged, CONFLICT_START, local_region);
+            push_side(&mut merged, CONFLICT_SEPARATOR, upstream_region);
+            push_line(&mut merged, CONFLICT_END);
+        }
+
+        match anchor {
+            Some(_) => {
+                merged.push_str(base[bi]);
+                (b, l, u) = (bi + 1, li + 1, ui + 1);
+            }
+            None => break,
+        }
+    }
+
+    Some(MergedFile {
+        content: merged.into_bytes(),
+        conflicted,
+    })
+}
+
+/// For each line of `base`, the index of the same line in `other` if unchanged
+fn matching_lines(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            for offset in 0..len {
+                matches[old_index + offset] = Some(new_index + offset);
+            }
+        }
+    }
+    matches
+}
+
+/// Writes a conflict marker followed by the lines of one side
+fn push_side(merged: &mut String, marker: &str, lines: &[&str]) {
+    push_line(merged, marker);
+    for line in lines {
+        merged.push_str(line);
+    }
+}
+
+/// Writes a line, ending the previous one first if it had no line break
+fn push_line(merged: &mut String, line: &str) {
+    if !merged.is_empty() && !merged.ends_with('\n') {
+        merged.push('\n');
+    }
+    merged.push_str(line);
+    merged.push('\n');
+}
@@ -0,0 +1,84 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use log::{info, warn};
+
+use crate::domain::auth::AuthenticationService;
+use crate::domain::error::DomainError;
+
+/// Mirrors replacing a URL prefix, as configured in `[mirrors]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirrorRule {
+    /// Prefix of the canonical URL, such as `https://github.com/`
+    pub prefix: String,
+
+    /// Replacements for the prefix, tried in order
+    pub mirrors: Vec<String>,
+}
+
+/// URL rewriting rules, similar to git's `url.<base>.insteadOf`
+///
+/// The rule with the longest matching prefix applies. Its mirrors are tried in
+/// order, and the canonical URL is tried last, so a failing mirror never breaks
+/// an update.
+#[derive(Debug, Clone, Default)]
+pub struct Mirrors {
+    rules: Vec<MirrorRule>,
+}
+
+impl Mirrors {
+    pub fn new(rules: Vec<MirrorRule>) -> Self {
+        Self { rules }
+    }
+
+    /// URLs to try for a repository, ending with the canonical URL itself
+    pub fn candidates(&self, url: &str) -> Vec<String> {
+        let rule = self
+            .rules
+            .iter()
+            .filter(|rule| url.starts_with(&rule.prefix))
+            .max_by_key(|rule| rule.prefix.len());
+
+        let mut candidates: Vec<String> = rule
+            .map(|rule| {
+                rule.mirrors
+                    .iter()
+                    .map(|mirror| format!("{}{}", mirror, &url[rule.prefix.len()..]))
+                    .collect()
+            })
+            .unwrap_or_default();
+        candidates.push(url.to_string());
+        candidates
+    }
+
+    /// Runs `operation` against each candidate URL until one succeeds
+    ///
+    /// Returns the result together with the mirror that served it, or `None`
+    /// when the canonical URL did. The error of the canonical URL is returned
+    /// when every candidate fails.
+    pub fn try_each<T>(
+        &self,
+        url: &str,
+        mut operation: impl FnMut(&str) -> Result<T, DomainError>,
+    ) -> Result<(T, Option<String>), DomainError> {
+        let candidates = self.candidates(url);
+        let (mirrors, canonical) = candidates.split_at(candidates.len() - 1);
+
+        for mirror in mirrors {
+            info!(
+                "Using mirror {} for {}",
+                AuthenticationService::redact_url(mirror),
+                AuthenticationService::redact_url(url)
+            );
+            match operation(mirror) {
+                Ok(value) => return Ok((value, Some(mirror.clone()))),
+                Err(e) => warn!(
+                    "Mirror {} failed, trying the next candidate: {}",
+                    AuthenticationService::redact_url(mirror),
+                    e
+                ),
+            }
+        }
+
+        operation(&canonical[0]).map(|value| (value, None))
+    }
+}
@@ -1,11 +1,25 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
 pub mod auth;
+pub mod commit;
+pub mod diff;
 pub mod error;
 pub mod examples;
+pub mod export;
+pub mod gitattributes;
+pub mod manifest;
+pub mod merge;
+pub mod mirrors;
 pub mod models;
+pub mod patch;
+pub mod provenance;
 pub mod repositories;
 pub mod services;
+pub mod sources;
+pub mod time;
+pub mod transform;
+pub mod url;
+pub mod validation;
 
 pub use error::*;
 pub use models::*;
@@ -1,9 +1,12 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use crate::domain::transform::Transform;
+
 /// Represents an external content dependency
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Dependency {
     /// Unique identifier for the dependency
     pub name: String,
@@ -11,48 +14,309 @@ pub struct Dependency {
     /// Git repository URL (SSH or HTTPS)
     pub repository_url: String,
 
-    /// Git revision (branch, tag, or commit)
+    /// Revision (branch, tag, or commit), for sources that track revisions
     pub revision: String,
 
-    /// Repository type (currently only 'git' is supported)
-    pub repository_type: RepositoryType,
+    /// Source type, the name of a registered source backend such as `git`
+    pub repository_type: String,
 
     /// Patterns for selecting specific paths from the repository
     pub sparse_paths: Vec<String>,
 
     /// Target location in the project where content will be placed
     pub target_location: PathBuf,
-}
 
-/// Type of repository for the dependency
-#[derive(Debug, Clone, PartialEq)]
-pub enum RepositoryType {
-    Git,
-    // Future support for other repository types can be added here
+    /// Patch files applied in order to the extracted content, as patterns
+    /// relative to the configuration file
+    pub patches: Vec<String>,
+
+    /// Steps rewriting the extracted content in order, before the patches apply
+    pub transforms: Vec<Transform>,
+
+    /// SSH settings overriding those configured for the repository host
+    pub ssh: SshSettings,
+
+    /// Options of the source type, from its `[sources.<type>]` block
+    pub options: SourceOptions,
 }
 
+/// Type-specific options of a source, interpreted by its backend
+pub type SourceOptions = toml::Table;
+
+/// Source type used when none is given
+pub const DEFAULT_SOURCE_TYPE: &str = "git";
+
 /// Repository authentication information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RepositoryAuth {
     pub auth_type: AuthType,
     pub credentials: Option<String>,
+
+    /// Key and host verification settings, for SSH authentication
+    pub ssh: SshSettings,
 }
 
 /// Type of authentication for the repository
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum AuthType {
+    #[default]
     None,
     Ssh,
     HttpsBasic,
     HttpsToken,
 }
 
+/// SSH key and host verification settings for a source or host
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SshSettings {
+    /// Private key to authenticate with instead of the SSH agent
+    pub key: Option<PathBuf>,
+
+    /// Known hosts file to verify the server against
+    pub known_hosts: Option<PathBuf>,
+
+    /// Whether unknown or changed host keys are rejected (the default)
+    pub strict_host_key_checking: Option<bool>,
+}
+
+impl SshSettings {
+    /// Fills unset fields from `fallback`
+    pub fn or(&self, fallback: &SshSettings) -> SshSettings {
+        SshSettings {
+            key: self.key.clone().or_else(|| fallback.key.clone()),
+            known_hosts: self
+                .known_hosts
+                .clone()
+                .or_else(|| fallback.known_hosts.clone()),
+            strict_host_key_checking: self
+                .strict_host_key_checking
+                .or(fallback.strict_host_key_checking),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &SshSettings::default()
+    }
+}
+
+/// Where the credentials for a host come from, as configured in `[auth."host"]`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostAuth {
+    /// Username sent with the secret; without one the secret is used as a token
+    pub username: Option<String>,
+
+    /// Environment variable holding the secret
+    pub env: Option<String>,
+
+    /// File holding the secret
+    pub file: Option<PathBuf>,
+
+    /// Ask `git credential fill` for the username and password instead
+    pub credential_helper: bool,
+
+    /// SSH settings for repositories on this host
+    pub ssh: SshSettings,
+}
+
+impl HostAuth {
+    /// Number of configured secret sources; at most one is allowed
+    pub fn source_count(&self) -> usize {
+        [
+            self.env.is_some(),
+            self.file.is_some(),
+            self.credential_helper,
+        ]
+        .iter()
+        .filter(|set| **set)
+        .count()
+    }
+}
+
 /// Configuration for the application
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Configuration {
     /// Default location for vendored content
     pub default_location: Option<PathBuf>,
 
     /// List of all dependencies
     pub dependencies: Vec<Dependency>,
+
+    /// Credential sources keyed by host name
+    pub auth: BTreeMap<String, HostAuth>,
+
+    /// URL rewriting rules applied before fetching
+    pub mirrors: Vec<crate::domain::mirrors::MirrorRule>,
+
+    /// How acdm maintains its block in `.gitattributes`
+    pub gitattributes: GitAttributesSettings,
+
+    /// Which uncommitted changes stop commands that modify the working tree
+    pub require_clean: CleanPolicy,
+}
+
+/// Which uncommitted changes stop `add`, `include`, `update` and `remove`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CleanPolicy {
+    /// Changes to the configuration, lock file, `.gitattributes` or the
+    /// targets the command modifies
+    #[default]
+    Paths,
+
+    /// Changes anywhere in the repository
+    Repository,
+
+    /// No changes; the repository is not checked at all
+    None,
+}
+
+/// What `update` does with vendored files modified since the last update
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LocalChanges {
+    /// Refuse to update a target holding local modifications
+    #[default]
+    Refuse,
+
+    /// Overwrite local modifications with the upstream content
+    Discard,
+
+    /// Carry local modifications forward, merging them with upstream changes
+    Keep,
+}
+
+/// Settings of the acdm-managed block in `.gitattributes`, from `[gitattributes]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitAttributesSettings {
+    /// Whether acdm maintains the block at all; off unless opted in
+    pub manage: bool,
+
+    /// Whether git diffs vendored files; `false` marks them `-diff`
+    pub diff: bool,
+}
+
+impl Default for GitAttributesSettings {
+    fn default() -> Self {
+        Self {
+            manage: false,
+            diff: true,
+        }
+    }
+}
+
+/// Resolved state of a dependency as of its last update
+#[derive(Debug, Clone)]
+pub struct LockedDependency {
+    pub name: String,
+    pub repository_url: String,
+    pub revision: String,
+
+    /// Commit the revision resolved to, when the fetcher can tell
+    pub commit: Option<String>,
+
+    /// Mirror the content was fetched from instead of `repository_url`
+    pub mirror: Option<String>,
+
+    pub sparse_paths: Vec<String>,
+    pub target_location: PathBuf,
+
+    /// Patch files applied on top of the upstream content, in order
+    pub patches: Vec<LockedPatch>,
+
+    /// Steps that rewrote the extracted content, in order
+    pub transforms: Vec<Transform>,
+
+    /// Options of the source type the content was fetched with
+    pub options: SourceOptions,
+
+    /// Content hashes of the vendored files
+    pub files: crate::domain::manifest::Manifest,
+}
+
+impl LockedDependency {
+    /// Whether the configuration of a dependency still matches this lock entry
+    ///
+    /// `patches` are the patch files the dependency's patterns resolve to now.
+    pub fn matches_config(&self, dependency: &Dependency, patches: &[LockedPatch]) -> bool {
+        self.repository_url == dependency.repository_url
+            && self.revision == dependency.revision
+            && self.sparse_paths == dependency.sparse_paths
+            && self.target_location == dependency.target_location
+            && self.patches == patches
+            && self.transforms == dependency.transforms
+            && self.options == dependency.options
+    }
+}
+
+/// A patch file as applied by an update
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPatch {
+    /// Path relative to the configuration file, `/`-separated
+    pub path: String,
+
+    /// SHA-256 of the patch file's content
+    pub sha256: String,
+}
+
+/// Lock file recording what was vendored by the last update
+#[derive(Debug, Clone, Default)]
+pub struct LockFile {
+    pub dependencies: Vec<LockedDependency>,
+}
+
+impl LockFile {
+    /// Lock file location for a configuration file (`acdm.toml` -> `acdm.lock`)
+    pub fn path_for(config_path: &std::path::Path) -> PathBuf {
+        config_path.with_extension("lock")
+    }
+
+    /// Finds the locked entry for a dependency
+    pub fn get(&self, name: &str) -> Option<&LockedDependency> {
+        self.dependencies.iter().find(|d| d.name == name)
+    }
+
+    /// Inserts or replaces the locked entry for a dependency
+    pub fn upsert(&mut self, locked: LockedDependency) {
+        match self.dependencies.iter_mut().find(|d| d.name == locked.name) {
+            Some(existing) => *existing = locked,
+            None => self.dependencies.push(locked),
+        }
+    }
+}
+
+/// Summary of a single upstream commit
+#[derive(Debug, Clone)]
+pub struct CommitSummary {
+    pub id: String,
+    pub author: String,
+    pub date: String,
+    pub subject: String,
+}
+
+/// State of a vendored dependency relative to its configuration and lock entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyState {
+    /// Vendored content matches the lock file and configuration
+    UpToDate,
+    /// Target location does not exist
+    Missing,
+    /// No lock entry records what was vendored
+    Stale,
+    /// Configuration changed since the last update
+    ConfigChanged,
+    /// Vendored files differ from the lock file manifest
+    LocallyModified,
+    /// The configured revision now resolves to a different upstream commit
+    UpstreamMoved,
+}
+
+impl std::fmt::Display for DependencyState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependencyState::UpToDate => write!(f, "up-to-date"),
+            DependencyState::Missing => write!(f, "missing"),
+            DependencyState::Stale => write!(f, "stale"),
+            DependencyState::ConfigChanged => write!(f, "config-changed"),
+            DependencyState::LocallyModified => write!(f, "locally-modified"),
+            DependencyState::UpstreamMoved => write!(f, "upstream-moved"),
+        }
+    }
 }
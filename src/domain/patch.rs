@@ -0,0 +1,379 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use glob::Pattern;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::domain::diff::as_text;
+use crate::domain::error::DomainError;
+use crate::domain::manifest;
+use crate::domain::models::LockedPatch;
+
+/// Resolves the patch patterns of a source to files, in the order they apply
+///
+/// Patterns are relative to `base_dir`, the directory of the configuration
+/// file, and applied in the order given; the files matched by one pattern
+/// apply in lexical order. Every pattern must match at least one file, so a
+/// mistyped pattern is not silently skipped.
+pub fn resolve(base_dir: &Path, patterns: &[String]) -> Result<Vec<PathBuf>, DomainError> {
+    let mut files = Vec::new();
+
+    for pattern in patterns {
+        let full_pattern = base_dir.join(pattern);
+        if Pattern::escape(pattern) == *pattern {
+            if !full_pattern.is_file() {
+                return Err(DomainError::PatchError(format!(
+                    "Patch file {} does not exist",
+                    full_pattern.display()
+                )));
+            }
+            files.push(full_pattern);
+            continue;
+        }
+
+        let matches = glob::glob(&full_pattern.to_string_lossy()).map_err(|e| {
+            DomainError::PathPatternError(format!("Invalid patch pattern '{}': {}", pattern, e))
+        })?;
+        let mut matched = matches
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        if matched.is_empty() {
+            return Err(DomainError::PatchError(format!(
+                "Patch pattern '{}' matches no files in {}",
+                pattern,
+                base_dir.display()
+            )));
+        }
+        matched.sort();
+        files.extend(matched);
+    }
+
+    Ok(files)
+}
+
+/// Records patch files, absolute or relative to `base_dir`, with the digest
+/// of their current content
+pub fn fingerprint(base_dir: &Path, files: &[PathBuf]) -> Result<Vec<LockedPatch>, DomainError> {
+    files
+        .iter()
+        .map(|file| {
+            let relative = file.strip_prefix(base_dir).unwrap_or(file);
+            Ok(LockedPatch {
+                path: relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                sha256: manifest::hash_file(&base_dir.join(relative))?,
+            })
+        })
+        .collect()
+}
+
+/// Applies patch files in order to the files below `root`
+pub fn apply_all(patch_files: &[PathBuf], root: &Path) -> Result<(), DomainError> {
+    for patch_file in patch_files {
+        let contents = fs::read_to_string(patch_file).map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to read {}: {}", patch_file.display(), e))
+        })?;
+        apply(&contents, root).map_err(|e| {
+            DomainError::PatchError(format!("{} no longer applies: {}", patch_file.display(), e))
+        })?;
+    }
+    Ok(())
+}
+
+/// Applies a unified diff, as written by `git diff` or `acdm patch create`,
+/// to the files below `root`
+///
+/// Paths in the diff are relative to `root`, with git's `a/` and `b/`
+/// prefixes. Every hunk must match its context exactly, though possibly at
+/// other lines than recorded. Nothing is written unless the whole patch
+/// applies.
+pub fn apply(patch: &str, root: &Path) -> Result<(), String> {
+    let file_patches = parse(patch)?;
+    if file_patches.is_empty() {
+        return Err("no changes found in the patch".to_string());
+    }
+
+    // Compute every patched file before writing any of them
+    let mut results: BTreeMap<PathBuf, Option<String>> = BTreeMap::new();
+    for file_patch in &file_patches {
+        let original = match &file_patch.old_path {
+            Some(old_path) => {
+                let path = root.join(old_path);
+                let contents = match results.get(&path) {
+                    Some(Some(contents)) => contents.clone(),
+                    Some(None) => return Err(format!("{} was deleted earlier", old_path)),
+                    None => read_text(&path).map_err(|e| format!("{}: {}", old_path, e))?,
+                };
+                Some((path, contents))
+            }
+            None => None,
+        };
+
+        let patched = apply_hunks(
+            original.as_ref().map(|(_, contents)| contents.as_str()),
+            &file_patch.hunks,
+        )
+        .map_err(|e| format!("{}: {}", file_patch.display_path(), e))?;
+
+        match &file_patch.new_path {
+            Some(new_path) => {
+                let path = root.join(new_path);
+                if file_patch.old_path.is_none() && path.exists() {
+                    return Err(format!("{} already exists", new_path));
+                }
+                if let Some((old, _)) = &original {
+                    if *old != path {
+                        results.insert(old.clone(), None);
+                    }
+                }
+                results.insert(path, Some(patched));
+            }
+            None => {
+                if let Some((old, _)) = original {
+                    results.insert(old, None);
+                }
+            }
+        }
+    }
+
+    for (path, contents) in results {
+        let written = match contents {
+            Some(contents) => path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, contents)),
+            None => fs::remove_file(&path),
+        };
+        written.map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    }
+
+    Ok(())
+}
+
+/// The changes of a patch to a single file
+struct FilePatch {
+    /// Path before the change, `None` for a new file
+    old_path: Option<String>,
+
+    /// Path after the change, `None` for a deleted file
+    new_path: Option<String>,
+
+    hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    fn display_path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+struct Hunk {
+    /// First line the hunk replaces, counting from 1
+    old_start: usize,
+
+    /// Lines the hunk expects, with their line breaks
+    old_lines: Vec<String>,
+
+    /// Lines the hunk writes in their place
+    new_lines: Vec<String>,
+}
+
+/// Whether a path names a file below the patched directory, rather than an
+/// absolute path or one leaving it with `..`
+fn is_relative_path(path: &str) -> bool {
+    let mut components = Path::new(path).components().peekable();
+    components.peek().is_some() && components.all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn parse(patch: &str) -> Result<Vec<FilePatch>, String> {
+    // Split on line feeds alone, so carriage returns of CRLF files stay in the lines
+    let lines: Vec<&str> = patch
+        .split_inclusive('\n')
+        .map(|line| line.strip_suffix('\n').unwrap_or(line))
+        .collect();
+    let mut file_patches = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if line.starts_with("GIT binary patch") || line.starts_with("Binary files ") {
+            return Err("binary patches are not supported".to_string());
+        }
+        if !(line.starts_with("--- ") && i + 1 < lines.len() && lines[i + 1].starts_with("+++ ")) {
+            i += 1;
+            continue;
+        }
+
+        let old_path = header_path(&line[4..], "a/");
+        let new_path = header_path(&lines[i + 1][4..], "b/");
+        if old_path.is_none() && new_path.is_none() {
+            return Err("a file patch has neither an old nor a new path".to_string());
+        }
+        for path in old_path.iter().chain(&new_path) {
+            if !is_relative_path(path) {
+                return Err(format!("{} is outside the patched files", path));
+            }
+        }
+        i += 2;
+
+        let mut hunks = Vec::new();
+        while i < lines.len() && lines[i].starts_with("@@ ") {
+            let (old_start, mut old_count, mut new_count) = hunk_header(lines[i])?;
+            i += 1;
+
+            let mut hunk = Hunk {
+                old_start,
+                old_lines: Vec::new(),
+                new_lines: Vec::new(),
+            };
+            while old_count > 0 || new_count > 0 {
+                let line = *lines
+                    .get(i)
+                    .ok_or_else(|| "the patch ends inside a hunk".to_string())?;
+                let (marker, text) = match line.chars().next() {
+                    Some(marker @ (' ' | '-' | '+')) => (marker, &line[1..]),
+                    // Blank context lines lose their leading space in some editors
+                    None => (' ', ""),
+                    Some(_) => return Err(format!("unexpected line in hunk: {}", line)),
+                };
+                let text = format!("{}\n", text);
+                if marker != '+' {
+                    old_count = old_count
+                        .checked_sub(1)
+                        .ok_or_else(|| "a hunk is longer than its header says".to_string())?;
+                    hunk.old_lines.push(text.clone());
+                }
+                if marker != '-' {
+                    new_count = new_count
+                        .checked_sub(1)
+                        .ok_or_else(|| "a hunk is longer than its header says".to_string())?;
+                    hunk.new_lines.push(text);
+                }
+                i += 1;
+
+                // The last line of a side may have no line break
+                while i < lines.len() && lines[i].starts_with('\\') {
+                    let side = match marker {
+                        '-' => &mut hunk.old_lines,
+                        '+' => &mut hunk.new_lines,
+                        _ => {
+                            strip_newline(&mut hunk.old_lines);
+                            &mut hunk.new_lines
+                        }
+                    };
+                    strip_newline(side);
+                    i += 1;
+                }
+            }
+            hunks.push(hunk);
+        }
+
+        file_patches.push(FilePatch {
+            old_path,
+            new_path,
+            hunks,
+        });
+    }
+
+    Ok(file_patches)
+}
+
+/// Path of a `---` or `+++` header, `None` for `/dev/null`
+fn header_path(header: &str, prefix: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or_default().trim_end();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+}
+
+/// Parses `@@ -start,count +start,count @@`, where counts default to 1
+fn hunk_header(line: &str) -> Result<(usize, usize, usize), String> {
+    let invalid = || format!("invalid hunk header: {}", line);
+    let mut ranges = line[3..].split(' ');
+    let old = ranges
+        .next()
+        .and_then(|r| r.strip_prefix('-'))
+        .ok_or_else(invalid)?;
+    let new = ranges
+        .next()
+        .and_then(|r| r.strip_prefix('+'))
+        .ok_or_else(invalid)?;
+
+    let range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = range(old).ok_or_else(invalid)?;
+    let (_, new_count) = range(new).ok_or_else(invalid)?;
+    Ok((old_start, old_count, new_count))
+}
+
+fn strip_newline(lines: &mut [String]) {
+    if let Some(last) = lines.last_mut() {
+        if last.ends_with('\n') {
+            last.pop();
+        }
+    }
+}
+
+/// Applies hunks in order to the lines of a file, `None` for a new file
+fn apply_hunks(original: Option<&str>, hunks: &[Hunk]) -> Result<String, String> {
+    let mut lines: Vec<String> = original
+        .unwrap_or_default()
+        .split_inclusive('\n')
+        .map(str::to_string)
+        .collect();
+
+    // Hunks apply at or after the end of the previous one, shifted as it shifted
+    let mut next = 0usize;
+    let mut offset = 0isize;
+    for (number, hunk) in hunks.iter().enumerate() {
+        let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(next as isize);
+        let position = find_lines(&lines, &hunk.old_lines, next, expected as usize)
+            .ok_or_else(|| format!("hunk {} does not match", number + 1))?;
+
+        lines.splice(
+            position..position + hunk.old_lines.len(),
+            hunk.new_lines.iter().cloned(),
+        );
+        next = position + hunk.new_lines.len();
+        offset += hunk.new_lines.len() as isize - hunk.old_lines.len() as isize;
+        offset += position as isize - expected;
+    }
+
+    Ok(lines.concat())
+}
+
+/// Finds `needle` in `lines` at or after `from`, nearest to `expected` first
+fn find_lines(lines: &[String], needle: &[String], from: usize, expected: usize) -> Option<usize> {
+    let last = lines.len().checked_sub(needle.len())?;
+    let matches_at = |position: usize| lines[position..position + needle.len()] == *needle;
+
+    (0..=last.max(expected))
+        .flat_map(|distance| {
+            let after = expected + distance;
+            let before = expected.checked_sub(distance).filter(|_| distance > 0);
+            [Some(after), before]
+        })
+        .flatten()
+        .filter(|&position| position >= from && position <= last)
+        .find(|&position| matches_at(position))
+}
+
+fn read_text(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    as_text(&bytes)
+        .map(str::to_string)
+        .ok_or_else(|| "binary files cannot be patched".to_string())
+}
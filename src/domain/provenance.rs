@@ -0,0 +1,205 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::domain::error::DomainError;
+use crate::domain::time::rfc3339;
+use crate::domain::{Dependency, LockedDependency};
+
+/// Name of the provenance file written into every target directory
+///
+/// It is not vendored content: manifests, diffs and cleaning skip it.
+pub const PROVENANCE_FILE: &str = ".acdm-source.toml";
+
+/// Where the content of a target directory came from, as of its last update
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    pub name: String,
+    pub repository_url: String,
+    pub repository_type: String,
+    pub revision: String,
+
+    /// Commit the revision resolved to, when the fetcher can tell
+    pub commit: Option<String>,
+
+    /// Mirror the content was fetched from instead of `repository_url`
+    pub mirror: Option<String>,
+
+    pub sparse_paths: Vec<String>,
+
+    /// Patch files applied on top of the upstream content
+    pub patches: Vec<String>,
+
+    /// Version of acdm that wrote the file
+    pub tool_version: String,
+
+    /// When the update happened, in RFC 3339 format, or empty if unknown
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProvenanceToml {
+    name: String,
+    repo: String,
+    #[serde(rename = "type")]
+    repository_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    rev: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mirror: Option<String>,
+    #[serde(default)]
+    sparse_paths: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    patches: Vec<String>,
+    acdm_version: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    updated_at: String,
+}
+
+impl Provenance {
+    /// Records an update of `dependency` that produced `locked`, now
+    pub fn new(dependency: &Dependency, locked: &LockedDependency) -> Self {
+        Self {
+            name: locked.name.clone(),
+            repository_url: locked.repository_url.clone(),
+            repository_type: dependency.repository_type.clone(),
+            revision: locked.revision.clone(),
+            commit: locked.commit.clone(),
+            mirror: locked.mirror.clone(),
+            sparse_paths: locked.sparse_paths.clone(),
+            patches: Vec::new(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            updated_at: rfc3339(SystemTime::now()),
+        }
+    }
+
+    /// Records the patch files applied, relative to the configuration file
+    pub fn with_patches(mut self, patches: &[PathBuf]) -> Self {
+        self.patches = patches
+            .iter()
+            .map(|path| {
+                path.components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect();
+        self
+    }
+
+    /// Location of the provenance file in a target directory
+    pub fn path(target: &Path) -> PathBuf {
+        target.join(PROVENANCE_FILE)
+    }
+
+    /// Reads the provenance file of a target directory, if there is one
+    pub fn read(target: &Path) -> Result<Option<Self>, DomainError> {
+        let path = Self::path(target);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let toml: ProvenanceToml = toml::from_str(&contents).map_err(|e| {
+            DomainError::ConfigurationError(format!(
+                "Failed to parse {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })?;
+
+        Ok(Some(Self {
+            name: toml.name,
+            repository_url: toml.repo,
+            repository_type: toml.repository_type,
+            revision: toml.rev,
+            commit: toml.commit,
+            mirror: toml.mirror,
+            sparse_paths: toml.sparse_paths,
+            patches: toml.patches,
+            tool_version: toml.acdm_version,
+            updated_at: toml.updated_at,
+        }))
+    }
+
+    /// Writes the provenance file into a target directory
+    ///
+    /// A file recording the same update at another time is left as it was,
+    /// so an update that changes nothing does not touch the target.
+    pub fn write(&self, target: &Path) -> Result<(), DomainError> {
+        if let Ok(Some(existing)) = Self::read(target) {
+            if self.same_update(&existing) {
+                return Ok(());
+            }
+        }
+
+        let toml = ProvenanceToml {
+            name: self.name.clone(),
+            repo: self.repository_url.clone(),
+            repository_type: self.repository_type.clone(),
+            rev: self.revision.clone(),
+            commit: self.commit.clone(),
+            mirror: self.mirror.clone(),
+            sparse_paths: self.sparse_paths.clone(),
+            patches: self.patches.clone(),
+            acdm_version: self.tool_version.clone(),
+            updated_at: self.updated_at.clone(),
+        };
+        let contents = toml::to_string(&toml).map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to serialize provenance: {}", e))
+        })?;
+
+        let path = Self::path(target);
+        fs::write(
+            &path,
+            format!(
+                "# Written by acdm on every update. Do not edit it by hand.\n{}",
+                contents
+            ),
+        )
+        .map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to write {}: {}", path.display(), e))
+        })
+    }
+
+    /// Whether two records agree on everything but the time of the update
+    pub fn same_update(&self, other: &Self) -> bool {
+        *self
+            == Self {
+                updated_at: self.updated_at.clone(),
+                ..other.clone()
+            }
+    }
+
+    /// Describes how this record disagrees with the lock entry, if it does
+    pub fn conflicts_with(&self, locked: &LockedDependency) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        if self.repository_url != locked.repository_url {
+            conflicts.push(format!(
+                "repo {} (locked {})",
+                self.repository_url, locked.repository_url
+            ));
+        }
+        if self.revision != locked.revision {
+            conflicts.push(format!(
+                "rev {} (locked {})",
+                self.revision, locked.revision
+            ));
+        }
+        if self.commit != locked.commit {
+            conflicts.push(format!(
+                "commit {} (locked {})",
+                self.commit.as_deref().unwrap_or("none"),
+                locked.commit.as_deref().unwrap_or("none")
+            ));
+        }
+        conflicts
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
 use crate::domain::error::DomainError;
+use crate::domain::Dependency;
 use std::path::{Path, PathBuf};
 
 /// Interface for repository operations
@@ -15,6 +16,104 @@ pub trait RepositoryFetcher {
         patterns: &[String],
         target_path: &Path,
     ) -> Result<(), DomainError>;
+
+    /// Fetches the content of a dependency from `url`, returning the resolved commit
+    ///
+    /// `url` is the configured URL or one of its mirrors. Fetchers serving
+    /// several source types dispatch on the dependency here.
+    fn fetch_dependency(
+        &self,
+        dependency: &Dependency,
+        url: &str,
+        temp_path: &Path,
+    ) -> Result<Option<String>, DomainError> {
+        self.fetch(url, &dependency.revision, temp_path)?;
+        self.resolve_revision(temp_path)
+    }
+
+    /// Extracts the paths of a dependency matching `patterns` from its
+    /// fetched content
+    ///
+    /// Fetchers serving several source types dispatch on the dependency here.
+    fn extract_dependency_paths(
+        &self,
+        _dependency: &Dependency,
+        repo_path: &Path,
+        patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        self.extract_paths(repo_path, patterns, target_path)
+    }
+
+    /// Whether the dependency's `rev` names an upstream revision that can move
+    ///
+    /// Only such sources are checked by `status --remote` and have a log.
+    fn tracks_revisions(&self, _dependency: &Dependency) -> bool {
+        false
+    }
+
+    /// Whether vendored files keep the paths they have upstream, so that
+    /// changes to them apply in the upstream repository
+    fn keeps_upstream_paths(&self, _dependency: &Dependency) -> bool {
+        false
+    }
+
+    /// Checks the source type's options of a dependency, returning the problems found
+    fn validate_dependency(&self, dependency: &Dependency) -> Vec<String> {
+        unknown_options(dependency)
+    }
+
+    /// Resolves the commit checked out in a fetched repository, if supported
+    fn resolve_revision(&self, _repo_path: &Path) -> Result<Option<String>, DomainError> {
+        Ok(None)
+    }
+
+    /// Resolves the commit a revision currently points to upstream, if supported
+    fn resolve_remote_revision(
+        &self,
+        _url: &str,
+        _revision: &str,
+    ) -> Result<Option<String>, DomainError> {
+        Ok(None)
+    }
+
+    /// Resolves the commit the revision of a dependency currently points to
+    /// at `url`, if supported
+    ///
+    /// Fetchers serving several source types dispatch on the dependency here.
+    fn resolve_remote_dependency(
+        &self,
+        dependency: &Dependency,
+        url: &str,
+    ) -> Result<Option<String>, DomainError> {
+        self.resolve_remote_revision(url, &dependency.revision)
+    }
+}
+
+/// Reports each option of a dependency as unknown, for types without options
+pub fn unknown_options(dependency: &Dependency) -> Vec<String> {
+    dependency
+        .options
+        .keys()
+        .map(|key| {
+            format!(
+                "unknown option '{}' for {} sources",
+                key, dependency.repository_type
+            )
+        })
+        .collect()
+}
+
+/// Interface for inspecting upstream history
+pub trait RepositoryHistory {
+    /// Lists commits reachable from `to` but not from `from` that touch the given patterns
+    fn log(
+        &self,
+        url: &str,
+        from: &str,
+        to: &str,
+        patterns: &[String],
+    ) -> Result<Vec<crate::domain::CommitSummary>, DomainError>;
 }
 
 /// Interface for file system operations
@@ -30,6 +129,10 @@ pub trait FileSystemManager {
 
     /// Removes a temporary directory
     fn remove_temp_directory(&self, path: &Path) -> Result<(), DomainError>;
+
+    /// Removes the given files below `root`, then the directories they leave
+    /// empty, up to `root` itself; other files are kept
+    fn remove_files(&self, root: &Path, files: &[PathBuf]) -> Result<(), DomainError>;
 }
 
 /// Git repository status information
@@ -53,6 +156,74 @@ pub trait GitOperations {
 
     /// Gets the current Git repository status
     fn get_status(&self, repo_path: &Path) -> Result<GitStatus, DomainError>;
+
+    /// Gets the status of `paths` alone, relative to `repo_path`
+    fn get_paths_status(
+        &self,
+        repo_path: &Path,
+        paths: &[PathBuf],
+    ) -> Result<GitStatus, DomainError>;
+
+    /// Stages `paths`, relative to `repo_path`, and commits them alone
+    ///
+    /// Changes staged elsewhere stay staged but out of the commit. Returns the
+    /// new commit, or `None` when the paths had nothing to commit.
+    fn commit(
+        &self,
+        repo_path: &Path,
+        paths: &[PathBuf],
+        message: &str,
+    ) -> Result<Option<String>, DomainError>;
+
+    /// Creates a branch at HEAD and switches to it, refusing existing branches
+    fn create_branch(&self, repo_path: &Path, name: &str) -> Result<(), DomainError>;
+
+    /// Author identity git would record for a commit, as `Name <email>`, if configured
+    fn author(&self, _repo_path: &Path) -> Result<Option<String>, DomainError> {
+        Ok(None)
+    }
+}
+
+/// Lets the git implementation be chosen at runtime
+impl<T: GitOperations + ?Sized> GitOperations for Box<T> {
+    fn is_git_repository(&self, path: &Path) -> Result<bool, DomainError> {
+        (**self).is_git_repository(path)
+    }
+
+    fn get_status(&self, repo_path: &Path) -> Result<GitStatus, DomainError> {
+        (**self).get_status(repo_path)
+    }
+
+    fn get_paths_status(
+        &self,
+        repo_path: &Path,
+        paths: &[PathBuf],
+    ) -> Result<GitStatus, DomainError> {
+        (**self).get_paths_status(repo_path, paths)
+    }
+
+    fn commit(
+        &self,
+        repo_path: &Path,
+        paths: &[PathBuf],
+        message: &str,
+    ) -> Result<Option<String>, DomainError> {
+        (**self).commit(repo_path, paths, message)
+    }
+
+    fn create_branch(&self, repo_path: &Path, name: &str) -> Result<(), DomainError> {
+        (**self).create_branch(repo_path, name)
+    }
+
+    fn author(&self, repo_path: &Path) -> Result<Option<String>, DomainError> {
+        (**self).author(repo_path)
+    }
+}
+
+/// Interface for credentials managed outside acdm, such as git credential helpers
+pub trait CredentialStore {
+    /// Looks up a username and password for a URL, if the store has any
+    fn fill(&self, url: &str) -> Result<Option<(String, String)>, DomainError>;
 }
 
 /// Interface for configuration operations
@@ -66,3 +237,12 @@ pub trait ConfigurationRepository {
     /// Initializes a new configuration file
     fn init(&self, path: &Path) -> Result<(), DomainError>;
 }
+
+/// Interface for lock file operations
+pub trait LockFileRepository {
+    /// Loads the lock file, returning an empty lock if it does not exist
+    fn load(&self, path: &Path) -> Result<crate::domain::LockFile, DomainError>;
+
+    /// Saves the lock file
+    fn save(&self, lock: &crate::domain::LockFile, path: &Path) -> Result<(), DomainError>;
+}
@@ -1,8 +1,67 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
+use crate::domain::diff::{self, DependencyDiff};
+use crate::domain::export::{self, ExportedPatch};
+use crate::domain::manifest::{self, Manifest};
+use crate::domain::merge::{self, MergedFile};
+use crate::domain::mirrors::Mirrors;
+use crate::domain::patch;
+use crate::domain::provenance::Provenance;
 use crate::domain::repositories::{FileSystemManager, GitOperations, RepositoryFetcher};
-use crate::domain::{Dependency, DomainError};
-use std::path::Path;
+use crate::domain::transform;
+use crate::domain::{Dependency, DomainError, LocalChanges, LockFile, LockedDependency};
+use log::debug;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Progress notifications emitted while updating dependencies
+pub enum UpdateProgress<'a> {
+    Started(&'a Dependency),
+    Finished(&'a UpdateOutcome),
+}
+
+/// A dependency updated, with what became of its local modifications
+#[derive(Debug, Clone)]
+pub struct UpdateOutcome {
+    /// Resolved state for the lock file, hashing the upstream content
+    pub locked: LockedDependency,
+
+    /// Locally modified files carried forward, relative to the target
+    pub kept_local: Vec<String>,
+
+    /// Files whose local and upstream changes conflict, relative to the target
+    pub conflicts: Vec<String>,
+}
+
+/// A dependency fetched, and patched if it has patches, ready to be installed
+#[derive(Debug, Clone)]
+pub struct PreparedDependency {
+    /// The fetched repository
+    temp_dir: PathBuf,
+
+    /// The extracted and patched content, for dependencies with patches
+    staging_dir: Option<PathBuf>,
+
+    /// Patch files applied, relative to the repository root
+    patches: Vec<PathBuf>,
+
+    commit: Option<String>,
+    mirror: Option<String>,
+}
+
+/// Local versions of files merged with their upstream versions
+#[derive(Debug, Default)]
+struct MergedLocal {
+    /// Content to write over the upstream version, by path relative to the target
+    files: Vec<(String, Vec<u8>)>,
+
+    /// Files carried forward without conflicts
+    kept: Vec<String>,
+
+    /// Files whose local and upstream changes conflict
+    conflicts: Vec<String>,
+}
 
 /// Service for updating a single dependency
 pub struct DependencyUpdater<R, F, G>
@@ -14,6 +73,7 @@ where
     repository_fetcher: R,
     file_system_manager: F,
     git_operations: G,
+    mirrors: Mirrors,
 }
 
 impl<R, F, G> DependencyUpdater<R, F, G>
@@ -27,24 +87,258 @@ where
             repository_fetcher,
             file_system_manager,
             git_operations,
+            mirrors: Mirrors::default(),
         }
     }
 
-    /// Updates a single dependency
-    pub fn update(&self, dependency: &Dependency, repo_root: &Path) -> Result<(), DomainError> {
+    /// Fetch through the configured mirrors before the canonical URL
+    pub fn with_mirrors(mut self, mirrors: Mirrors) -> Self {
+        self.mirrors = mirrors;
+        self
+    }
+
+    /// Fetches a dependency, returning the resolved commit and the mirror used, if any
+    fn fetch(
+        &self,
+        dependency: &Dependency,
+        temp_dir: &Path,
+    ) -> Result<(Option<String>, Option<String>), DomainError> {
+        self.mirrors.try_each(&dependency.repository_url, |url| {
+            self.repository_fetcher
+                .fetch_dependency(dependency, url, temp_dir)
+        })
+    }
+
+    /// Files added or modified below the target since `previous` was recorded
+    ///
+    /// Files deleted locally are not reported: an update restores them. Lock
+    /// entries without file hashes, from before they were recorded, report none.
+    pub fn local_modifications(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+        previous: Option<&LockedDependency>,
+    ) -> Result<Vec<String>, DomainError> {
+        let previous = match previous {
+            Some(previous)
+                if previous.target_location == dependency.target_location
+                    && !previous.files.is_empty() =>
+            {
+                previous
+            }
+            _ => return Ok(Vec::new()),
+        };
+
+        let drift = manifest::compare_manifest(
+            &repo_root.join(&dependency.target_location),
+            &previous.files,
+        )?;
+        let mut modified: Vec<String> = drift.modified.into_iter().chain(drift.added).collect();
+        modified.sort();
+        Ok(modified)
+    }
+
+    /// Updates a single dependency, returning its resolved state for the lock file
+    ///
+    /// `previous` is the dependency's lock entry, against which local
+    /// modifications are detected and then handled as `local_changes` says.
+    /// With `refuse_conflicts`, kept modifications that would conflict with
+    /// upstream changes fail the update before the target changes.
+    pub fn update(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+        previous: Option<&LockedDependency>,
+        local_changes: LocalChanges,
+        refuse_conflicts: bool,
+    ) -> Result<UpdateOutcome, DomainError> {
+        let mut prepared = self.prepare(dependency, repo_root)?;
+        if local_changes == LocalChanges::Keep && refuse_conflicts {
+            match self.conflicts(dependency, repo_root, &mut prepared, previous) {
+                Ok(conflicts) if conflicts.is_empty() => {}
+                conflicts => {
+                    self.discard(prepared)?;
+                    return Err(conflicts_error(
+                        conflicts?
+                            .iter()
+                            .map(|path| dependency.target_location.join(path))
+                            .collect(),
+                    ));
+                }
+            }
+        }
+        self.install(dependency, repo_root, prepared, previous, local_changes)
+    }
+
+    /// Fetches a dependency and applies its transforms and patches, leaving
+    /// the target untouched
+    ///
+    /// Content with transforms or patches is extracted and rewritten in a
+    /// staging directory, so a patch that no longer applies fails the update
+    /// before any target is cleaned.
+    pub fn prepare(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+    ) -> Result<PreparedDependency, DomainError> {
+        let patches = patch::resolve(repo_root, &dependency.patches)?;
+
         // Create a temporary directory for fetching the repository
         let temp_dir = self.file_system_manager.create_temp_directory()?;
 
-        // Fetch the repository to the temporary directory
-        self.repository_fetcher.fetch(
-            &dependency.repository_url,
-            &dependency.revision,
-            &temp_dir,
-        )?;
+        // Fetch the repository to the temporary directory, recording which
+        // commit the revision resolved to
+        let (commit, mirror) = match self.fetch(dependency, &temp_dir) {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                self.file_system_manager.remove_temp_directory(&temp_dir)?;
+                return Err(e);
+            }
+        };
+
+        let mut prepared = PreparedDependency {
+            temp_dir,
+            staging_dir: None,
+            patches: Vec::new(),
+            commit,
+            mirror,
+        };
+        if patches.is_empty() && dependency.transforms.is_empty() {
+            return Ok(prepared);
+        }
 
+        // Extract, transform and patch the content in a staging directory
+        let staging_dir = match self.file_system_manager.create_temp_directory() {
+            Ok(staging_dir) => staging_dir,
+            Err(e) => {
+                self.discard(prepared)?;
+                return Err(e);
+            }
+        };
+        prepared.staging_dir = Some(staging_dir.clone());
+        let staged = self
+            .repository_fetcher
+            .extract_dependency_paths(
+                dependency,
+                &prepared.temp_dir,
+                &dependency.sparse_paths,
+                &staging_dir,
+            )
+            .and_then(|_| transform::apply_all(&dependency.transforms, &staging_dir))
+            .and_then(|_| patch::apply_all(&patches, &staging_dir));
+        if let Err(e) = staged {
+            self.discard(prepared)?;
+            return Err(e);
+        }
+
+        prepared.patches = patches
+            .iter()
+            .map(|path| path.strip_prefix(repo_root).unwrap_or(path).to_path_buf())
+            .collect();
+        Ok(prepared)
+    }
+
+    /// Removes the temporary directories of a dependency that will not be installed
+    pub fn discard(&self, prepared: PreparedDependency) -> Result<(), DomainError> {
+        if let Some(staging_dir) = &prepared.staging_dir {
+            self.file_system_manager
+                .remove_temp_directory(staging_dir)?;
+        }
+        self.file_system_manager
+            .remove_temp_directory(&prepared.temp_dir)
+    }
+
+    /// Replaces the content of a dependency's target with the prepared content
+    pub fn install(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+        prepared: PreparedDependency,
+        previous: Option<&LockedDependency>,
+        local_changes: LocalChanges,
+    ) -> Result<UpdateOutcome, DomainError> {
         // Determine the absolute target path
         let target_path = repo_root.join(&dependency.target_location);
 
+        let written = self.write_target(dependency, repo_root, &prepared, previous, local_changes);
+
+        // Clean up the temporary directories, whether or not the target was written
+        let PreparedDependency {
+            commit,
+            mirror,
+            patches,
+            ..
+        } = prepared.clone();
+        self.discard(prepared)?;
+        let (files, local_files) = written?;
+
+        // Put the local modifications back on top of the upstream content
+        let (kept_local, conflicts) = match previous {
+            Some(previous) if !local_files.is_empty() => {
+                self.carry_forward(dependency, previous, repo_root, local_files, &files)?
+            }
+            _ => (Vec::new(), Vec::new()),
+        };
+
+        let locked = LockedDependency {
+            name: dependency.name.clone(),
+            repository_url: dependency.repository_url.clone(),
+            revision: dependency.revision.clone(),
+            commit,
+            mirror,
+            sparse_paths: dependency.sparse_paths.clone(),
+            target_location: dependency.target_location.clone(),
+            patches: patch::fingerprint(repo_root, &patches)?,
+            transforms: dependency.transforms.clone(),
+            options: dependency.options.clone(),
+            files,
+        };
+
+        // Record where the content came from next to it
+        Provenance::new(dependency, &locked)
+            .with_patches(&patches)
+            .write(&target_path)?;
+
+        Ok(UpdateOutcome {
+            locked,
+            kept_local,
+            conflicts,
+        })
+    }
+
+    /// Replaces the files of a dependency's target with the prepared content,
+    /// returning the hashes of the upstream files and the local versions of
+    /// the modified files to keep
+    fn write_target(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+        prepared: &PreparedDependency,
+        previous: Option<&LockedDependency>,
+        local_changes: LocalChanges,
+    ) -> Result<(Manifest, BTreeMap<String, Vec<u8>>), DomainError> {
+        let target_path = repo_root.join(&dependency.target_location);
+
+        // Check for local modifications before the target is cleaned
+        let modified = self.local_modifications(dependency, repo_root, previous)?;
+        if !modified.is_empty() && local_changes == LocalChanges::Refuse {
+            return Err(local_modifications_error(
+                modified
+                    .iter()
+                    .map(|path| dependency.target_location.join(path))
+                    .collect(),
+            ));
+        }
+
+        // Keep the local versions aside while the target is cleaned
+        let mut local_files = BTreeMap::new();
+        if local_changes == LocalChanges::Keep {
+            for path in modified {
+                let content = read_file(&target_path.join(&path))?;
+                local_files.insert(path, content);
+            }
+        }
+
         // Create the target directory if it doesn't exist
         if !target_path.exists() {
             std::fs::create_dir_all(&target_path).map_err(|e| {
@@ -55,19 +349,332 @@ where
         // Clean the target directory
         self.file_system_manager.clean_directory(&target_path)?;
 
-        // Extract paths from the repository to the target directory
-        self.repository_fetcher
-            .extract_paths(&temp_dir, &dependency.sparse_paths, &target_path)?;
+        // Extract paths from the repository to the target directory, or copy
+        // the rewritten content over
+        match &prepared.staging_dir {
+            Some(staging_dir) => self
+                .file_system_manager
+                .copy_content(staging_dir, &target_path)?,
+            None => self.repository_fetcher.extract_dependency_paths(
+                dependency,
+                &prepared.temp_dir,
+                &dependency.sparse_paths,
+                &target_path,
+            )?,
+        }
+
+        // Record the hashes of the extracted files
+        let files = manifest::compute_manifest(&target_path)?;
 
         // Check if repo_root is valid and is a git repository
         if repo_root.exists() && self.git_operations.is_git_repository(repo_root)? {
             // No staging action needed anymore
         }
 
-        // Clean up the temporary directory
+        Ok((files, local_files))
+    }
+
+    /// Writes local versions of files over the new upstream content
+    ///
+    /// Returns the files kept and those in conflict.
+    fn carry_forward(
+        &self,
+        dependency: &Dependency,
+        previous: &LockedDependency,
+        repo_root: &Path,
+        local_files: BTreeMap<String, Vec<u8>>,
+        upstream: &Manifest,
+    ) -> Result<(Vec<String>, Vec<String>), DomainError> {
+        let target_path = repo_root.join(&dependency.target_location);
+        let merged = self.merge_local(
+            dependency,
+            previous,
+            repo_root,
+            local_files,
+            upstream,
+            &target_path,
+        )?;
+        for (path, content) in &merged.files {
+            write_file(&target_path.join(path), content)?;
+        }
+        Ok((merged.kept, merged.conflicts))
+    }
+
+    /// Merges local versions of files with their upstream versions, found
+    /// below `upstream_dir`
+    ///
+    /// A file upstream left alone keeps its local version. A file changed on
+    /// both sides is merged against its previously vendored version, fetched
+    /// again at the locked commit, or against an empty file for sources
+    /// without commits.
+    fn merge_local(
+        &self,
+        dependency: &Dependency,
+        previous: &LockedDependency,
+        repo_root: &Path,
+        local_files: BTreeMap<String, Vec<u8>>,
+        upstream: &Manifest,
+        upstream_dir: &Path,
+    ) -> Result<MergedLocal, DomainError> {
+        let mut merged_local = MergedLocal::default();
+        let mut base_dir: Option<Option<PathBuf>> = None;
+
+        let merge_all = || -> Result<(), DomainError> {
+            for (path, local) in local_files {
+                let base_hash = previous.files.get(&path);
+                let upstream_hash = upstream.get(&path);
+                if upstream_hash == Some(&manifest::hash_bytes(&local)) {
+                    continue;
+                }
+
+                let merged = if upstream_hash == base_hash {
+                    Some(MergedFile {
+                        content: local.clone(),
+                        conflicted: false,
+                    })
+                } else if upstream_hash.is_some() {
+                    // Both sides changed the file, so merge it against its previous version
+                    if base_hash.is_some() && base_dir.is_none() {
+                        base_dir = Some(self.fetch_previous(dependency, previous, repo_root)?);
+                    }
+                    let base_path = base_dir
+                        .iter()
+                        .flatten()
+                        .map(|base_dir| base_dir.join(&path))
+                        .find(|base_path| base_hash.is_some() && base_path.is_file());
+                    let base = match base_path {
+                        Some(base_path) => read_file(&base_path)?,
+                        None => Vec::new(),
+                    };
+                    merge::merge(&base, &local, &read_file(&upstream_dir.join(&path))?)
+                } else {
+                    // Upstream removed a file modified locally
+                    None
+                };
+
+                let content = match merged {
+                    Some(merged) if !merged.conflicted => {
+                        merged_local.kept.push(path.clone());
+                        merged.content
+                    }
+                    Some(merged) => {
+                        merged_local.conflicts.push(path.clone());
+                        merged.content
+                    }
+                    None => {
+                        merged_local.conflicts.push(path.clone());
+                        local
+                    }
+                };
+                merged_local.files.push((path, content));
+            }
+            Ok(())
+        };
+        let merged = merge_all();
+
+        if let Some(Some(base_dir)) = base_dir {
+            self.file_system_manager.remove_temp_directory(&base_dir)?;
+        }
+        merged.map(|_| merged_local)
+    }
+
+    /// Files of a prepared dependency whose local modifications would conflict
+    /// with the upstream changes, relative to the target
+    ///
+    /// Nothing is written to the target. The content is staged if it was not
+    /// already, so that it can be compared with the local files.
+    pub fn conflicts(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+        prepared: &mut PreparedDependency,
+        previous: Option<&LockedDependency>,
+    ) -> Result<Vec<String>, DomainError> {
+        let modified = self.local_modifications(dependency, repo_root, previous)?;
+        let previous = match previous {
+            Some(previous) if !modified.is_empty() => previous,
+            _ => return Ok(Vec::new()),
+        };
+
+        let staging_dir = match &prepared.staging_dir {
+            Some(staging_dir) => staging_dir.clone(),
+            None => {
+                let staging_dir = self.file_system_manager.create_temp_directory()?;
+                prepared.staging_dir = Some(staging_dir.clone());
+                self.repository_fetcher.extract_dependency_paths(
+                    dependency,
+                    &prepared.temp_dir,
+                    &dependency.sparse_paths,
+                    &staging_dir,
+                )?;
+                staging_dir
+            }
+        };
+
+        let target_path = repo_root.join(&dependency.target_location);
+        let mut local_files = BTreeMap::new();
+        for path in modified {
+            let content = read_file(&target_path.join(&path))?;
+            local_files.insert(path, content);
+        }
+        let upstream = manifest::compute_manifest(&staging_dir)?;
+        let merged = self.merge_local(
+            dependency,
+            previous,
+            repo_root,
+            local_files,
+            &upstream,
+            &staging_dir,
+        )?;
+        Ok(merged.conflicts)
+    }
+
+    /// Extracts the content vendored at the locked commit into a temporary
+    /// directory, or returns `None` for sources without commits
+    ///
+    /// The patches are applied when they still apply to that content.
+    fn fetch_previous(
+        &self,
+        dependency: &Dependency,
+        previous: &LockedDependency,
+        repo_root: &Path,
+    ) -> Result<Option<PathBuf>, DomainError> {
+        if previous.commit.is_none() {
+            return Ok(None);
+        }
+
+        let base_dir = self.extract_locked(dependency, previous)?;
+        let patches = match patch::resolve(repo_root, &dependency.patches) {
+            Ok(patches) => patches,
+            Err(e) => {
+                self.file_system_manager.remove_temp_directory(&base_dir)?;
+                return Err(e);
+            }
+        };
+        if let Err(e) = patch::apply_all(&patches, &base_dir) {
+            debug!("Merging against the unpatched content: {}", e);
+        }
+
+        Ok(Some(base_dir))
+    }
+
+    /// Recreates the content last installed for a dependency, with its
+    /// patches, in a temporary directory
+    ///
+    /// The content is fetched at the locked commit, or at the configured
+    /// revision for sources without commits.
+    pub fn rebuild(
+        &self,
+        dependency: &Dependency,
+        previous: &LockedDependency,
+        repo_root: &Path,
+    ) -> Result<PathBuf, DomainError> {
+        let content_dir = self.extract_locked(dependency, previous)?;
+        let applied = patch::resolve(repo_root, &dependency.patches)
+            .and_then(|patches| patch::apply_all(&patches, &content_dir));
+        if let Err(e) = applied {
+            self.file_system_manager
+                .remove_temp_directory(&content_dir)?;
+            return Err(e);
+        }
+        Ok(content_dir)
+    }
+
+    /// Extracts the paths recorded in a lock entry at its commit into a
+    /// temporary directory, and applies the transforms
+    fn extract_locked(
+        &self,
+        dependency: &Dependency,
+        previous: &LockedDependency,
+    ) -> Result<PathBuf, DomainError> {
+        let locked_dependency = Dependency {
+            revision: previous
+                .commit
+                .clone()
+                .unwrap_or_else(|| dependency.revision.clone()),
+            sparse_paths: previous.sparse_paths.clone(),
+            ..dependency.clone()
+        };
+
+        let temp_dir = self.file_system_manager.create_temp_directory()?;
+        let content_dir = match self.file_system_manager.create_temp_directory() {
+            Ok(content_dir) => content_dir,
+            Err(e) => {
+                self.file_system_manager.remove_temp_directory(&temp_dir)?;
+                return Err(e);
+            }
+        };
+        let extracted = self
+            .fetch(&locked_dependency, &temp_dir)
+            .and_then(|_| {
+                self.repository_fetcher.extract_dependency_paths(
+                    dependency,
+                    &temp_dir,
+                    &previous.sparse_paths,
+                    &content_dir,
+                )
+            })
+            .and_then(|_| transform::apply_all(&dependency.transforms, &content_dir));
         self.file_system_manager.remove_temp_directory(&temp_dir)?;
+        if let Err(e) = extracted {
+            self.file_system_manager
+                .remove_temp_directory(&content_dir)?;
+            return Err(e);
+        }
 
-        Ok(())
+        Ok(content_dir)
+    }
+
+    /// Computes the changes an update would make without touching the target
+    pub fn preview(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+        with_text: bool,
+    ) -> Result<DependencyDiff, DomainError> {
+        // Fetch the repository to a temporary directory, and extract the
+        // selected paths into a separate staging directory
+        let temp_dir = self.file_system_manager.create_temp_directory()?;
+        let staging_dir = match self.file_system_manager.create_temp_directory() {
+            Ok(staging_dir) => staging_dir,
+            Err(e) => {
+                self.file_system_manager.remove_temp_directory(&temp_dir)?;
+                return Err(e);
+            }
+        };
+
+        // Compare the staged content with what is currently vendored
+        let target_path = repo_root.join(&dependency.target_location);
+        let compared = self
+            .fetch(dependency, &temp_dir)
+            .and_then(|_| {
+                self.repository_fetcher.extract_dependency_paths(
+                    dependency,
+                    &temp_dir,
+                    &dependency.sparse_paths,
+                    &staging_dir,
+                )
+            })
+            .and_then(|_| transform::apply_all(&dependency.transforms, &staging_dir))
+            .and_then(|transforms| {
+                let patches = patch::resolve(repo_root, &dependency.patches)?;
+                patch::apply_all(&patches, &staging_dir)?;
+                let changes = diff::compare_directories(&staging_dir, &target_path, with_text)?;
+                Ok((changes, transforms))
+            });
+
+        // Clean up the temporary directories, whether or not the comparison succeeded
+        self.file_system_manager
+            .remove_temp_directory(&staging_dir)?;
+        self.file_system_manager.remove_temp_directory(&temp_dir)?;
+        let (changes, transforms) = compared?;
+
+        Ok(DependencyDiff {
+            name: dependency.name.clone(),
+            target_location: dependency.target_location.clone(),
+            changes,
+            transforms,
+        })
     }
 }
 
@@ -97,18 +704,405 @@ where
         }
     }
 
-    /// Updates all dependencies
+    /// Fetch through the configured mirrors before the canonical URL
+    pub fn with_mirrors(mut self, mirrors: Mirrors) -> Self {
+        self.dependency_updater = self.dependency_updater.with_mirrors(mirrors);
+        self
+    }
+
+    /// Updates all dependencies, reporting progress as each one is processed
+    ///
+    /// With `refuse_conflicts`, local modifications kept with
+    /// [`LocalChanges::Keep`] that would conflict with upstream changes fail
+    /// the update before any target changes, instead of being left between
+    /// conflict markers.
     pub fn update_all(
         &self,
         dependencies: &[Dependency],
         repo_root: &Path,
-        _force: bool, // Unused but necessary for API compatibility
+        lock: &LockFile,
+        local_changes: LocalChanges,
+        refuse_conflicts: bool,
+        progress: &mut dyn FnMut(UpdateProgress),
+    ) -> Result<Vec<UpdateOutcome>, DomainError> {
+        // Refuse before any target changes if one holds local modifications
+        if local_changes == LocalChanges::Refuse {
+            let mut modified = Vec::new();
+            for dependency in dependencies {
+                let paths = self.dependency_updater.local_modifications(
+                    dependency,
+                    repo_root,
+                    lock.get(&dependency.name),
+                )?;
+                modified.extend(
+                    paths
+                        .iter()
+                        .map(|path| dependency.target_location.join(path)),
+                );
+            }
+            if !modified.is_empty() {
+                return Err(local_modifications_error(modified));
+            }
+        }
+
+        // A single dependency has no others to wait for
+        if let [dependency] = dependencies {
+            progress(UpdateProgress::Started(dependency));
+            let outcome = self.dependency_updater.update(
+                dependency,
+                repo_root,
+                lock.get(&dependency.name),
+                local_changes,
+                refuse_conflicts,
+            )?;
+            progress(UpdateProgress::Finished(&outcome));
+            return Ok(vec![outcome]);
+        }
+
+        let mut prepared = self.prepare_all(dependencies, repo_root, progress)?;
+        if local_changes == LocalChanges::Keep && refuse_conflicts {
+            let conflicts = self.find_conflicts(dependencies, repo_root, lock, &mut prepared);
+            match conflicts {
+                Ok(conflicts) if conflicts.is_empty() => {}
+                conflicts => {
+                    for entry in prepared {
+                        self.dependency_updater.discard(entry)?;
+                    }
+                    return Err(conflicts_error(conflicts?));
+                }
+            }
+        }
+
+        let mut outcomes = Vec::with_capacity(dependencies.len());
+
+        // Install each dependency
+        let mut prepared = prepared.into_iter();
+        for dependency in dependencies {
+            let Some(entry) = prepared.next() else {
+                break;
+            };
+            let installed = self.dependency_updater.install(
+                dependency,
+                repo_root,
+                entry,
+                lock.get(&dependency.name),
+                local_changes,
+            );
+            let outcome = match installed {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    // Dependencies not installed yet keep their targets as they were
+                    for entry in prepared {
+                        self.dependency_updater.discard(entry)?;
+                    }
+                    return Err(e);
+                }
+            };
+            progress(UpdateProgress::Finished(&outcome));
+            outcomes.push(outcome);
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Fails when an update keeping local modifications would leave some in
+    /// conflict with upstream changes
+    ///
+    /// Every dependency is fetched and staged to compare, and no target changes.
+    pub fn check_conflicts(
+        &self,
+        dependencies: &[Dependency],
+        repo_root: &Path,
+        lock: &LockFile,
     ) -> Result<(), DomainError> {
-        // Update each dependency
+        let mut prepared = self.prepare_all(dependencies, repo_root, &mut |_| {})?;
+        let conflicts = self.find_conflicts(dependencies, repo_root, lock, &mut prepared);
+        for entry in prepared {
+            self.dependency_updater.discard(entry)?;
+        }
+        match conflicts? {
+            conflicts if conflicts.is_empty() => Ok(()),
+            conflicts => Err(conflicts_error(conflicts)),
+        }
+    }
+
+    /// Fetches and patches every dependency, so that a failure leaves all
+    /// targets as they were
+    fn prepare_all(
+        &self,
+        dependencies: &[Dependency],
+        repo_root: &Path,
+        progress: &mut dyn FnMut(UpdateProgress),
+    ) -> Result<Vec<PreparedDependency>, DomainError> {
+        let mut prepared = Vec::with_capacity(dependencies.len());
         for dependency in dependencies {
-            self.dependency_updater.update(dependency, repo_root)?;
+            progress(UpdateProgress::Started(dependency));
+            match self.dependency_updater.prepare(dependency, repo_root) {
+                Ok(entry) => prepared.push(entry),
+                Err(e) => {
+                    for entry in prepared {
+                        self.dependency_updater.discard(entry)?;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(prepared)
+    }
+
+    fn find_conflicts(
+        &self,
+        dependencies: &[Dependency],
+        repo_root: &Path,
+        lock: &LockFile,
+        prepared: &mut [PreparedDependency],
+    ) -> Result<Vec<PathBuf>, DomainError> {
+        let mut conflicts = Vec::new();
+        for (dependency, entry) in dependencies.iter().zip(prepared) {
+            let paths = self.dependency_updater.conflicts(
+                dependency,
+                repo_root,
+                entry,
+                lock.get(&dependency.name),
+            )?;
+            conflicts.extend(
+                paths
+                    .iter()
+                    .map(|path| dependency.target_location.join(path)),
+            );
         }
+        Ok(conflicts)
+    }
+
+    /// Turns the local modifications of a dependency's target into a patch
+    ///
+    /// The patch is a unified diff, relative to the target, from the content
+    /// last installed to the current files. Returns `None` when the target
+    /// has no local modifications.
+    pub fn create_patch(
+        &self,
+        dependency: &Dependency,
+        previous: &LockedDependency,
+        repo_root: &Path,
+    ) -> Result<Option<String>, DomainError> {
+        let installed_dir = self
+            .dependency_updater
+            .rebuild(dependency, previous, repo_root)?;
+        let created = self.diff_against(dependency, repo_root, &installed_dir);
+        self.dependency_updater
+            .file_system_manager
+            .remove_temp_directory(&installed_dir)?;
+        created
+    }
+
+    fn diff_against(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+        installed_dir: &Path,
+    ) -> Result<Option<String>, DomainError> {
+        let target_path = repo_root.join(&dependency.target_location);
+        let mut patch_text = String::new();
+        for change in diff::compare_directories(&target_path, installed_dir, true)? {
+            let text_diff = change.text_diff.unwrap_or_default();
+            if text_diff.starts_with("Binary files ") {
+                return Err(DomainError::PatchError(format!(
+                    "{} is binary and cannot be included in a patch",
+                    change.path.display()
+                )));
+            }
+            patch_text.push_str(&text_diff);
+        }
+        if patch_text.is_empty() {
+            return Ok(None);
+        }
+
+        // The patch must turn the installed content into the current one
+        patch::apply(&patch_text, installed_dir).map_err(DomainError::PatchError)?;
+        if manifest::compute_manifest(installed_dir)? != manifest::compute_manifest(&target_path)? {
+            return Err(DomainError::PatchError(
+                "The local modifications cannot be reproduced by a patch".to_string(),
+            ));
+        }
+
+        Ok(Some(patch_text))
+    }
+
+    /// Collects the changes made to a dependency while vendoring it, as a
+    /// series to contribute upstream
+    ///
+    /// Each patch file becomes one change, followed by one for the local
+    /// modifications of the target, if any. The changes are taken against
+    /// the content at the locked commit.
+    pub fn export_changes(
+        &self,
+        dependency: &Dependency,
+        previous: &LockedDependency,
+        repo_root: &Path,
+    ) -> Result<Vec<ExportedPatch>, DomainError> {
+        let updater = &self.dependency_updater;
+
+        // Paths chosen by the source, such as a plugin's, mean nothing upstream
+        if !updater.repository_fetcher.keeps_upstream_paths(dependency) {
+            return Err(DomainError::InvalidDependencyError(format!(
+                "'{}' is a {} source, whose files do not keep their upstream paths, so its changes cannot be exported",
+                dependency.name, dependency.repository_type
+            )));
+        }
+
+        // The changes are carried over to the untransformed content, so
+        // that they apply upstream
+        let upstream_dependency = Dependency {
+            transforms: Vec::new(),
+            ..dependency.clone()
+        };
+        let mut dirs = vec![updater.extract_locked(&upstream_dependency, previous)?];
+        let exported = (|| {
+            for _ in 0..3 {
+                dirs.push(updater.file_system_manager.create_temp_directory()?);
+            }
+            updater
+                .file_system_manager
+                .copy_content(&dirs[0], &dirs[1])?;
+            let reports = transform::apply_all(&dependency.transforms, &dirs[1])?;
+            let renamed = transform::renamed_files(&dependency.transforms, &reports);
+            self.export_between(dependency, repo_root, &dirs, &renamed)
+        })();
+
+        for dir in &dirs {
+            updater.file_system_manager.remove_temp_directory(dir)?;
+        }
+        exported
+    }
+
+    /// Diffs the content before and after every patch, then against the
+    /// target, once carried over to the untransformed content
+    ///
+    /// `dirs` holds the untransformed content at the locked commit, the same
+    /// content transformed, and two work directories, which the transformed
+    /// and untransformed content alternate with.
+    fn export_between(
+        &self,
+        dependency: &Dependency,
+        repo_root: &Path,
+        dirs: &[PathBuf],
+        renamed: &BTreeMap<PathBuf, PathBuf>,
+    ) -> Result<Vec<ExportedPatch>, DomainError> {
+        let file_system_manager = &self.dependency_updater.file_system_manager;
+        let mut exported = Vec::new();
+        let (mut upstream_before, mut before) = (dirs[0].as_path(), dirs[1].as_path());
+        let (mut after, mut upstream_after) = (dirs[2].as_path(), dirs[3].as_path());
+
+        // Carries the changes from `before` to `after` over to the
+        // untransformed content, and diffs it
+        let diff_upstream =
+            |before: &Path, after: &Path, upstream_before: &Path, upstream_after: &Path| {
+                file_system_manager.clean_directory(upstream_after)?;
+                file_system_manager.copy_content(upstream_before, upstream_after)?;
+                export::untransform(before, after, upstream_after, renamed)?;
+                export::git_diff(upstream_before, upstream_after)
+            };
+
+        for patch_file in patch::resolve(repo_root, &dependency.patches)? {
+            file_system_manager.clean_directory(after)?;
+            file_system_manager.copy_content(before, after)?;
+            patch::apply_all(std::slice::from_ref(&patch_file), after)?;
+
+            let (diff, files) = diff_upstream(before, after, upstream_before, upstream_after)?;
+            if !diff.is_empty() {
+                let contents = fs::read_to_string(&patch_file).map_err(|e| {
+                    DomainError::FileSystemError(format!(
+                        "Failed to read {}: {}",
+                        patch_file.display(),
+                        e
+                    ))
+                })?;
+                let relative = patch_file.strip_prefix(repo_root).unwrap_or(&patch_file);
+                let (subject, body) = export::describe(relative, &contents);
+                exported.push(ExportedPatch {
+                    subject,
+                    body,
+                    files,
+                    diff,
+                });
+            }
+            std::mem::swap(&mut before, &mut after);
+            std::mem::swap(&mut upstream_before, &mut upstream_after);
+        }
+
+        let target_path = repo_root.join(&dependency.target_location);
+        let (diff, files) = diff_upstream(before, &target_path, upstream_before, upstream_after)?;
+        if !diff.is_empty() {
+            exported.push(ExportedPatch {
+                subject: format!(
+                    "Local modifications made while vendoring {}",
+                    dependency.name
+                ),
+                body: String::new(),
+                files,
+                diff,
+            });
+        }
+
+        Ok(exported)
+    }
+
+    /// Previews the changes an update of all dependencies would make
+    pub fn preview_all(
+        &self,
+        dependencies: &[Dependency],
+        repo_root: &Path,
+        with_text: bool,
+    ) -> Result<Vec<DependencyDiff>, DomainError> {
+        dependencies
+            .iter()
+            .map(|dependency| {
+                self.dependency_updater
+                    .preview(dependency, repo_root, with_text)
+            })
+            .collect()
+    }
+}
+
+fn local_modifications_error(paths: Vec<PathBuf>) -> DomainError {
+    DomainError::LocalModificationsError(format!(
+        "{}. Update with --keep-local to merge them with the upstream changes or --discard-local to overwrite them",
+        paths
+            .iter()
+            .map(|path| path.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+fn conflicts_error(paths: Vec<PathBuf>) -> DomainError {
+    DomainError::ConflictError(format!(
+        "{}. Nothing was changed; update without --commit or --branch to merge them with conflict markers, or with --discard-local to overwrite them",
+        paths
+            .iter()
+            .map(|path| path.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, DomainError> {
+    fs::read(path).map_err(|e| {
+        DomainError::FileSystemError(format!("Failed to read {}: {}", path.display(), e))
+    })
+}
 
-        Ok(())
+fn write_file(path: &Path, content: &[u8]) -> Result<(), DomainError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            DomainError::FileSystemError(format!(
+                "Failed to create directory {}: {}",
+                parent.display(),
+                e
+            ))
+        })?;
     }
+    fs::write(path, content).map_err(|e| {
+        DomainError::FileSystemError(format!("Failed to write {}: {}", path.display(), e))
+    })
 }
@@ -0,0 +1,186 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::domain::error::DomainError;
+use crate::domain::repositories::{RepositoryFetcher, RepositoryHistory};
+use crate::domain::{CommitSummary, Dependency, DEFAULT_SOURCE_TYPE};
+
+/// Backends found on demand, keyed by source type
+pub type DiscoveredBackends = BTreeMap<String, Box<dyn RepositoryFetcher>>;
+
+/// Source backends keyed by the `type` name used in `acdm.toml`
+///
+/// The registry is itself a fetcher, dispatching each dependency to the
+/// backend of its type. Operations that only know a URL or a checkout go to
+/// the backend of the default type.
+#[derive(Default)]
+pub struct SourceRegistry {
+    backends: BTreeMap<String, Box<dyn RepositoryFetcher>>,
+    history: Option<Box<dyn RepositoryHistory>>,
+    discover: Option<Box<dyn Fn() -> DiscoveredBackends>>,
+    discovered: OnceCell<DiscoveredBackends>,
+}
+
+impl SourceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `backend` for sources of type `name`
+    pub fn with_backend(mut self, name: &str, backend: impl RepositoryFetcher + 'static) -> Self {
+        self.backends.insert(name.to_string(), Box::new(backend));
+        self
+    }
+
+    /// Uses `history` to list upstream commits of sources tracking revisions
+    pub fn with_history(mut self, history: impl RepositoryHistory + 'static) -> Self {
+        self.history = Some(Box::new(history));
+        self
+    }
+
+    /// Looks up backends for types that are not registered with `discover`,
+    /// the first time such a type is needed
+    ///
+    /// Registered backends take precedence over discovered ones.
+    pub fn with_discovery(mut self, discover: impl Fn() -> DiscoveredBackends + 'static) -> Self {
+        self.discover = Some(Box::new(discover));
+        self
+    }
+
+    /// Backends found by discovery, which runs on the first call
+    fn discovered(&self) -> &DiscoveredBackends {
+        self.discovered.get_or_init(|| match &self.discover {
+            Some(discover) => discover(),
+            None => DiscoveredBackends::new(),
+        })
+    }
+
+    /// Names of the registered and discovered source types
+    pub fn types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self
+            .backends
+            .keys()
+            .chain(self.discovered().keys())
+            .map(String::as_str)
+            .collect();
+        types.sort_unstable();
+        types.dedup();
+        types
+    }
+
+    /// The backend handling sources of type `name`
+    pub fn backend(&self, name: &str) -> Result<&dyn RepositoryFetcher, DomainError> {
+        self.backends
+            .get(name)
+            .or_else(|| self.discovered().get(name))
+            .map(|backend| backend.as_ref())
+            .ok_or_else(|| {
+                DomainError::ConfigurationError(format!(
+                    "Unsupported source type '{}' (supported: {})",
+                    name,
+                    self.types().join(", ")
+                ))
+            })
+    }
+
+    fn default_backend(&self) -> Result<&dyn RepositoryFetcher, DomainError> {
+        self.backend(DEFAULT_SOURCE_TYPE)
+    }
+}
+
+impl RepositoryFetcher for SourceRegistry {
+    fn fetch(&self, url: &str, revision: &str, temp_path: &Path) -> Result<(), DomainError> {
+        self.default_backend()?.fetch(url, revision, temp_path)
+    }
+
+    fn extract_paths(
+        &self,
+        repo_path: &Path,
+        patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        self.default_backend()?
+            .extract_paths(repo_path, patterns, target_path)
+    }
+
+    fn fetch_dependency(
+        &self,
+        dependency: &Dependency,
+        url: &str,
+        temp_path: &Path,
+    ) -> Result<Option<String>, DomainError> {
+        self.backend(&dependency.repository_type)?
+            .fetch_dependency(dependency, url, temp_path)
+    }
+
+    fn extract_dependency_paths(
+        &self,
+        dependency: &Dependency,
+        repo_path: &Path,
+        patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        self.backend(&dependency.repository_type)?
+            .extract_dependency_paths(dependency, repo_path, patterns, target_path)
+    }
+
+    fn tracks_revisions(&self, dependency: &Dependency) -> bool {
+        self.backend(&dependency.repository_type)
+            .map(|backend| backend.tracks_revisions(dependency))
+            .unwrap_or(false)
+    }
+
+    fn keeps_upstream_paths(&self, dependency: &Dependency) -> bool {
+        self.backend(&dependency.repository_type)
+            .map(|backend| backend.keeps_upstream_paths(dependency))
+            .unwrap_or(false)
+    }
+
+    fn validate_dependency(&self, dependency: &Dependency) -> Vec<String> {
+        match self.backend(&dependency.repository_type) {
+            Ok(backend) => backend.validate_dependency(dependency),
+            Err(DomainError::ConfigurationError(message)) => vec![message],
+            Err(e) => vec![e.to_string()],
+        }
+    }
+
+    fn resolve_revision(&self, repo_path: &Path) -> Result<Option<String>, DomainError> {
+        self.default_backend()?.resolve_revision(repo_path)
+    }
+
+    fn resolve_remote_revision(
+        &self,
+        url: &str,
+        revision: &str,
+    ) -> Result<Option<String>, DomainError> {
+        self.default_backend()?
+            .resolve_remote_revision(url, revision)
+    }
+
+    fn resolve_remote_dependency(
+        &self,
+        dependency: &Dependency,
+        url: &str,
+    ) -> Result<Option<String>, DomainError> {
+        self.backend(&dependency.repository_type)?
+            .resolve_remote_dependency(dependency, url)
+    }
+}
+
+impl RepositoryHistory for SourceRegistry {
+    fn log(
+        &self,
+        url: &str,
+        from: &str,
+        to: &str,
+        patterns: &[String],
+    ) -> Result<Vec<CommitSummary>, DomainError> {
+        let history = self.history.as_ref().ok_or_else(|| {
+            DomainError::RepositoryError("No source type provides upstream history".to_string())
+        })?;
+        history.log(url, from, to, patterns)
+    }
+}
@@ -0,0 +1,306 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use glob::Pattern;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::domain::diff::as_text;
+use crate::domain::error::DomainError;
+
+/// A step rewriting vendored content after extraction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform {
+    pub action: TransformAction,
+
+    /// Patterns selecting the files the step applies to, relative to the
+    /// target; every file when empty
+    pub paths: Vec<String>,
+}
+
+/// What a transform step does to each file it applies to
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransformAction {
+    /// Replaces every occurrence of a text
+    Replace { from: String, to: String },
+
+    /// Converts line endings
+    LineEndings(LineEnding),
+
+    /// Removes the comment block opening a file, if it contains a text
+    StripHeader { contains: String },
+
+    /// Renames files with one extension to another
+    RenameExtension { from: String, to: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+/// Files a transform step changed, as reported by `diff`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformReport {
+    /// Description of the step
+    pub step: String,
+
+    /// Changed files relative to the content root, by their name before the step
+    pub files: Vec<PathBuf>,
+}
+
+impl Transform {
+    /// Problems that would make the step fail or do nothing
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self
+            .paths
+            .iter()
+            .filter_map(|pattern| {
+                Pattern::new(pattern)
+                    .err()
+                    .map(|e| format!("invalid transform pattern '{}': {}", pattern, e))
+            })
+            .collect();
+
+        match &self.action {
+            TransformAction::Replace { from, .. } if from.is_empty() => {
+                problems.push("replace transform must set a non-empty from".to_string());
+            }
+            TransformAction::StripHeader { contains } if contains.is_empty() => {
+                problems.push("strip-header transform must set a non-empty contains".to_string());
+            }
+            TransformAction::RenameExtension { from, to }
+                if extension(from).is_empty() || extension(to).is_empty() =>
+            {
+                problems.push("rename-extension transform must set from and to".to_string());
+            }
+            _ => {}
+        }
+        problems
+    }
+
+    /// Whether the step applies to a file, by its path relative to the content root
+    fn selects(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        self.paths.is_empty()
+            || self
+                .paths
+                .iter()
+                .filter_map(|pattern| Pattern::new(pattern).ok())
+                .any(|pattern| pattern.matches(&path))
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.action {
+            TransformAction::Replace { from, to } => write!(f, "replace '{}' with '{}'", from, to)?,
+            TransformAction::LineEndings(LineEnding::Lf) => {
+                write!(f, "convert line endings to LF")?
+            }
+            TransformAction::LineEndings(LineEnding::Crlf) => {
+                write!(f, "convert line endings to CRLF")?
+            }
+            TransformAction::StripHeader { contains } => {
+                write!(f, "strip headers containing '{}'", contains)?
+            }
+            TransformAction::RenameExtension { from, to } => {
+                write!(f, "rename .{} to .{}", extension(from), extension(to))?
+            }
+        }
+        if !self.paths.is_empty() {
+            write!(f, " in {}", self.paths.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Applies transform steps in order to the files below `root`
+pub fn apply_all(
+    transforms: &[Transform],
+    root: &Path,
+) -> Result<Vec<TransformReport>, DomainError> {
+    transforms
+        .iter()
+        .map(|transform| {
+            Ok(TransformReport {
+                step: transform.to_string(),
+                files: apply(transform, root)?,
+            })
+        })
+        .collect()
+}
+
+/// Applies one transform step to the files below `root`, returning those it changed
+///
+/// Text steps leave binary files alone.
+pub fn apply(transform: &Transform, root: &Path) -> Result<Vec<PathBuf>, DomainError> {
+    if let Some(problem) = transform.problems().into_iter().next() {
+        return Err(DomainError::TransformError(problem));
+    }
+
+    let mut changed = Vec::new();
+    for path in list_files(root)? {
+        if !transform.selects(&path) {
+            continue;
+        }
+        let file_path = root.join(&path);
+
+        if let TransformAction::RenameExtension { from, to } = &transform.action {
+            if path.extension().is_some_and(|ext| ext == extension(from)) {
+                let renamed = file_path.with_extension(extension(to));
+                if renamed.exists() {
+                    return Err(DomainError::TransformError(format!(
+                        "Cannot rename {} to {}: the file exists",
+                        path.display(),
+                        renamed.display()
+                    )));
+                }
+                fs::rename(&file_path, &renamed).map_err(|e| {
+                    DomainError::TransformError(format!(
+                        "Failed to rename {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                changed.push(path);
+            }
+            continue;
+        }
+
+        let bytes = fs::read(&file_path).map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to read {}: {}", file_path.display(), e))
+        })?;
+        let Some(text) = as_text(&bytes) else {
+            continue;
+        };
+
+        let transformed = match &transform.action {
+            TransformAction::Replace { from, to } => text.replace(from.as_str(), to),
+            TransformAction::LineEndings(ending) => convert_line_endings(text, *ending),
+            TransformAction::StripHeader { contains } => {
+                strip_header(text, contains).unwrap_or_else(|| text.to_string())
+            }
+            TransformAction::RenameExtension { .. } => unreachable!("renames are handled above"),
+        };
+        if transformed != text {
+            fs::write(&file_path, transformed).map_err(|e| {
+                DomainError::FileSystemError(format!(
+                    "Failed to write {}: {}",
+                    file_path.display(),
+                    e
+                ))
+            })?;
+            changed.push(path);
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Upstream paths of the files renamed by extension steps, by their
+/// transformed path
+///
+/// `reports` are those `apply_all` returned for `transforms`, so only the
+/// files a step actually renamed are mapped back.
+pub fn renamed_files(
+    transforms: &[Transform],
+    reports: &[TransformReport],
+) -> BTreeMap<PathBuf, PathBuf> {
+    let mut renamed = BTreeMap::new();
+    for (transform, report) in transforms.iter().zip(reports) {
+        if let TransformAction::RenameExtension { to, .. } = &transform.action {
+            for file in &report.files {
+                let upstream = renamed.remove(file).unwrap_or_else(|| file.clone());
+                renamed.insert(file.with_extension(extension(to)), upstream);
+            }
+        }
+    }
+    renamed
+}
+
+/// An extension as configured, with or without its leading dot
+fn extension(configured: &str) -> &str {
+    configured.trim_start_matches('.')
+}
+
+fn convert_line_endings(text: &str, ending: LineEnding) -> String {
+    let lf = text.replace("\r\n", "\n");
+    match ending {
+        LineEnding::Lf => lf,
+        LineEnding::Crlf => lf.replace('\n', "\r\n"),
+    }
+}
+
+/// Removes the comment block opening `text`, and the blank lines after it,
+/// if the block contains `contains`
+///
+/// A shebang line stays in place, though a Rust inner attribute such as
+/// `#![allow]` does not count as one. Comments are recognised by their leading
+/// `//`, `# `, `--` or `;`, or by `/* */` and `<!-- -->` delimiters.
+fn strip_header(text: &str, contains: &str) -> Option<String> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let start = usize::from(
+        lines
+            .first()
+            .is_some_and(|line| line.starts_with("#!") && !line.starts_with("#![")),
+    );
+
+    let mut end = start;
+    let mut closing: Option<&str> = None;
+    while let Some(line) = lines.get(end) {
+        let line = line.trim();
+        if let Some(delimiter) = closing {
+            if line.contains(delimiter) {
+                closing = None;
+            }
+        } else if let Some(rest) = line.strip_prefix("/*") {
+            closing = (!rest.contains("*/")).then_some("*/");
+        } else if let Some(rest) = line.strip_prefix("<!--") {
+            closing = (!rest.contains("-->")).then_some("-->");
+        } else if !is_line_comment(line) {
+            break;
+        }
+        end += 1;
+    }
+
+    if end == start || !lines[start..end].concat().contains(contains) {
+        return None;
+    }
+    while lines.get(end).is_some_and(|line| line.trim().is_empty()) {
+        end += 1;
+    }
+    Some(lines[..start].concat() + &lines[end..].concat())
+}
+
+/// Whether a trimmed line is a whole-line comment
+///
+/// `#` only opens a comment when followed by whitespace, another `#` or
+/// nothing, so directives such as `#include` and `#![...]` are code.
+fn is_line_comment(line: &str) -> bool {
+    if let Some(rest) = line.strip_prefix('#') {
+        return rest.is_empty() || rest.starts_with(|c: char| c == '#' || c.is_whitespace());
+    }
+    ["//", "--", ";"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// Files below `root`, relative to it, in a stable order
+fn list_files(root: &Path) -> Result<Vec<PathBuf>, DomainError> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(root).min_depth(1).sort_by_file_name() {
+        let entry = entry.map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to walk directory: {}", e))
+        })?;
+        if entry.file_type().is_file() {
+            if let Ok(relative) = entry.path().strip_prefix(root) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+    Ok(files)
+}
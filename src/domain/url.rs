@@ -0,0 +1,209 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::fmt;
+
+use crate::domain::error::DomainError;
+
+/// Transport used to reach a repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlScheme {
+    Ssh,
+    Git,
+    Http,
+    Https,
+    File,
+}
+
+impl fmt::Display for UrlScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlScheme::Ssh => write!(f, "ssh"),
+            UrlScheme::Git => write!(f, "git"),
+            UrlScheme::Http => write!(f, "http"),
+            UrlScheme::Https => write!(f, "https"),
+            UrlScheme::File => write!(f, "file"),
+        }
+    }
+}
+
+/// A repository location in any of the forms git accepts
+///
+/// Supported forms are `scheme://[user[:password]@]host[:port]/path` for the
+/// `ssh`, `git`, `http`, `https` and `file` schemes, scp-style
+/// `[user@]host:path` and plain local paths. Passwords are never kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryUrl {
+    pub scheme: UrlScheme,
+    pub user: Option<String>,
+    /// Lowercased host name, absent for local repositories
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub path: String,
+}
+
+impl RepositoryUrl {
+    /// Parses and normalizes a repository URL
+    pub fn parse(url: &str) -> Result<Self, DomainError> {
+        let url = url.trim();
+        let invalid = |reason: &str| {
+            DomainError::ConfigurationError(format!(
+                "Invalid repository URL '{}': {}",
+                redact(url),
+                reason
+            ))
+        };
+
+        if url.is_empty() {
+            return Err(invalid("URL is empty"));
+        }
+
+        if let Some((scheme, rest)) = url.split_once("://") {
+            let scheme = match scheme.to_ascii_lowercase().as_str() {
+                "ssh" | "git+ssh" | "ssh+git" => UrlScheme::Ssh,
+                "git" => UrlScheme::Git,
+                "http" => UrlScheme::Http,
+                "https" => UrlScheme::Https,
+                "file" => UrlScheme::File,
+                other => return Err(invalid(&format!("unsupported scheme '{}'", other))),
+            };
+
+            let (authority, path) = match rest.find('/') {
+                Some(i) => (&rest[..i], &rest[i..]),
+                None => (rest, ""),
+            };
+
+            if scheme == UrlScheme::File {
+                if !authority.is_empty() && authority != "localhost" {
+                    return Err(invalid("file URLs must not name a remote host"));
+                }
+                if path.is_empty() {
+                    return Err(invalid("path is empty"));
+                }
+                return Ok(Self::local(path));
+            }
+
+            let (user, host_port) = match authority.rsplit_once('@') {
+                Some((userinfo, host_port)) => {
+                    let user = userinfo.split(':').next().unwrap_or_default();
+                    ((!user.is_empty()).then(|| user.to_string()), host_port)
+                }
+                None => (None, authority),
+            };
+            let (host, port) = split_port(host_port).map_err(|reason| invalid(&reason))?;
+
+            if host.is_empty() {
+                return Err(invalid("host is empty"));
+            }
+            if path.trim_matches('/').is_empty() {
+                return Err(invalid("path is empty"));
+            }
+
+            return Ok(Self {
+                scheme,
+                user,
+                host: Some(host.to_lowercase()),
+                port,
+                path: path.to_string(),
+            });
+        }
+
+        // scp-style `[user@]host:path`, recognized like git does: a colon
+        // before the first slash
+        if let Some((before, path)) = url.split_once(':') {
+            if !before.contains('/') {
+                let (user, host) = match before.rsplit_once('@') {
+                    Some((user, host)) => ((!user.is_empty()).then(|| user.to_string()), host),
+                    None => (None, before),
+                };
+
+                if host.is_empty() {
+                    return Err(invalid("host is empty"));
+                }
+                if path.trim_matches('/').is_empty() {
+                    return Err(invalid("path is empty"));
+                }
+
+                return Ok(Self {
+                    scheme: UrlScheme::Ssh,
+                    user,
+                    host: Some(host.to_lowercase()),
+                    port: None,
+                    path: path.to_string(),
+                });
+            }
+        }
+
+        Ok(Self::local(url))
+    }
+
+    fn local(path: &str) -> Self {
+        Self {
+            scheme: UrlScheme::File,
+            user: None,
+            host: None,
+            port: None,
+            path: path.to_string(),
+        }
+    }
+
+    /// `host[:port]`, used to look up per-host settings
+    pub fn authority(&self) -> Option<String> {
+        let host = self.host.as_ref()?;
+        Some(match self.port {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.clone(),
+        })
+    }
+
+    pub fn is_ssh(&self) -> bool {
+        self.scheme == UrlScheme::Ssh
+    }
+
+    pub fn is_http(&self) -> bool {
+        matches!(self.scheme, UrlScheme::Http | UrlScheme::Https)
+    }
+
+    /// Identifies the repository regardless of transport and spelling
+    ///
+    /// `git@github.com:org/repo.git` and `https://github.com/org/repo` share
+    /// the key `github.com/org/repo`; ports are kept since they may name a
+    /// different server.
+    pub fn cache_key(&self) -> String {
+        let path = self.path.trim_end_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+
+        match self.authority() {
+            Some(authority) => format!("{}/{}", authority, path.trim_start_matches('/')),
+            None => path.to_string(),
+        }
+    }
+}
+
+/// Splits `host:port`, keeping bracketed IPv6 addresses intact
+fn split_port(host_port: &str) -> Result<(&str, Option<u16>), String> {
+    let (host, port) = match host_port.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((host, rest)) => (host, rest.strip_prefix(':')),
+            None => return Err("unterminated IPv6 address".to_string()),
+        },
+        None => match host_port.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        },
+    };
+
+    let port = match port {
+        Some("") | None => None,
+        Some(port) => Some(
+            port.parse::<u16>()
+                .map_err(|_| format!("invalid port '{}'", port))?,
+        ),
+    };
+
+    Ok((host, port))
+}
+
+/// Drops any password from a URL before it is shown in an error
+fn redact(url: &str) -> String {
+    crate::domain::auth::AuthenticationService::redact_url(url)
+}
@@ -0,0 +1,139 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use glob::Pattern;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use crate::domain::auth::ssh_conflicts;
+use crate::domain::repositories::RepositoryFetcher;
+use crate::domain::Configuration;
+
+/// A problem found while validating a configuration
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    /// Name of the affected dependency, if any
+    pub dependency: Option<String>,
+    pub message: String,
+}
+
+/// Checks a configuration for problems that would make updates fail or misbehave
+///
+/// Type-specific options are checked by the backend in `sources`.
+pub fn validate_configuration(
+    config: &Configuration,
+    sources: &dyn RepositoryFetcher,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut names = HashSet::new();
+    let mut targets: Vec<(String, PathBuf)> = Vec::new();
+
+    for dep in &config.dependencies {
+        let mut issue = |message: String| {
+            issues.push(ValidationIssue {
+                dependency: Some(dep.name.clone()),
+                message,
+            })
+        };
+
+        if dep.name.trim().is_empty() {
+            issue("name must not be empty".to_string());
+        }
+        if !names.insert(dep.name.as_str()) {
+            issue(format!("duplicate dependency name '{}'", dep.name));
+        }
+        if dep.repository_url.trim().is_empty() {
+            issue("repo must not be empty".to_string());
+        }
+        for message in sources.validate_dependency(dep) {
+            issue(message);
+        }
+
+        let target = &dep.target_location;
+        if let Some(problem) = target_problem(target) {
+            issue(problem);
+        }
+
+        for pattern in &dep.sparse_paths {
+            if let Err(e) = Pattern::new(pattern) {
+                issue(format!("invalid pattern '{}': {}", pattern, e));
+            }
+        }
+        for problem in dep.transforms.iter().flat_map(|t| t.problems()) {
+            issue(problem);
+        }
+
+        if target_problem(target).is_some() {
+            continue;
+        }
+
+        // Overlapping targets would purge each other's content
+        for (other_name, other_target) in &targets {
+            if target.starts_with(other_target) || other_target.starts_with(target) {
+                issue(format!(
+                    "target '{}' overlaps with '{}' of dependency '{}'",
+                    target.display(),
+                    other_target.display(),
+                    other_name
+                ));
+            }
+        }
+        targets.push((dep.name.clone(), target.clone()));
+    }
+
+    for (dep, message) in ssh_conflicts(&config.dependencies) {
+        issues.push(ValidationIssue {
+            dependency: Some(dep.name.clone()),
+            message,
+        });
+    }
+
+    for (host, auth) in &config.auth {
+        if auth.source_count() > 1 {
+            issues.push(ValidationIssue {
+                dependency: None,
+                message: format!(
+                    "auth for '{}' must set only one of env, file or credential-helper",
+                    host
+                ),
+            });
+        } else if auth.source_count() == 0 && auth.ssh.is_empty() {
+            issues.push(ValidationIssue {
+                dependency: None,
+                message: format!("auth for '{}' sets no credentials", host),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Why a target cannot hold vendored content, if it cannot
+///
+/// Targets are purged and removed along with their source, so they must name
+/// a directory below the one holding the configuration file.
+pub fn target_problem(target: &Path) -> Option<String> {
+    let mut components = target.components().filter(|c| *c != Component::CurDir);
+    if target.is_absolute() {
+        Some(format!("target '{}' must be relative", target.display()))
+    } else if target
+        .components()
+        .any(|c| matches!(c, Component::ParentDir))
+    {
+        Some(format!(
+            "target '{}' must not leave the repository",
+            target.display()
+        ))
+    } else if components.next().is_none() {
+        Some(format!(
+            "target '{}' must name a directory inside the repository",
+            target.display()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Whether a value is a hex encoded SHA-256 digest
+pub fn is_sha256(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
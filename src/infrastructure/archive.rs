@@ -0,0 +1,314 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use log::{debug, info, warn};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+use crate::domain::auth::AuthenticationService;
+use crate::domain::repositories::RepositoryFetcher;
+use crate::domain::validation::is_sha256;
+use crate::domain::{Dependency, DomainError};
+use crate::infrastructure::file_system::extract_matching;
+use crate::infrastructure::http::get;
+use crate::infrastructure::source::{parse_options, validate_url};
+
+/// Archive formats recognized from the file name
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveFormat {
+    TarGz,
+    TarXz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    fn detect(url: &str) -> Result<Self, DomainError> {
+        // Query strings and fragments do not belong to the file name
+        let name = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Ok(ArchiveFormat::TarXz)
+        } else if name.ends_with(".zip") {
+            Ok(ArchiveFormat::Zip)
+        } else {
+            Err(DomainError::RepositoryError(format!(
+                "Unsupported archive format: {} (expected .tar.gz, .tar.xz or .zip)",
+                AuthenticationService::redact_url(url)
+            )))
+        }
+    }
+}
+
+/// Options of `archive` sources, from `[sources.archive]`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ArchiveOptions {
+    /// Expected SHA-256 of the archive, as hex
+    sha256: String,
+
+    /// Leading path components dropped from archive entries
+    #[serde(default)]
+    strip_components: usize,
+}
+
+/// Fetcher unpacking `.tar.gz`, `.tar.xz` and `.zip` archives
+///
+/// Archives are local files, resolved against the directory holding the
+/// configuration file, or `http(s)` downloads. Their SHA-256 is verified
+/// before anything is unpacked.
+pub struct ArchiveFetcher {
+    base_dir: PathBuf,
+    auth_service: AuthenticationService,
+}
+
+impl ArchiveFetcher {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self {
+            base_dir,
+            auth_service: AuthenticationService::default(),
+        }
+    }
+
+    /// Uses `auth_service` to authenticate downloads
+    pub fn with_auth_service(mut self, auth_service: AuthenticationService) -> Self {
+        self.auth_service = auth_service;
+        self
+    }
+
+    /// Opens the archive, downloading it to a temporary file if needed
+    fn open(&self, url: &str) -> Result<File, DomainError> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return self.download(url);
+        }
+
+        let path = Path::new(url.strip_prefix("file://").unwrap_or(url));
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.base_dir.join(path)
+        };
+        File::open(&path).map_err(|e| {
+            DomainError::RepositoryError(format!(
+                "Failed to open archive {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    fn download(&self, url: &str) -> Result<File, DomainError> {
+        let redacted = AuthenticationService::redact_url(url);
+        info!("Downloading {}", redacted);
+
+        let response = get(&self.auth_service, url, &[])?;
+
+        let mut file = tempfile::tempfile().map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to create temporary file: {}", e))
+        })?;
+        io::copy(&mut response.into_reader(), &mut file).map_err(|e| {
+            DomainError::RepositoryError(format!("Failed to download {}: {}", redacted, e))
+        })?;
+        Ok(file)
+    }
+}
+
+impl RepositoryFetcher for ArchiveFetcher {
+    fn fetch(&self, url: &str, _revision: &str, temp_path: &Path) -> Result<(), DomainError> {
+        unpack(self.open(url)?, ArchiveFormat::detect(url)?, 0, temp_path)
+    }
+
+    fn validate_dependency(&self, dependency: &Dependency) -> Vec<String> {
+        let mut issues: Vec<String> = validate_url(dependency).into_iter().collect();
+        match parse_options::<ArchiveOptions>(dependency) {
+            Ok(options) if !is_sha256(&options.sha256) => issues.push(format!(
+                "sha256 '{}' is not a SHA-256 hex digest",
+                options.sha256
+            )),
+            Ok(_) => {}
+            Err(message) => issues.push(message),
+        }
+        issues
+    }
+
+    fn extract_paths(
+        &self,
+        repo_path: &Path,
+        patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        extract_matching(repo_path, patterns, target_path)
+    }
+
+    fn fetch_dependency(
+        &self,
+        dependency: &Dependency,
+        url: &str,
+        temp_path: &Path,
+    ) -> Result<Option<String>, DomainError> {
+        let options: ArchiveOptions =
+            parse_options(dependency).map_err(DomainError::ConfigurationError)?;
+        let expected = &options.sha256;
+        let format = ArchiveFormat::detect(url)?;
+        let mut file = self.open(url)?;
+
+        let actual = sha256(&mut file)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(DomainError::RepositoryError(format!(
+                "Checksum mismatch for {}: expected sha256 {}, got {}",
+                AuthenticationService::redact_url(url),
+                expected,
+                actual
+            )));
+        }
+        debug!("Verified sha256 {} of {}", actual, dependency.name);
+
+        unpack(file, format, options.strip_components, temp_path)?;
+        Ok(None)
+    }
+}
+
+/// Hashes a file from its start, leaving it rewound for unpacking
+fn sha256(file: &mut File) -> Result<String, DomainError> {
+    let io_error =
+        |e: io::Error| DomainError::FileSystemError(format!("Failed to read archive: {}", e));
+
+    file.seek(SeekFrom::Start(0)).map_err(io_error)?;
+    let mut hasher = Sha256::new();
+    io::copy(file, &mut hasher).map_err(io_error)?;
+    file.seek(SeekFrom::Start(0)).map_err(io_error)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn unpack(
+    file: File,
+    format: ArchiveFormat,
+    strip_components: usize,
+    destination: &Path,
+) -> Result<(), DomainError> {
+    match format {
+        ArchiveFormat::TarGz => unpack_tar(
+            flate2::read::GzDecoder::new(file),
+            strip_components,
+            destination,
+        ),
+        ArchiveFormat::TarXz => unpack_tar(
+            xz2::read::XzDecoder::new(file),
+            strip_components,
+            destination,
+        ),
+        ArchiveFormat::Zip => unpack_zip(file, strip_components, destination),
+    }
+}
+
+fn unpack_tar(
+    reader: impl Read,
+    strip_components: usize,
+    destination: &Path,
+) -> Result<(), DomainError> {
+    let archive_error =
+        |e: io::Error| DomainError::RepositoryError(format!("Failed to read archive: {}", e));
+
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(archive_error)? {
+        let mut entry = entry.map_err(archive_error)?;
+        let path = entry.path().map_err(archive_error)?.into_owned();
+        let Some(relative) = stripped_path(&path, strip_components)? else {
+            continue;
+        };
+        let output = destination.join(&relative);
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            create_dir(&output)?;
+        } else if entry_type.is_file() {
+            if let Some(parent) = output.parent() {
+                create_dir(parent)?;
+            }
+            entry.unpack(&output).map_err(|e| {
+                DomainError::FileSystemError(format!(
+                    "Failed to unpack {}: {}",
+                    relative.display(),
+                    e
+                ))
+            })?;
+        } else {
+            warn!("Skipping unsupported archive entry {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn unpack_zip(file: File, strip_components: usize, destination: &Path) -> Result<(), DomainError> {
+    let archive_error = |e: zip::result::ZipError| {
+        DomainError::RepositoryError(format!("Failed to read archive: {}", e))
+    };
+
+    let mut archive = zip::ZipArchive::new(file).map_err(archive_error)?;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(archive_error)?;
+        let path = PathBuf::from(entry.name());
+        let Some(relative) = stripped_path(&path, strip_components)? else {
+            continue;
+        };
+        let output = destination.join(&relative);
+
+        if entry.is_dir() {
+            create_dir(&output)?;
+            continue;
+        }
+        if let Some(parent) = output.parent() {
+            create_dir(parent)?;
+        }
+        let mut out = File::create(&output).map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to create {}: {}", output.display(), e))
+        })?;
+        io::copy(&mut entry, &mut out).map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to unpack {}: {}", relative.display(), e))
+        })?;
+
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&output, fs::Permissions::from_mode(mode & 0o777));
+        }
+    }
+    Ok(())
+}
+
+/// Drops the leading components of an entry path
+///
+/// Returns `None` for entries consumed entirely by stripping, and rejects
+/// absolute paths and `..` components so nothing lands outside the target.
+fn stripped_path(path: &Path, strip_components: usize) -> Result<Option<PathBuf>, DomainError> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => components.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(DomainError::RepositoryError(format!(
+                    "Archive entry {} escapes the target directory",
+                    path.display()
+                )))
+            }
+        }
+    }
+
+    if components.len() <= strip_components {
+        return Ok(None);
+    }
+    Ok(Some(components[strip_components..].iter().collect()))
+}
+
+fn create_dir(path: &Path) -> Result<(), DomainError> {
+    fs::create_dir_all(path).map_err(|e| {
+        DomainError::FileSystemError(format!(
+            "Failed to create directory {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
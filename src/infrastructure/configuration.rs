@@ -1,28 +1,224 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
+use crate::domain::mirrors::MirrorRule;
 use crate::domain::repositories::ConfigurationRepository;
-use crate::domain::{Configuration, Dependency, DomainError, RepositoryType};
+use crate::domain::transform::{LineEnding, Transform, TransformAction};
+use crate::domain::validation::target_problem;
+use crate::domain::{
+    CleanPolicy, Configuration, Dependency, DomainError, GitAttributesSettings, HostAuth,
+    SshSettings,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ConfigFile {
     location: Option<String>,
     sources: Vec<SourceConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    auth: BTreeMap<String, AuthConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    mirrors: BTreeMap<String, MirrorConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gitattributes: Option<GitAttributesConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    safety: Option<SafetyConfig>,
+}
+
+/// The `[safety]` table
+#[derive(Debug, Serialize, Deserialize)]
+struct SafetyConfig {
+    #[serde(default)]
+    require_clean: CleanPolicyConfig,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum CleanPolicyConfig {
+    #[default]
+    Paths,
+    Repository,
+    None,
+}
+
+/// The `[gitattributes]` table
+#[derive(Debug, Serialize, Deserialize)]
+struct GitAttributesConfig {
+    #[serde(default)]
+    manage: bool,
+    #[serde(default = "default_true")]
+    diff: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// One mirror, or several tried in order
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum MirrorConfig {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AuthConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(
+        rename = "credential-helper",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    credential_helper: bool,
+    #[serde(flatten)]
+    ssh: SshConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct SourceConfig {
     repo: String,
     name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     rev: String,
     #[serde(rename = "type")]
     repo_type: String,
     sparse_paths: Vec<String>,
     target: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    patches: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    transforms: Vec<TransformConfig>,
+    #[serde(flatten)]
+    ssh: SshConfig,
+    /// Keys left over once the fixed keys are read, which may only be the
+    /// option block named by `type`, such as `[sources.archive]`
+    ///
+    /// serde cannot deny unknown fields next to a flattened table, so any
+    /// other key is rejected when the source is converted.
+    #[serde(flatten)]
+    blocks: toml::Table,
+}
+
+/// One step of a source's `[[sources.transforms]]`, also recorded in the lock file
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct TransformConfig {
+    #[serde(flatten)]
+    action: TransformActionConfig,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum TransformActionConfig {
+    Replace { from: String, to: String },
+    LineEndings { style: LineEndingConfig },
+    StripHeader { contains: String },
+    RenameExtension { from: String, to: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LineEndingConfig {
+    Lf,
+    Crlf,
+}
+
+impl From<&Transform> for TransformConfig {
+    fn from(transform: &Transform) -> Self {
+        let action = match &transform.action {
+            TransformAction::Replace { from, to } => TransformActionConfig::Replace {
+                from: from.clone(),
+                to: to.clone(),
+            },
+            TransformAction::LineEndings(ending) => TransformActionConfig::LineEndings {
+                style: match ending {
+                    LineEnding::Lf => LineEndingConfig::Lf,
+                    LineEnding::Crlf => LineEndingConfig::Crlf,
+                },
+            },
+            TransformAction::StripHeader { contains } => TransformActionConfig::StripHeader {
+                contains: contains.clone(),
+            },
+            TransformAction::RenameExtension { from, to } => {
+                TransformActionConfig::RenameExtension {
+                    from: from.clone(),
+                    to: to.clone(),
+                }
+            }
+        };
+        Self {
+            action,
+            paths: transform.paths.clone(),
+        }
+    }
+}
+
+impl From<TransformConfig> for Transform {
+    fn from(transform: TransformConfig) -> Self {
+        let action = match transform.action {
+            TransformActionConfig::Replace { from, to } => TransformAction::Replace { from, to },
+            TransformActionConfig::LineEndings { style } => {
+                TransformAction::LineEndings(match style {
+                    LineEndingConfig::Lf => LineEnding::Lf,
+                    LineEndingConfig::Crlf => LineEnding::Crlf,
+                })
+            }
+            TransformActionConfig::StripHeader { contains } => {
+                TransformAction::StripHeader { contains }
+            }
+            TransformActionConfig::RenameExtension { from, to } => {
+                TransformAction::RenameExtension { from, to }
+            }
+        };
+        Self {
+            action,
+            paths: transform.paths,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SshConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ssh_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    known_hosts: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strict_host_key_checking: Option<bool>,
+}
+
+impl From<&SshSettings> for SshConfig {
+    fn from(ssh: &SshSettings) -> Self {
+        Self {
+            ssh_key: ssh.key.as_ref().map(|k| k.to_string_lossy().to_string()),
+            known_hosts: ssh
+                .known_hosts
+                .as_ref()
+                .map(|k| k.to_string_lossy().to_string()),
+            strict_host_key_checking: ssh.strict_host_key_checking,
+        }
+    }
+}
+
+impl From<SshConfig> for SshSettings {
+    fn from(ssh: SshConfig) -> Self {
+        Self {
+            key: ssh.ssh_key.map(PathBuf::from),
+            known_hosts: ssh.known_hosts.map(PathBuf::from),
+            strict_host_key_checking: ssh.strict_host_key_checking,
+        }
+    }
 }
 
 pub struct TomlConfigurationRepository;
@@ -42,20 +238,24 @@ impl TomlConfigurationRepository {
         let sources = config
             .dependencies
             .iter()
-            .map(|dep| {
-                let repo_type = match dep.repository_type {
-                    RepositoryType::Git => "git".to_string(),
-                    // Add other repository types here
-                };
-
-                SourceConfig {
-                    repo: dep.repository_url.clone(),
-                    name: dep.name.clone(),
-                    rev: dep.revision.clone(),
-                    repo_type,
-                    sparse_paths: dep.sparse_paths.clone(),
-                    target: dep.target_location.to_string_lossy().to_string(),
-                }
+            .map(|dep| SourceConfig {
+                repo: dep.repository_url.clone(),
+                name: dep.name.clone(),
+                rev: dep.revision.clone(),
+                repo_type: dep.repository_type.clone(),
+                sparse_paths: dep.sparse_paths.clone(),
+                target: dep.target_location.to_string_lossy().to_string(),
+                patches: dep.patches.clone(),
+                transforms: dep.transforms.iter().map(TransformConfig::from).collect(),
+                ssh: SshConfig::from(&dep.ssh),
+                blocks: if dep.options.is_empty() {
+                    toml::Table::new()
+                } else {
+                    toml::Table::from_iter([(
+                        dep.repository_type.clone(),
+                        toml::Value::Table(dep.options.clone()),
+                    )])
+                },
             })
             .collect();
 
@@ -65,6 +265,46 @@ impl TomlConfigurationRepository {
                 .as_ref()
                 .map(|l| l.to_string_lossy().to_string()),
             sources,
+            auth: config
+                .auth
+                .iter()
+                .map(|(host, auth)| {
+                    (
+                        host.clone(),
+                        AuthConfig {
+                            username: auth.username.clone(),
+                            env: auth.env.clone(),
+                            file: auth.file.as_ref().map(|f| f.to_string_lossy().to_string()),
+                            credential_helper: auth.credential_helper,
+                            ssh: SshConfig::from(&auth.ssh),
+                        },
+                    )
+                })
+                .collect(),
+            mirrors: config
+                .mirrors
+                .iter()
+                .map(|rule| {
+                    let mirrors = match rule.mirrors.as_slice() {
+                        [mirror] => MirrorConfig::One(mirror.clone()),
+                        mirrors => MirrorConfig::Many(mirrors.to_vec()),
+                    };
+                    (rule.prefix.clone(), mirrors)
+                })
+                .collect(),
+            gitattributes: (config.gitattributes != GitAttributesSettings::default()).then_some(
+                GitAttributesConfig {
+                    manage: config.gitattributes.manage,
+                    diff: config.gitattributes.diff,
+                },
+            ),
+            safety: (config.require_clean != CleanPolicy::default()).then_some(SafetyConfig {
+                require_clean: match config.require_clean {
+                    CleanPolicy::Paths => CleanPolicyConfig::Paths,
+                    CleanPolicy::Repository => CleanPolicyConfig::Repository,
+                    CleanPolicy::None => CleanPolicyConfig::None,
+                },
+            }),
         }
     }
 
@@ -73,30 +313,99 @@ impl TomlConfigurationRepository {
             .sources
             .into_iter()
             .map(|source| {
-                let repository_type = match source.repo_type.as_str() {
-                    "git" => RepositoryType::Git,
-                    unsupported => {
-                        return Err(DomainError::ConfigurationError(format!(
-                            "Unsupported repository type: {}",
-                            unsupported
-                        )))
-                    }
-                };
+                let mut options = toml::Table::new();
+                for (key, value) in source.blocks {
+                    options = match value {
+                        toml::Value::Table(block) if key == source.repo_type => block,
+                        _ if key == source.repo_type => {
+                            return Err(DomainError::ConfigurationError(format!(
+                                "'{}' of source '{}' must be a table of options",
+                                source.repo_type, source.name
+                            )))
+                        }
+                        toml::Value::Table(_) => {
+                            return Err(DomainError::ConfigurationError(format!(
+                                "options [sources.{}] of source '{}' do not apply to its type '{}'",
+                                key, source.name, source.repo_type
+                            )))
+                        }
+                        _ => {
+                            return Err(DomainError::ConfigurationError(format!(
+                                "unknown key '{}' in source '{}'",
+                                key, source.name
+                            )))
+                        }
+                    };
+                }
+
+                if let Some(problem) = target_problem(Path::new(&source.target)) {
+                    return Err(DomainError::ConfigurationError(format!(
+                        "{} in source '{}'",
+                        problem, source.name
+                    )));
+                }
 
                 Ok(Dependency {
                     name: source.name,
                     repository_url: source.repo,
                     revision: source.rev,
-                    repository_type,
+                    repository_type: source.repo_type,
                     sparse_paths: source.sparse_paths,
                     target_location: PathBuf::from(source.target),
+                    patches: source.patches,
+                    transforms: source.transforms.into_iter().map(Transform::from).collect(),
+                    ssh: source.ssh.into(),
+                    options,
                 })
             })
             .collect::<Result<Vec<_>, _>>()?;
 
+        let auth = config_file
+            .auth
+            .into_iter()
+            .map(|(host, auth)| {
+                (
+                    host,
+                    HostAuth {
+                        username: auth.username,
+                        env: auth.env,
+                        file: auth.file.map(PathBuf::from),
+                        credential_helper: auth.credential_helper,
+                        ssh: auth.ssh.into(),
+                    },
+                )
+            })
+            .collect();
+
+        let mirrors = config_file
+            .mirrors
+            .into_iter()
+            .map(|(prefix, mirrors)| MirrorRule {
+                prefix,
+                mirrors: match mirrors {
+                    MirrorConfig::One(mirror) => vec![mirror],
+                    MirrorConfig::Many(mirrors) => mirrors,
+                },
+            })
+            .collect();
+
         Ok(Configuration {
             default_location: config_file.location.map(PathBuf::from),
             dependencies,
+            auth,
+            mirrors,
+            gitattributes: config_file
+                .gitattributes
+                .map(|g| GitAttributesSettings {
+                    manage: g.manage,
+                    diff: g.diff,
+                })
+                .unwrap_or_default(),
+            require_clean: match config_file.safety.map(|s| s.require_clean) {
+                Some(CleanPolicyConfig::Repository) => CleanPolicy::Repository,
+                Some(CleanPolicyConfig::None) => CleanPolicy::None,
+                Some(CleanPolicyConfig::Paths) | None => CleanPolicy::Paths,
+            },
         })
     }
 }
@@ -146,10 +455,7 @@ impl ConfigurationRepository for TomlConfigurationRepository {
 
     fn init(&self, path: &Path) -> Result<(), DomainError> {
         // Create an empty configuration
-        let config = Configuration {
-            default_location: None,
-            dependencies: vec![],
-        };
+        let config = Configuration::default();
 
         self.save(&config, path)
     }
@@ -1,11 +1,13 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
-use log::{debug, error};
+use glob::Pattern;
+use log::{debug, error, warn};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 use walkdir::WalkDir;
 
+use crate::domain::provenance::PROVENANCE_FILE;
 use crate::domain::repositories::FileSystemManager;
 use crate::domain::DomainError;
 
@@ -44,7 +46,8 @@ impl FileSystemManager for FileSystemManagerImpl {
             )));
         }
 
-        // Remove all contents of the directory
+        // Remove all contents of the directory except its provenance file,
+        // which the update rewrites
         for entry in fs::read_dir(path).map_err(|e| {
             error!("Failed to read directory {}: {}", path.display(), e);
             DomainError::FileSystemError(format!("Failed to read directory: {}", e))
@@ -55,6 +58,9 @@ impl FileSystemManager for FileSystemManagerImpl {
             })?;
 
             let entry_path = entry.path();
+            if entry.file_name() == PROVENANCE_FILE {
+                continue;
+            }
 
             if entry_path.is_dir() {
                 debug!("Removing directory: {}", entry_path.display());
@@ -114,8 +120,13 @@ impl FileSystemManager for FileSystemManagerImpl {
             })?;
         }
 
-        // Copy all contents from source to destination
-        for entry in WalkDir::new(source).min_depth(1) {
+        // Copy all contents from source to destination, except the metadata
+        // of a git checkout
+        let entries = WalkDir::new(source)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|e| e.depth() != 1 || e.file_name() != ".git");
+        for entry in entries {
             let entry = entry.map_err(|e| {
                 error!("Failed to walk directory {}: {}", source.display(), e);
                 DomainError::FileSystemError(format!("Failed to walk directory: {}", e))
@@ -216,4 +227,200 @@ impl FileSystemManager for FileSystemManagerImpl {
             Ok(())
         }
     }
+
+    fn remove_files(&self, root: &Path, files: &[PathBuf]) -> Result<(), DomainError> {
+        debug!("Removing {} files below {}", files.len(), root.display());
+
+        for file in files {
+            let path = root.join(file);
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    error!("Failed to remove file {}: {}", path.display(), e);
+                    return Err(DomainError::FileSystemError(format!(
+                        "Failed to remove file {}: {}",
+                        path.display(),
+                        e
+                    )));
+                }
+            }
+
+            // Directories still holding other files stay, so removal stops there
+            for dir in path.ancestors().skip(1) {
+                if !dir.starts_with(root) || fs::remove_dir(dir).is_err() {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Directory holding cached repositories and downloads
+///
+/// `ACDM_CACHE_DIR` takes precedence, then `XDG_CACHE_HOME/acdm` and finally
+/// `~/.cache/acdm`.
+pub fn default_cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("ACDM_CACHE_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+        return PathBuf::from(dir).join("acdm");
+    }
+
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".cache").join("acdm"))
+        .unwrap_or_else(|| std::env::temp_dir().join("acdm-cache"))
+}
+
+/// Copies the files below `repo_path` that match any of `patterns` to `target_path`
+///
+/// Without patterns everything is copied. Matching no file at all is an error,
+/// since it almost always means a typo in the configuration.
+pub fn extract_matching(
+    repo_path: &Path,
+    patterns: &[String],
+    target_path: &Path,
+) -> Result<(), DomainError> {
+    debug!(
+        "Extracting paths from {} to {}",
+        repo_path.display(),
+        target_path.display()
+    );
+    debug!("Patterns: {:?}", patterns);
+
+    if patterns.is_empty() {
+        debug!("No patterns specified, copying everything");
+        // If no patterns are specified, copy everything
+        let fs_manager = FileSystemManagerImpl::new();
+        return fs_manager.copy_content(repo_path, target_path);
+    }
+
+    // Compile all patterns
+    debug!("Compiling {} patterns", patterns.len());
+    let compiled_patterns: Vec<Pattern> = patterns
+        .iter()
+        .map(|p| {
+            debug!("Compiling pattern: {}", p);
+            Pattern::new(p).map_err(|e| {
+                error!("Invalid pattern '{}': {}", p, e);
+                DomainError::PathPatternError(format!("Invalid pattern '{}': {}", p, e))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    // Walk the repository and copy matching files
+    let mut copied_any = false;
+    let mut matched_count = 0;
+
+    debug!("Walking repository for matching files");
+    // The metadata of a git checkout is never vendored
+    let entries = WalkDir::new(repo_path)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| e.depth() != 1 || e.file_name() != ".git");
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            error!("Failed to walk directory {}: {}", repo_path.display(), e);
+            DomainError::FileSystemError(format!("Failed to walk directory: {}", e))
+        })?;
+
+        // Get the path relative to the repository root
+        let relative_path = entry.path().strip_prefix(repo_path).map_err(|e| {
+            error!("Failed to strip prefix {}: {}", repo_path.display(), e);
+            DomainError::FileSystemError(format!("Failed to strip prefix: {}", e))
+        })?;
+
+        // Check if the path matches any pattern
+        let relative_path_str = relative_path.to_string_lossy();
+        let should_include = compiled_patterns
+            .iter()
+            .any(|p| p.matches(&relative_path_str));
+
+        if should_include {
+            matched_count += 1;
+            debug!("Path matched pattern: {}", relative_path_str);
+            let target_file_path = target_path.join(relative_path);
+
+            if entry.path().is_dir() {
+                debug!("Creating directory: {}", target_file_path.display());
+                std::fs::create_dir_all(&target_file_path).map_err(|e| {
+                    error!(
+                        "Failed to create directory {}: {}",
+                        target_file_path.display(),
+                        e
+                    );
+                    DomainError::FileSystemError(format!("Failed to create directory: {}", e))
+                })?;
+            } else {
+                // Ensure parent directories exist
+                if let Some(parent) = target_file_path.parent() {
+                    if !parent.exists() {
+                        debug!("Creating parent directory: {}", parent.display());
+                        std::fs::create_dir_all(parent).map_err(|e| {
+                            error!(
+                                "Failed to create parent directory {}: {}",
+                                parent.display(),
+                                e
+                            );
+                            DomainError::FileSystemError(format!(
+                                "Failed to create parent directory: {}",
+                                e
+                            ))
+                        })?;
+                    }
+                }
+
+                debug!(
+                    "Copying file: {} to {}",
+                    entry.path().display(),
+                    target_file_path.display()
+                );
+                std::fs::copy(entry.path(), &target_file_path).map_err(|e| {
+                    error!(
+                        "Failed to copy file from {} to {}: {}",
+                        entry.path().display(),
+                        target_file_path.display(),
+                        e
+                    );
+                    DomainError::FileSystemError(format!("Failed to copy file: {}", e))
+                })?;
+
+                copied_any = true;
+            }
+        }
+    }
+
+    debug!(
+        "Matched {} paths, copied files: {}",
+        matched_count, copied_any
+    );
+
+    if !copied_any && !patterns.is_empty() {
+        warn!("No files matched the provided patterns: {:?}", patterns);
+        return Err(DomainError::PathPatternError(format!(
+            "No files matched the provided patterns: {:?}",
+            patterns
+        )));
+    }
+
+    debug!("Path extraction completed successfully");
+
+    // Additional logging to help diagnose path issues
+    if !target_path.exists() {
+        warn!(
+            "Target path does not exist after extraction: {}",
+            target_path.display()
+        );
+    } else {
+        debug!(
+            "Target path exists after extraction: {}",
+            target_path.display()
+        );
+    }
+
+    Ok(())
 }
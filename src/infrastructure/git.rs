@@ -1,13 +1,79 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
-use glob::Pattern;
 use log::{debug, error, info, warn};
-use std::path::Path;
-use std::process::Command;
-use walkdir::WalkDir;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::OnceLock;
+
+use crate::domain::auth::AuthenticationService;
+use crate::domain::repositories::{
+    CredentialStore, GitOperations, GitStatus, RepositoryFetcher, RepositoryHistory,
+};
+use crate::domain::{
+    AuthType, CommitSummary, Dependency, DomainError, RepositoryAuth, SshSettings,
+};
+use crate::infrastructure::file_system::default_cache_dir;
+use crate::infrastructure::git_common::{cache_key, validate_git_dependency, TOKEN_USERNAME};
+use crate::infrastructure::libgit2::{LibGitCredentialStore, LibGitOperations};
+
+/// Environment variable selecting the git implementation
+pub const GIT_BACKEND_ENV: &str = "ACDM_GIT_BACKEND";
+
+/// Implementation behind git sources, repository checks and credential helpers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GitBackend {
+    /// Runs the `git` executable
+    #[default]
+    Cli,
+    /// Uses libgit2, without needing git installed
+    Libgit2,
+}
+
+impl GitBackend {
+    /// Reads the backend from `ACDM_GIT_BACKEND`, defaulting to the git executable
+    ///
+    /// The variable is read once per process, so an unknown value is only
+    /// reported once.
+    pub fn from_env() -> Self {
+        static BACKEND: OnceLock<GitBackend> = OnceLock::new();
+        *BACKEND.get_or_init(Self::read_env)
+    }
 
-use crate::domain::repositories::{FileSystemManager, GitOperations, RepositoryFetcher};
-use crate::domain::DomainError;
+    fn read_env() -> Self {
+        let Ok(value) = std::env::var(GIT_BACKEND_ENV) else {
+            return Self::Cli;
+        };
+        match value.trim().to_lowercase().as_str() {
+            "" | "cli" => Self::Cli,
+            "libgit2" => Self::Libgit2,
+            other => {
+                warn!(
+                    "Unknown git backend '{}' in {} (expected cli or libgit2), using cli",
+                    other, GIT_BACKEND_ENV
+                );
+                Self::Cli
+            }
+        }
+    }
+
+    /// Repository checks for the project acdm runs in
+    pub fn operations(self) -> Box<dyn GitOperations> {
+        match self {
+            Self::Cli => Box::new(GitOperationsImpl::new()),
+            Self::Libgit2 => Box::new(LibGitOperations::new()),
+        }
+    }
+
+    /// Store consulting the user's configured git credential helpers
+    pub fn credential_store(self) -> Box<dyn CredentialStore> {
+        match self {
+            Self::Cli => Box::new(GitCredentialStore::new()),
+            Self::Libgit2 => Box::new(LibGitCredentialStore::new()),
+        }
+    }
+}
 
 pub struct GitOperationsImpl;
 
@@ -74,12 +140,7 @@ impl GitOperations for GitOperationsImpl {
         }
     }
 
-    fn get_status(
-        &self,
-        repo_path: &Path,
-    ) -> Result<crate::domain::repositories::GitStatus, DomainError> {
-        use crate::domain::repositories::GitStatus;
-
+    fn get_status(&self, repo_path: &Path) -> Result<GitStatus, DomainError> {
         // Check if there are staged changes
         let staged_output = Command::new("git")
             .args(["diff", "--cached", "--quiet"])
@@ -125,16 +186,198 @@ impl GitOperations for GitOperationsImpl {
             has_untracked_files,
         })
     }
+
+    fn get_paths_status(
+        &self,
+        repo_path: &Path,
+        paths: &[PathBuf],
+    ) -> Result<GitStatus, DomainError> {
+        let mut status = GitStatus {
+            has_staged_changes: false,
+            has_unstaged_changes: false,
+            has_untracked_files: false,
+        };
+        if paths.is_empty() {
+            return Ok(status);
+        }
+
+        let output = Command::new("git")
+            .args([
+                "--literal-pathspecs",
+                "status",
+                "--porcelain",
+                "--untracked-files=all",
+                "--",
+            ])
+            .args(paths)
+            .current_dir(repo_path)
+            .output()
+            .map_err(|e| DomainError::GitError(format!("Failed to check status: {}", e)))?;
+        if !output.status.success() {
+            return Err(DomainError::GitError(format!(
+                "Failed to check status: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        // Each line starts with the index and the working tree state
+        for line in output.stdout.split(|b| *b == b'\n') {
+            match line {
+                [b'?', b'?', ..] => status.has_untracked_files = true,
+                [index, worktree, ..] => {
+                    status.has_staged_changes |= *index != b' ';
+                    status.has_unstaged_changes |= *worktree != b' ';
+                }
+                _ => {}
+            }
+        }
+        Ok(status)
+    }
+
+    fn commit(
+        &self,
+        repo_path: &Path,
+        paths: &[PathBuf],
+        message: &str,
+    ) -> Result<Option<String>, DomainError> {
+        let git = |args: &[&OsStr], action: &str| -> Result<Output, DomainError> {
+            Command::new("git")
+                .arg("--literal-pathspecs")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .map_err(|e| DomainError::GitError(format!("Failed to {}: {}", action, e)))
+        };
+
+        // Paths that neither exist nor are tracked have nothing to commit, and
+        // git refuses pathspecs matching nothing
+        let mut pathspecs: Vec<&OsStr> = Vec::new();
+        for path in paths {
+            let tracked = git(
+                &["ls-files".as_ref(), "--".as_ref(), path.as_ref()],
+                "list files",
+            )?;
+            if repo_path.join(path).exists() || !tracked.stdout.is_empty() {
+                pathspecs.push(path.as_os_str());
+            }
+        }
+        if pathspecs.is_empty() {
+            return Ok(None);
+        }
+
+        let with_pathspecs = |args: &[&'static str]| -> Vec<&OsStr> {
+            args.iter()
+                .map(|arg| OsStr::new(*arg))
+                .chain(std::iter::once(OsStr::new("--")))
+                .chain(pathspecs.iter().copied())
+                .collect()
+        };
+
+        let output = git(&with_pathspecs(&["add", "-A"]), "stage changes")?;
+        if !output.status.success() {
+            return Err(DomainError::GitError(format!(
+                "Failed to stage changes: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let output = git(
+            &with_pathspecs(&["diff", "--cached", "--quiet"]),
+            "check staged changes",
+        )?;
+        if output.status.success() {
+            debug!("Nothing to commit in {:?}", pathspecs);
+            return Ok(None);
+        }
+
+        let mut args = vec![OsStr::new("commit"), OsStr::new("-m"), OsStr::new(message)];
+        args.extend(with_pathspecs(&[]));
+        let output = git(&args, "commit")?;
+        if !output.status.success() {
+            return Err(DomainError::GitError(format!(
+                "Failed to commit: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let output = git(&["rev-parse".as_ref(), "HEAD".as_ref()], "resolve HEAD")?;
+        Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ))
+    }
+
+    fn create_branch(&self, repo_path: &Path, name: &str) -> Result<(), DomainError> {
+        let exists = Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("refs/heads/{}", name))
+            .current_dir(repo_path)
+            .output()
+            .map_err(|e| DomainError::GitError(format!("Failed to check branches: {}", e)))?;
+        if exists.status.success() {
+            return Err(DomainError::GitError(format!(
+                "Branch '{}' already exists",
+                name
+            )));
+        }
+
+        let output = Command::new("git")
+            .args(["switch", "--quiet", "-c", name])
+            .current_dir(repo_path)
+            .output()
+            .map_err(|e| DomainError::GitError(format!("Failed to create branch: {}", e)))?;
+        if !output.status.success() {
+            return Err(DomainError::GitError(format!(
+                "Failed to create branch '{}': {}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    fn author(&self, repo_path: &Path) -> Result<Option<String>, DomainError> {
+        let output = Command::new("git")
+            .args(["var", "GIT_AUTHOR_IDENT"])
+            .current_dir(repo_path)
+            .output()
+            .map_err(|e| DomainError::GitError(format!("Failed to read author: {}", e)))?;
+        if !output.status.success() {
+            debug!(
+                "No author identity configured: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Ok(None);
+        }
+
+        // `Name <email> <timestamp> <timezone>`
+        let ident = String::from_utf8_lossy(&output.stdout);
+        Ok(ident.rfind('>').map(|end| ident[..=end].trim().to_string()))
+    }
 }
 
+/// Environment variable holding the username for the ephemeral credential helper
+const USERNAME_ENV: &str = "ACDM_GIT_USERNAME";
+
+/// Environment variable holding the secret for the ephemeral credential helper
+const PASSWORD_ENV: &str = "ACDM_GIT_PASSWORD";
+
+/// Credential helper answering from the environment, so secrets never appear
+/// in command arguments or git configuration values
+const CREDENTIAL_HELPER: &str = "!f() { test \"$1\" = get || return 0; \
+    echo \"username=${ACDM_GIT_USERNAME}\"; echo \"password=${ACDM_GIT_PASSWORD}\"; }; f";
+
 pub struct GitRepositoryFetcher {
     git_command_path: String,
+    cache_dir: PathBuf,
+    auth_service: AuthenticationService,
 }
 
 impl Default for GitRepositoryFetcher {
     fn default() -> Self {
         Self {
             git_command_path: "git".to_string(),
+            cache_dir: default_cache_dir(),
+            auth_service: AuthenticationService::new(),
         }
     }
 }
@@ -144,14 +387,159 @@ impl GitRepositoryFetcher {
         Self::default()
     }
 
-    // Function removed as it was unused
+    /// Use an authentication service configured from `acdm.toml`
+    pub fn with_auth_service(mut self, auth_service: AuthenticationService) -> Self {
+        self.auth_service = auth_service;
+        self
+    }
+
+    /// Creates a git command, applying credentials for the remote it talks to
+    fn git_command(&self, url: Option<&str>) -> Result<Command, DomainError> {
+        let mut command = Command::new(&self.git_command_path);
+
+        if let Some(url) = url {
+            if let Some(auth) = self.auth_service.get_auth_for_repository(url)? {
+                debug!("Using {} authentication", auth.auth_type);
+                apply_auth(&mut command, &auth);
+            }
+        }
+
+        Ok(command)
+    }
+
+    /// Location of the cached bare clone for a repository URL
+    fn cache_path(&self, url: &str) -> PathBuf {
+        self.cache_dir.join("repos").join(cache_key(url))
+    }
+
+    /// Runs a git command and returns its stdout, mapping failures to domain errors
+    ///
+    /// `remote` is the URL the command talks to, if any, and selects the credentials.
+    fn run_git(
+        &self,
+        args: &[&str],
+        cwd: Option<&Path>,
+        remote: Option<&str>,
+    ) -> Result<String, DomainError> {
+        debug!(
+            "Running git command: {} {}",
+            self.git_command_path,
+            redact_args(args)
+        );
+
+        let mut command = self.git_command(remote)?;
+        command.args(args);
+        if let Some(dir) = cwd {
+            command.current_dir(dir);
+        }
+
+        let output = command.output().map_err(|e| {
+            error!("Failed to execute git {}: {}", args[0], e);
+            DomainError::GitError(format!("Failed to execute git {}: {}", args[0], e))
+        })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            error!("Git {} failed: {}", args[0], stderr);
+            return Err(DomainError::GitError(format!(
+                "Git {} command failed: {}",
+                args[0],
+                stderr.trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Creates or refreshes the cached bare clone of a repository
+    fn refresh_cache(&self, url: &str) -> Result<PathBuf, DomainError> {
+        let cache_path = self.cache_path(url);
+
+        if cache_path.join("HEAD").exists() {
+            info!("Refreshing cached repository: {}", cache_path.display());
+            self.run_git(
+                &[
+                    "fetch",
+                    "--quiet",
+                    "--prune",
+                    "--tags",
+                    url,
+                    "+refs/heads/*:refs/heads/*",
+                ],
+                Some(&cache_path),
+                Some(url),
+            )?;
+        } else {
+            info!(
+                "Caching repository {} in {}",
+                AuthenticationService::redact_url(url),
+                cache_path.display()
+            );
+            std::fs::create_dir_all(&self.cache_dir).map_err(|e| {
+                DomainError::FileSystemError(format!("Failed to create cache directory: {}", e))
+            })?;
+            self.run_git(
+                &[
+                    "clone",
+                    "--quiet",
+                    "--bare",
+                    "--filter=blob:none",
+                    url,
+                    &cache_path.to_string_lossy(),
+                ],
+                None,
+                Some(url),
+            )?;
+        }
+
+        Ok(cache_path)
+    }
+}
+
+impl RepositoryHistory for GitRepositoryFetcher {
+    fn log(
+        &self,
+        url: &str,
+        from: &str,
+        to: &str,
+        patterns: &[String],
+    ) -> Result<Vec<CommitSummary>, DomainError> {
+        let cache_path = self.refresh_cache(url)?;
+
+        let range = format!("{}..{}", from, to);
+        let mut args = vec![
+            "log".to_string(),
+            "--format=%H%x1f%an%x1f%ad%x1f%s".to_string(),
+            "--date=short".to_string(),
+            range,
+            "--".to_string(),
+        ];
+        // Sparse paths are glob patterns, so use git's glob pathspec magic
+        args.extend(patterns.iter().map(|p| format!(":(glob){}", p)));
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let stdout = self.run_git(&args, Some(&cache_path), None)?;
+
+        Ok(stdout
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, '\x1f');
+                Some(CommitSummary {
+                    id: fields.next()?.to_string(),
+                    author: fields.next()?.to_string(),
+                    date: fields.next()?.to_string(),
+                    subject: fields.next()?.to_string(),
+                })
+            })
+            .collect())
+    }
 }
 
 impl RepositoryFetcher for GitRepositoryFetcher {
     fn fetch(&self, url: &str, revision: &str, temp_path: &Path) -> Result<(), DomainError> {
         debug!(
             "Fetching repository: {} revision: {} to {}",
-            url,
+            AuthenticationService::redact_url(url),
             revision,
             temp_path.display()
         );
@@ -170,10 +558,11 @@ impl RepositoryFetcher for GitRepositoryFetcher {
         debug!(
             "Running git command: {} {}",
             self.git_command_path,
-            clone_args.join(" ")
+            redact_args(&clone_args)
         );
 
-        let output = Command::new(&self.git_command_path)
+        let output = self
+            .git_command(Some(url))?
             .args(clone_args)
             .output()
             .map_err(|e| {
@@ -195,10 +584,11 @@ impl RepositoryFetcher for GitRepositoryFetcher {
                 debug!(
                     "Running fallback git command: {} {}",
                     self.git_command_path,
-                    base_clone_args.join(" ")
+                    redact_args(&base_clone_args)
                 );
 
-                let base_output = Command::new(&self.git_command_path)
+                let base_output = self
+                    .git_command(Some(url))?
                     .args(base_clone_args)
                     .output()
                     .map_err(|e| {
@@ -259,144 +649,212 @@ impl RepositoryFetcher for GitRepositoryFetcher {
         Ok(())
     }
 
+    fn tracks_revisions(&self, _dependency: &Dependency) -> bool {
+        true
+    }
+
+    fn keeps_upstream_paths(&self, _dependency: &Dependency) -> bool {
+        true
+    }
+
+    fn validate_dependency(&self, dependency: &Dependency) -> Vec<String> {
+        validate_git_dependency(dependency)
+    }
+
+    fn resolve_revision(&self, repo_path: &Path) -> Result<Option<String>, DomainError> {
+        let commit = self.run_git(&["rev-parse", "HEAD"], Some(repo_path), None)?;
+        Ok(Some(commit.trim().to_string()))
+    }
+
+    fn resolve_remote_revision(
+        &self,
+        url: &str,
+        revision: &str,
+    ) -> Result<Option<String>, DomainError> {
+        // A full commit hash cannot move
+        if revision.len() == 40 && revision.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(Some(revision.to_lowercase()));
+        }
+
+        // Peeled entries only match a pattern ending in `^{}`
+        let peeled = format!("{}^{{}}", revision);
+        let stdout = self.run_git(&["ls-remote", url, revision, &peeled], None, Some(url))?;
+
+        // Prefer the peeled commit of annotated tags
+        let mut resolved = None;
+        for line in stdout.lines() {
+            let mut fields = line.split('\t');
+            let (Some(sha), Some(reference)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            if reference.ends_with("^{}") {
+                return Ok(Some(sha.to_string()));
+            }
+            if resolved.is_none() {
+                resolved = Some(sha.to_string());
+            }
+        }
+
+        Ok(resolved)
+    }
+
     fn extract_paths(
         &self,
         repo_path: &Path,
         patterns: &[String],
         target_path: &Path,
     ) -> Result<(), DomainError> {
-        debug!(
-            "Extracting paths from {} to {}",
-            repo_path.display(),
-            target_path.display()
-        );
-        debug!("Patterns: {:?}", patterns);
+        crate::infrastructure::file_system::extract_matching(repo_path, patterns, target_path)
+    }
+}
+
+/// Credential store backed by the git credential helpers configured by the user
+pub struct GitCredentialStore {
+    git_command_path: String,
+}
 
-        if patterns.is_empty() {
-            debug!("No patterns specified, copying everything");
-            // If no patterns are specified, copy everything
-            let fs_manager = crate::infrastructure::file_system::FileSystemManagerImpl::new();
-            return fs_manager.copy_content(repo_path, target_path);
+impl Default for GitCredentialStore {
+    fn default() -> Self {
+        Self {
+            git_command_path: "git".to_string(),
         }
+    }
+}
 
-        // Compile all patterns
-        debug!("Compiling {} patterns", patterns.len());
-        let compiled_patterns: Vec<Pattern> = patterns
-            .iter()
-            .map(|p| {
-                debug!("Compiling pattern: {}", p);
-                Pattern::new(p).map_err(|e| {
-                    error!("Invalid pattern '{}': {}", p, e);
-                    DomainError::PathPatternError(format!("Invalid pattern '{}': {}", p, e))
-                })
-            })
-            .collect::<Result<_, _>>()?;
+impl GitCredentialStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
-        // Walk the repository and copy matching files
-        let mut copied_any = false;
-        let mut matched_count = 0;
+impl CredentialStore for GitCredentialStore {
+    fn fill(&self, url: &str) -> Result<Option<(String, String)>, DomainError> {
+        debug!(
+            "Asking git credential fill for {}",
+            AuthenticationService::redact_url(url)
+        );
 
-        debug!("Walking repository for matching files");
-        for entry in WalkDir::new(repo_path).min_depth(1) {
-            let entry = entry.map_err(|e| {
-                error!("Failed to walk directory {}: {}", repo_path.display(), e);
-                DomainError::FileSystemError(format!("Failed to walk directory: {}", e))
+        // Never fall back to an interactive prompt
+        let mut child = Command::new(&self.git_command_path)
+            .args(["credential", "fill"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| DomainError::GitError(format!("Failed to run git credential: {}", e)))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            writeln!(stdin, "url={}\n", url).map_err(|e| {
+                DomainError::GitError(format!("Failed to write to git credential: {}", e))
             })?;
+        }
 
-            // Get the path relative to the repository root
-            let relative_path = entry.path().strip_prefix(repo_path).map_err(|e| {
-                error!("Failed to strip prefix {}: {}", repo_path.display(), e);
-                DomainError::FileSystemError(format!("Failed to strip prefix: {}", e))
-            })?;
+        let output = child
+            .wait_with_output()
+            .map_err(|e| DomainError::GitError(format!("Failed to run git credential: {}", e)))?;
 
-            // Check if the path matches any pattern
-            let relative_path_str = relative_path.to_string_lossy();
-            let should_include = compiled_patterns
-                .iter()
-                .any(|p| p.matches(&relative_path_str));
-
-            if should_include {
-                matched_count += 1;
-                debug!("Path matched pattern: {}", relative_path_str);
-                let target_file_path = target_path.join(relative_path);
-
-                if entry.path().is_dir() {
-                    debug!("Creating directory: {}", target_file_path.display());
-                    std::fs::create_dir_all(&target_file_path).map_err(|e| {
-                        error!(
-                            "Failed to create directory {}: {}",
-                            target_file_path.display(),
-                            e
-                        );
-                        DomainError::FileSystemError(format!("Failed to create directory: {}", e))
-                    })?;
-                } else {
-                    // Ensure parent directories exist
-                    if let Some(parent) = target_file_path.parent() {
-                        if !parent.exists() {
-                            debug!("Creating parent directory: {}", parent.display());
-                            std::fs::create_dir_all(parent).map_err(|e| {
-                                error!(
-                                    "Failed to create parent directory {}: {}",
-                                    parent.display(),
-                                    e
-                                );
-                                DomainError::FileSystemError(format!(
-                                    "Failed to create parent directory: {}",
-                                    e
-                                ))
-                            })?;
-                        }
-                    }
+        if !output.status.success() {
+            debug!(
+                "git credential fill failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Ok(None);
+        }
 
-                    debug!(
-                        "Copying file: {} to {}",
-                        entry.path().display(),
-                        target_file_path.display()
-                    );
-                    std::fs::copy(entry.path(), &target_file_path).map_err(|e| {
-                        error!(
-                            "Failed to copy file from {} to {}: {}",
-                            entry.path().display(),
-                            target_file_path.display(),
-                            e
-                        );
-                        DomainError::FileSystemError(format!("Failed to copy file: {}", e))
-                    })?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let value = |key: &str| {
+            stdout
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .map(str::to_string)
+        };
 
-                    copied_any = true;
-                }
+        Ok(value("username").zip(value("password")))
+    }
+}
+
+/// Passes resolved credentials to git through its environment
+fn apply_auth(command: &mut Command, auth: &RepositoryAuth) {
+    match auth.auth_type {
+        AuthType::None => {}
+        AuthType::Ssh => {
+            command.env("GIT_SSH_COMMAND", ssh_command(&auth.ssh));
+        }
+        AuthType::HttpsToken => {
+            if let Some(token) = &auth.credentials {
+                set_credential_helper(command, TOKEN_USERNAME, token);
             }
         }
+        AuthType::HttpsBasic => {
+            if let Some((username, password)) =
+                auth.credentials.as_ref().and_then(|c| c.split_once(':'))
+            {
+                set_credential_helper(command, username, password);
+            }
+        }
+    }
+}
 
-        debug!(
-            "Matched {} paths, copied files: {}",
-            matched_count, copied_any
-        );
+/// Builds the `GIT_SSH_COMMAND` for the configured key and host verification
+///
+/// Options are appended to an existing `GIT_SSH_COMMAND`, so wrappers set by
+/// the user keep working. Host keys are checked strictly unless disabled.
+fn ssh_command(ssh: &SshSettings) -> String {
+    let mut command = std::env::var("GIT_SSH_COMMAND").unwrap_or_else(|_| "ssh".to_string());
+
+    if let Some(key) = &ssh.key {
+        command.push_str(&format!(
+            " -i {} -o IdentitiesOnly=yes",
+            shell_quote(&key.to_string_lossy())
+        ));
+    }
+    if let Some(known_hosts) = &ssh.known_hosts {
+        command.push_str(&format!(
+            " -o UserKnownHostsFile={}",
+            shell_quote(&known_hosts.to_string_lossy())
+        ));
+    }
 
-        if !copied_any && !patterns.is_empty() {
-            warn!("No files matched the provided patterns: {:?}", patterns);
-            return Err(DomainError::PathPatternError(format!(
-                "No files matched the provided patterns: {:?}",
-                patterns
-            )));
-        }
+    let strict = ssh.strict_host_key_checking.unwrap_or(true);
+    command.push_str(if strict {
+        " -o StrictHostKeyChecking=yes"
+    } else {
+        " -o StrictHostKeyChecking=no"
+    });
 
-        debug!("Path extraction completed successfully");
+    command
+}
 
-        // Additional logging to help diagnose path issues
-        if !target_path.exists() {
-            warn!(
-                "Target path does not exist after extraction: {}",
-                target_path.display()
-            );
-        } else {
-            debug!(
-                "Target path exists after extraction: {}",
-                target_path.display()
-            );
-        }
+/// Quotes a value for the shell git runs `GIT_SSH_COMMAND` with
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
 
-        Ok(())
-    }
+/// Installs the ephemeral credential helper via `GIT_CONFIG_*` variables
+///
+/// Configured helpers are reset first so the resolved credentials win, and any
+/// `GIT_CONFIG_*` entries already present in the environment are preserved.
+fn set_credential_helper(command: &mut Command, username: &str, password: &str) {
+    let base = std::env::var("GIT_CONFIG_COUNT")
+        .ok()
+        .and_then(|c| c.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    command
+        .env(format!("GIT_CONFIG_KEY_{}", base), "credential.helper")
+        .env(format!("GIT_CONFIG_VALUE_{}", base), "")
+        .env(format!("GIT_CONFIG_KEY_{}", base + 1), "credential.helper")
+        .env(format!("GIT_CONFIG_VALUE_{}", base + 1), CREDENTIAL_HELPER)
+        .env("GIT_CONFIG_COUNT", (base + 2).to_string())
+        .env(USERNAME_ENV, username)
+        .env(PASSWORD_ENV, password);
+}
+
+/// Joins command arguments for logging, masking passwords embedded in URLs
+fn redact_args(args: &[&str]) -> String {
+    args.iter()
+        .map(|arg| AuthenticationService::redact_url(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
@@ -0,0 +1,47 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use sha2::{Digest, Sha256};
+
+use crate::domain::repositories::unknown_options;
+use crate::domain::url::RepositoryUrl;
+use crate::domain::Dependency;
+use crate::infrastructure::source::validate_url;
+
+/// Username sent along with bare tokens, accepted by GitHub, GitLab and Gitea
+pub(crate) const TOKEN_USERNAME: &str = "x-access-token";
+
+/// Name of the cache entry for a repository URL, safe as a directory name
+///
+/// Different spellings of the same repository share a cache entry. The
+/// entry is named after the repository, followed by a digest of the
+/// normalized URL so that distinct repositories never share one.
+pub(crate) fn cache_key(url: &str) -> String {
+    let normalized = RepositoryUrl::parse(url)
+        .map(|u| u.cache_key())
+        .unwrap_or_else(|_| url.to_string());
+    let name: String = normalized
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .take(32)
+        .collect();
+    let digest = hex::encode(Sha256::digest(normalized.as_bytes()));
+
+    if name.is_empty() {
+        digest[..16].to_string()
+    } else {
+        format!("{}-{}", name, &digest[..16])
+    }
+}
+
+/// Checks a git source, whichever backend fetches it
+pub(crate) fn validate_git_dependency(dependency: &Dependency) -> Vec<String> {
+    let mut issues: Vec<String> = validate_url(dependency).into_iter().collect();
+    if dependency.revision.trim().is_empty() {
+        issues.push("rev must not be empty".to_string());
+    }
+    issues.extend(unknown_options(dependency));
+    issues
+}
@@ -0,0 +1,344 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use base64::Engine;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use crate::domain::auth::AuthenticationService;
+use crate::domain::repositories::RepositoryFetcher;
+use crate::domain::url::RepositoryUrl;
+use crate::domain::validation::is_sha256;
+use crate::domain::{AuthType, Dependency, DomainError};
+use crate::infrastructure::file_system::{default_cache_dir, extract_matching};
+use crate::infrastructure::source::{parse_options, validate_url};
+
+/// Sends a GET request with `headers`, answering an authentication challenge
+/// with the credentials configured for the URL's host
+///
+/// Credentials are only sent to hosts with an `[auth]` section, only once the
+/// server asked for them with a `401`, and never across a redirect.
+pub fn get(
+    auth_service: &AuthenticationService,
+    url: &str,
+    headers: &[(&str, &str)],
+) -> Result<ureq::Response, DomainError> {
+    // ureq drops the Authorization header on every redirect
+    let request = || {
+        headers
+            .iter()
+            .fold(ureq::get(url), |request, (name, value)| {
+                request.set(name, value)
+            })
+    };
+    let failed = |e: ureq::Error| {
+        DomainError::RepositoryError(format!(
+            "Failed to download {}: {}",
+            AuthenticationService::redact_url(url),
+            e
+        ))
+    };
+
+    let challenge = match request().call() {
+        Err(ureq::Error::Status(401, response)) => response,
+        result => return result.map_err(failed),
+    };
+
+    // Only the host the credentials are configured for may receive them
+    let same_host = match (
+        RepositoryUrl::parse(url),
+        RepositoryUrl::parse(challenge.get_url()),
+    ) {
+        (Ok(requested), Ok(challenged)) => requested.authority() == challenged.authority(),
+        _ => false,
+    };
+    let authorization = match auth_service.get_host_auth(url)? {
+        Some(auth) if same_host => match (&auth.auth_type, &auth.credentials) {
+            (AuthType::HttpsToken, Some(token)) => Some(format!("Bearer {}", token)),
+            (AuthType::HttpsBasic, Some(credentials)) => Some(format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            )),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    match authorization {
+        Some(authorization) => {
+            debug!(
+                "Authenticating to {}",
+                AuthenticationService::redact_url(url)
+            );
+            request()
+                .set("Authorization", &authorization)
+                .call()
+                .map_err(failed)
+        }
+        None => Err(failed(ureq::Error::Status(401, challenge))),
+    }
+}
+
+/// Options of `http` sources, from `[sources.http]`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HttpOptions {
+    files: Vec<HttpFile>,
+}
+
+/// A single file downloaded by an HTTP source
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HttpFile {
+    /// Absolute URL, or a URL relative to the source's `repo`
+    url: String,
+
+    /// Location of the file below the target
+    path: PathBuf,
+
+    /// Expected SHA-256 of the content, as hex
+    sha256: String,
+}
+
+/// Validators of a cached download, stored next to its content
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheMetadata {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+}
+
+/// Fetcher downloading the files of HTTP sources
+///
+/// Responses carrying an `ETag` or `Last-Modified` header are cached, and
+/// later downloads of the same URL are conditional requests answered from the
+/// cache on `304 Not Modified`. Content is verified against the configured
+/// checksum either way.
+pub struct HttpFileFetcher {
+    cache_dir: PathBuf,
+    auth_service: AuthenticationService,
+}
+
+impl Default for HttpFileFetcher {
+    fn default() -> Self {
+        Self {
+            cache_dir: default_cache_dir(),
+            auth_service: AuthenticationService::default(),
+        }
+    }
+}
+
+impl HttpFileFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `auth_service` to authenticate downloads
+    pub fn with_auth_service(mut self, auth_service: AuthenticationService) -> Self {
+        self.auth_service = auth_service;
+        self
+    }
+
+    /// Cache directory of a URL, keyed by its hash since URLs can be long
+    fn cache_path(&self, url: &str) -> PathBuf {
+        self.cache_dir
+            .join("http")
+            .join(hex::encode(Sha256::digest(url.as_bytes())))
+    }
+
+    /// Downloads a URL, reusing the cached content when it is not modified
+    fn download(&self, url: &str) -> Result<Vec<u8>, DomainError> {
+        let redacted = AuthenticationService::redact_url(url);
+        let cache_path = self.cache_path(url);
+        let content_path = cache_path.join("content");
+        let metadata_path = cache_path.join("metadata.json");
+
+        let cached = fs::read_to_string(&metadata_path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<CacheMetadata>(&json).ok())
+            .filter(|_| content_path.exists());
+
+        let mut headers = Vec::new();
+        if let Some(metadata) = &cached {
+            if let Some(etag) = &metadata.etag {
+                headers.push(("If-None-Match", etag.as_str()));
+            }
+            if let Some(last_modified) = &metadata.last_modified {
+                headers.push(("If-Modified-Since", last_modified.as_str()));
+            }
+        }
+
+        info!("Downloading {}", redacted);
+        let response = get(&self.auth_service, url, &headers)?;
+
+        if response.status() == 304 && cached.is_some() {
+            debug!("{} is not modified, using the cached copy", redacted);
+            return fs::read(&content_path).map_err(|e| {
+                DomainError::FileSystemError(format!(
+                    "Failed to read cached download {}: {}",
+                    content_path.display(),
+                    e
+                ))
+            });
+        }
+
+        let metadata = CacheMetadata {
+            url: redacted.clone(),
+            etag: response.header("ETag").map(str::to_string),
+            last_modified: response.header("Last-Modified").map(str::to_string),
+        };
+
+        let mut content = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut content)
+            .map_err(|e| {
+                DomainError::RepositoryError(format!("Failed to download {}: {}", redacted, e))
+            })?;
+
+        if metadata.etag.is_some() || metadata.last_modified.is_some() {
+            // A failing cache only costs a full download next time
+            let stored = fs::create_dir_all(&cache_path)
+                .and_then(|_| fs::write(&content_path, &content))
+                .and_then(|_| {
+                    fs::write(
+                        &metadata_path,
+                        serde_json::to_string_pretty(&metadata).unwrap_or_default(),
+                    )
+                });
+            if let Err(e) = stored {
+                debug!("Failed to cache {}: {}", redacted, e);
+            }
+        }
+
+        Ok(content)
+    }
+}
+
+/// Resolves a file URL against the source's base URL
+fn resolve_url(base: &str, url: &str) -> String {
+    if url.contains("://") {
+        url.to_string()
+    } else {
+        format!(
+            "{}/{}",
+            base.trim_end_matches('/'),
+            url.trim_start_matches('/')
+        )
+    }
+}
+
+/// Whether a file path is relative and made of plain names only, so that it
+/// stays inside the directory it is joined to
+fn is_contained(path: &Path) -> bool {
+    !path.as_os_str().is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn write_file(path: &Path, content: &[u8]) -> Result<(), DomainError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            DomainError::FileSystemError(format!("Failed to create directory: {}", e))
+        })?;
+    }
+    fs::write(path, content).map_err(|e| {
+        DomainError::FileSystemError(format!("Failed to write {}: {}", path.display(), e))
+    })
+}
+
+impl RepositoryFetcher for HttpFileFetcher {
+    fn fetch(&self, url: &str, _revision: &str, temp_path: &Path) -> Result<(), DomainError> {
+        // Without a file list, the content is named after the last URL segment
+        let name = url
+            .split(['?', '#'])
+            .next()
+            .and_then(|u| u.trim_end_matches('/').rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("index");
+        write_file(&temp_path.join(name), &self.download(url)?)
+    }
+
+    fn extract_paths(
+        &self,
+        repo_path: &Path,
+        patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        extract_matching(repo_path, patterns, target_path)
+    }
+
+    fn validate_dependency(&self, dependency: &Dependency) -> Vec<String> {
+        let options: HttpOptions = match parse_options(dependency) {
+            Ok(options) => options,
+            Err(message) => return vec![message],
+        };
+
+        let mut issues: Vec<String> = validate_url(dependency).into_iter().collect();
+        if options.files.is_empty() {
+            issues.push("http sources must list at least one file".to_string());
+        }
+        let mut paths = HashSet::new();
+        for file in &options.files {
+            if file.url.trim().is_empty() {
+                issues.push(format!("file '{}' has no url", file.path.display()));
+            }
+            if !is_sha256(&file.sha256) {
+                issues.push(format!(
+                    "sha256 '{}' of file '{}' is not a SHA-256 hex digest",
+                    file.sha256,
+                    file.path.display()
+                ));
+            }
+            if !is_contained(&file.path) {
+                issues.push(format!(
+                    "file path '{}' must be relative and stay inside the target",
+                    file.path.display()
+                ));
+            }
+            if !paths.insert(&file.path) {
+                issues.push(format!("duplicate file path '{}'", file.path.display()));
+            }
+        }
+        issues
+    }
+
+    fn fetch_dependency(
+        &self,
+        dependency: &Dependency,
+        url: &str,
+        temp_path: &Path,
+    ) -> Result<Option<String>, DomainError> {
+        let options: HttpOptions =
+            parse_options(dependency).map_err(DomainError::ConfigurationError)?;
+        // Paths are checked before anything is downloaded, as the
+        // configuration may not have been validated
+        if let Some(file) = options.files.iter().find(|f| !is_contained(&f.path)) {
+            return Err(DomainError::ConfigurationError(format!(
+                "file path '{}' must be relative and stay inside the target",
+                file.path.display()
+            )));
+        }
+        for file in &options.files {
+            let file_url = resolve_url(url, &file.url);
+            let content = self.download(&file_url)?;
+
+            let actual = hex::encode(Sha256::digest(&content));
+            if !actual.eq_ignore_ascii_case(&file.sha256) {
+                return Err(DomainError::RepositoryError(format!(
+                    "Checksum mismatch for {}: expected sha256 {}, got {}",
+                    AuthenticationService::redact_url(&file_url),
+                    file.sha256,
+                    actual
+                )));
+            }
+
+            write_file(&temp_path.join(&file.path), &content)?;
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,851 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use base64::Engine;
+use git2::build::CheckoutBuilder;
+use git2::cert::Cert;
+use git2::{
+    AutotagOption, BranchType, CertificateCheckStatus, Commit, Config, Cred, CredentialHelper,
+    CredentialType, Direction, ErrorClass, ErrorCode, FetchOptions, FetchPrune, Index,
+    IndexAddOption, Oid, RemoteCallbacks, Repository, Sort, Status, StatusOptions,
+};
+use glob::{MatchOptions, Pattern};
+use hmac::{Hmac, Mac};
+use log::{debug, info, warn};
+use sha1::Sha1;
+use std::path::{Path, PathBuf};
+
+use crate::domain::auth::AuthenticationService;
+use crate::domain::repositories::{
+    CredentialStore, GitOperations, GitStatus, RepositoryFetcher, RepositoryHistory,
+};
+use crate::domain::time::civil_date;
+use crate::domain::{
+    AuthType, CommitSummary, Dependency, DomainError, RepositoryAuth, SshSettings,
+};
+use crate::infrastructure::file_system::{default_cache_dir, extract_matching};
+use crate::infrastructure::git_common::{cache_key, validate_git_dependency, TOKEN_USERNAME};
+
+/// Maps a libgit2 error to a domain error naming the operation that failed
+fn git_error(action: &str, error: git2::Error) -> DomainError {
+    let reason = match error.code() {
+        ErrorCode::Auth => "authentication failed: ",
+        ErrorCode::Certificate => "host verification failed: ",
+        _ => "",
+    };
+    DomainError::GitError(format!(
+        "Failed to {}: {}{}",
+        action,
+        reason,
+        error.message()
+    ))
+}
+
+/// Repository checks backed by libgit2
+pub struct LibGitOperations;
+
+impl Default for LibGitOperations {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl LibGitOperations {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl GitOperations for LibGitOperations {
+    fn is_git_repository(&self, path: &Path) -> Result<bool, DomainError> {
+        debug!("Checking if path is a git repository: {}", path.display());
+
+        if path.as_os_str().is_empty() || !path.exists() {
+            warn!("Path is empty or does not exist: {}", path.display());
+            return Ok(false);
+        }
+
+        match Repository::discover(path) {
+            Ok(repo) => Ok(!repo.is_bare()),
+            Err(e) => {
+                debug!(
+                    "Path is not a git repository: {} ({})",
+                    path.display(),
+                    e.message()
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    fn get_status(&self, repo_path: &Path) -> Result<GitStatus, DomainError> {
+        let repo = Repository::discover(repo_path).map_err(|e| git_error("open repository", e))?;
+
+        // Untracked files only count below `repo_path`, like `git ls-files --other`
+        let scope = workdir_relative(&repo, repo_path).unwrap_or_default();
+
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+        let statuses = repo
+            .statuses(Some(&mut options))
+            .map_err(|e| git_error("read repository status", e))?;
+
+        let mut status = GitStatus {
+            has_staged_changes: false,
+            has_unstaged_changes: false,
+            has_untracked_files: false,
+        };
+        for entry in statuses.iter() {
+            let flags = entry.status();
+            status.has_staged_changes |= flags.intersects(STAGED);
+            status.has_unstaged_changes |= flags.intersects(UNSTAGED);
+            if flags.contains(Status::WT_NEW)
+                && entry
+                    .path()
+                    .is_some_and(|path| Path::new(path).starts_with(&scope))
+            {
+                status.has_untracked_files = true;
+            }
+        }
+
+        Ok(status)
+    }
+
+    fn get_paths_status(
+        &self,
+        repo_path: &Path,
+        paths: &[PathBuf],
+    ) -> Result<GitStatus, DomainError> {
+        let repo = Repository::discover(repo_path).map_err(|e| git_error("open repository", e))?;
+        let pathspecs = workdir_pathspecs(&repo, repo_path, paths)?;
+
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+        let statuses = repo
+            .statuses(Some(&mut options))
+            .map_err(|e| git_error("read repository status", e))?;
+
+        let mut status = GitStatus {
+            has_staged_changes: false,
+            has_unstaged_changes: false,
+            has_untracked_files: false,
+        };
+        for entry in statuses
+            .iter()
+            .filter(|entry| in_pathspecs(&pathspecs, entry.path_bytes()))
+        {
+            let flags = entry.status();
+            status.has_staged_changes |= flags.intersects(STAGED);
+            status.has_unstaged_changes |= flags.intersects(UNSTAGED);
+            status.has_untracked_files |= flags.contains(Status::WT_NEW);
+        }
+        Ok(status)
+    }
+
+    fn commit(
+        &self,
+        repo_path: &Path,
+        paths: &[PathBuf],
+        message: &str,
+    ) -> Result<Option<String>, DomainError> {
+        let repo = Repository::discover(repo_path).map_err(|e| git_error("open repository", e))?;
+        let pathspecs = workdir_pathspecs(&repo, repo_path, paths)?;
+        let in_scope = |path: &[u8]| in_pathspecs(&pathspecs, path);
+
+        // Stage the paths like `git add -A`
+        let mut index = repo.index().map_err(|e| git_error("read index", e))?;
+        index
+            .add_all(pathspecs.iter(), IndexAddOption::DEFAULT, None)
+            .map_err(|e| git_error("stage changes", e))?;
+        index
+            .update_all(pathspecs.iter(), None)
+            .map_err(|e| git_error("stage changes", e))?;
+        index.write().map_err(|e| git_error("write index", e))?;
+
+        // The committed tree is HEAD's with only these paths taken from the index
+        let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let mut tree_index = Index::new().map_err(|e| git_error("create index", e))?;
+        if let Some(head) = &head {
+            let tree = head.tree().map_err(|e| git_error("read HEAD", e))?;
+            tree_index
+                .read_tree(&tree)
+                .map_err(|e| git_error("read HEAD", e))?;
+        }
+        let replaced: Vec<PathBuf> = tree_index
+            .iter()
+            .filter(|entry| in_scope(&entry.path))
+            .map(|entry| PathBuf::from(String::from_utf8_lossy(&entry.path).to_string()))
+            .collect();
+        for path in replaced {
+            tree_index
+                .remove_path(&path)
+                .map_err(|e| git_error("build commit", e))?;
+        }
+        for entry in index.iter().filter(|entry| in_scope(&entry.path)) {
+            tree_index
+                .add(&entry)
+                .map_err(|e| git_error("build commit", e))?;
+        }
+        let tree_id = tree_index
+            .write_tree_to(&repo)
+            .map_err(|e| git_error("write tree", e))?;
+        if head.as_ref().map(|head| head.tree_id()) == Some(tree_id) {
+            debug!("Nothing to commit in {:?}", pathspecs);
+            return Ok(None);
+        }
+
+        let tree = repo
+            .find_tree(tree_id)
+            .map_err(|e| git_error("write tree", e))?;
+        let signature = repo
+            .signature()
+            .map_err(|e| git_error("determine the committer", e))?;
+        let parents: Vec<&Commit> = head.iter().collect();
+        let commit = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )
+            .map_err(|e| git_error("commit", e))?;
+
+        Ok(Some(commit.to_string()))
+    }
+
+    fn create_branch(&self, repo_path: &Path, name: &str) -> Result<(), DomainError> {
+        let repo = Repository::discover(repo_path).map_err(|e| git_error("open repository", e))?;
+        if repo.find_branch(name, BranchType::Local).is_ok() {
+            return Err(DomainError::GitError(format!(
+                "Branch '{}' already exists",
+                name
+            )));
+        }
+
+        let head = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|e| git_error("read HEAD", e))?;
+        let branch = repo
+            .branch(name, &head, false)
+            .map_err(|e| git_error(&format!("create branch '{}'", name), e))?;
+
+        // HEAD keeps pointing at the same commit, so the working tree stays as it is
+        let reference = branch.get().name().unwrap_or_default().to_string();
+        repo.set_head(&reference)
+            .map_err(|e| git_error(&format!("switch to branch '{}'", name), e))
+    }
+
+    fn author(&self, repo_path: &Path) -> Result<Option<String>, DomainError> {
+        let repo = Repository::discover(repo_path).map_err(|e| git_error("open repository", e))?;
+        // Fails when user.name or user.email is not configured
+        Ok(repo.signature().ok().map(|signature| {
+            format!(
+                "{} <{}>",
+                String::from_utf8_lossy(signature.name_bytes()),
+                String::from_utf8_lossy(signature.email_bytes())
+            )
+        }))
+    }
+}
+
+/// Changes to the index, reported as staged like `git diff --cached`
+const STAGED: Status = Status::INDEX_NEW
+    .union(Status::INDEX_MODIFIED)
+    .union(Status::INDEX_DELETED)
+    .union(Status::INDEX_RENAMED)
+    .union(Status::INDEX_TYPECHANGE);
+
+/// Changes to tracked files in the working tree, reported as unstaged
+const UNSTAGED: Status = Status::WT_MODIFIED
+    .union(Status::WT_DELETED)
+    .union(Status::WT_RENAMED)
+    .union(Status::WT_TYPECHANGE)
+    .union(Status::CONFLICTED);
+
+/// Paths relative to `repo_path` as `/`-separated paths in the working tree
+fn workdir_pathspecs(
+    repo: &Repository,
+    repo_path: &Path,
+    paths: &[PathBuf],
+) -> Result<Vec<String>, DomainError> {
+    let prefix = workdir_relative(repo, repo_path).ok_or_else(|| {
+        DomainError::GitError(format!("{} is not in a working tree", repo_path.display()))
+    })?;
+    Ok(paths
+        .iter()
+        .map(|path| {
+            prefix
+                .join(path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect())
+}
+
+/// Whether a working tree path is one of `pathspecs` or below one
+fn in_pathspecs(pathspecs: &[String], path: &[u8]) -> bool {
+    pathspecs.iter().any(|spec| {
+        path.strip_prefix(spec.as_bytes())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(b"/"))
+    })
+}
+
+/// Location of `path` relative to the working tree of `repo`
+fn workdir_relative(repo: &Repository, path: &Path) -> Option<PathBuf> {
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    path.strip_prefix(workdir).ok().map(Path::to_path_buf)
+}
+
+/// Fetcher for git sources backed by libgit2, needing no git executable
+///
+/// Behaves like `GitRepositoryFetcher`: branches and tags are fetched
+/// shallowly, other revisions from the full history, and upstream history is
+/// read from a cached bare repository.
+pub struct LibGitRepositoryFetcher {
+    cache_dir: PathBuf,
+    auth_service: AuthenticationService,
+}
+
+impl Default for LibGitRepositoryFetcher {
+    fn default() -> Self {
+        Self {
+            cache_dir: default_cache_dir(),
+            auth_service: AuthenticationService::new(),
+        }
+    }
+}
+
+impl LibGitRepositoryFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use an authentication service configured from `acdm.toml`
+    pub fn with_auth_service(mut self, auth_service: AuthenticationService) -> Self {
+        self.auth_service = auth_service;
+        self
+    }
+
+    /// Location of the cached bare repository for a repository URL
+    ///
+    /// Kept apart from the caches of the git executable, which are partial
+    /// clones libgit2 cannot read.
+    fn cache_path(&self, url: &str) -> PathBuf {
+        self.cache_dir.join("libgit2").join(cache_key(url))
+    }
+
+    /// Callbacks answering credential and host key requests for `url`
+    fn callbacks(&self, url: &str) -> Result<RemoteCallbacks<'static>, DomainError> {
+        let auth = self.auth_service.get_auth_for_repository(url)?;
+        if let Some(auth) = &auth {
+            debug!("Using {} authentication", auth.auth_type);
+        }
+
+        let mut callbacks = RemoteCallbacks::new();
+        if let Some(auth) = auth.as_ref().filter(|a| a.auth_type == AuthType::Ssh) {
+            let ssh = auth.ssh.clone();
+            callbacks.certificate_check(move |cert, host| check_host_key(&ssh, cert, host));
+        }
+
+        let mut tried = CredentialType::empty();
+        callbacks.credentials(move |url, username, allowed| {
+            credentials(auth.as_ref(), url, username, allowed, &mut tried)
+        });
+
+        Ok(callbacks)
+    }
+
+    fn fetch_options(&self, url: &str) -> Result<FetchOptions<'static>, DomainError> {
+        let mut options = FetchOptions::new();
+        options
+            .remote_callbacks(self.callbacks(url)?)
+            .download_tags(AutotagOption::None);
+        Ok(options)
+    }
+
+    /// Lists the references advertised by a remote with the commits they point to
+    fn list_remote(&self, url: &str) -> Result<Vec<(String, Oid)>, DomainError> {
+        let mut remote =
+            git2::Remote::create_detached(url).map_err(|e| git_error("read remote", e))?;
+        let connection = remote
+            .connect_auth(Direction::Fetch, Some(self.callbacks(url)?), None)
+            .map_err(|e| git_error("connect to remote", e))?;
+        let heads = connection
+            .list()
+            .map_err(|e| git_error("list remote references", e))?;
+
+        Ok(heads
+            .iter()
+            .map(|head| (head.name().to_string(), head.oid()))
+            .collect())
+    }
+
+    /// Creates or refreshes the cached bare repository of a remote
+    fn refresh_cache(&self, url: &str) -> Result<Repository, DomainError> {
+        let cache_path = self.cache_path(url);
+
+        let repo = if cache_path.join("HEAD").exists() {
+            info!("Refreshing cached repository: {}", cache_path.display());
+            Repository::open_bare(&cache_path)
+        } else {
+            info!(
+                "Caching repository {} in {}",
+                AuthenticationService::redact_url(url),
+                cache_path.display()
+            );
+            Repository::init_bare(&cache_path)
+        }
+        .map_err(|e| git_error("open cached repository", e))?;
+
+        {
+            let mut options = self.fetch_options(url)?;
+            options.prune(FetchPrune::On);
+            let mut remote = repo
+                .remote_anonymous(url)
+                .map_err(|e| git_error("read remote", e))?;
+            remote
+                .fetch(
+                    &["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"],
+                    Some(&mut options),
+                    None,
+                )
+                .map_err(|e| git_error("fetch repository", e))?;
+        }
+
+        Ok(repo)
+    }
+}
+
+impl RepositoryHistory for LibGitRepositoryFetcher {
+    fn log(
+        &self,
+        url: &str,
+        from: &str,
+        to: &str,
+        patterns: &[String],
+    ) -> Result<Vec<CommitSummary>, DomainError> {
+        let repo = self.refresh_cache(url)?;
+        let resolve = |revision: &str| {
+            repo.revparse_single(revision)
+                .and_then(|object| object.peel_to_commit())
+                .map(|commit| commit.id())
+                .map_err(|e| git_error(&format!("resolve revision '{}'", revision), e))
+        };
+
+        let (to, from) = (resolve(to)?, resolve(from)?);
+
+        let mut walk = repo.revwalk().map_err(|e| git_error("read history", e))?;
+        walk.set_sorting(Sort::TIME)
+            .and_then(|_| walk.push(to))
+            .and_then(|_| walk.hide(from))
+            .map_err(|e| git_error("read history", e))?;
+
+        let patterns: Vec<Pattern> = patterns
+            .iter()
+            .filter_map(|p| Pattern::new(p).ok())
+            .collect();
+
+        let mut commits = Vec::new();
+        for id in walk {
+            let commit = id
+                .and_then(|id| repo.find_commit(id))
+                .map_err(|e| git_error("read history", e))?;
+            if !touches_patterns(&repo, &commit, &patterns)
+                .map_err(|e| git_error("read history", e))?
+            {
+                continue;
+            }
+
+            commits.push(CommitSummary {
+                id: commit.id().to_string(),
+                author: commit.author().name().unwrap_or_default().to_string(),
+                date: short_date(commit.author().when()),
+                subject: commit.summary().unwrap_or_default().to_string(),
+            });
+        }
+
+        Ok(commits)
+    }
+}
+
+impl RepositoryFetcher for LibGitRepositoryFetcher {
+    fn fetch(&self, url: &str, revision: &str, temp_path: &Path) -> Result<(), DomainError> {
+        let redacted = AuthenticationService::redact_url(url);
+        debug!(
+            "Fetching repository: {} revision: {} to {}",
+            redacted,
+            revision,
+            temp_path.display()
+        );
+
+        let repo = Repository::init(temp_path).map_err(|e| git_error("create repository", e))?;
+        let mut remote = repo
+            .remote("origin", url)
+            .map_err(|e| git_error("add remote", e))?;
+
+        // Branches win over tags of the same name, as with `git clone --branch`
+        let advertised = self.list_remote(url)?;
+        let is_advertised = |name: &str| advertised.iter().any(|(head, _)| head == name);
+        let branch = format!("refs/heads/{}", revision);
+        let tag = format!("refs/tags/{}", revision);
+
+        let mut options = self.fetch_options(url)?;
+        let reference = if is_advertised(&branch) {
+            let local = format!("refs/remotes/origin/{}", revision);
+            options.depth(1);
+            remote
+                .fetch(
+                    &[format!("+{}:{}", branch, local)],
+                    Some(&mut options),
+                    None,
+                )
+                .map_err(|e| git_error("fetch repository", e))?;
+            local
+        } else if is_advertised(&tag) {
+            options.depth(1);
+            remote
+                .fetch(&[format!("+{0}:{0}", tag)], Some(&mut options), None)
+                .map_err(|e| git_error("fetch repository", e))?;
+            tag
+        } else {
+            info!("Revision is not a branch or tag, fetching the full history");
+            remote
+                .fetch(
+                    &[
+                        "+refs/heads/*:refs/remotes/origin/*",
+                        "+refs/tags/*:refs/tags/*",
+                    ],
+                    Some(&mut options),
+                    None,
+                )
+                .map_err(|e| git_error("fetch repository", e))?;
+            revision.to_string()
+        };
+
+        let commit = repo
+            .revparse_single(&reference)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| {
+                DomainError::GitError(format!(
+                    "Revision '{}' not found in {}: {}",
+                    revision,
+                    redacted,
+                    e.message()
+                ))
+            })?;
+
+        repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+            .and_then(|_| repo.set_head_detached(commit.id()))
+            .map_err(|e| git_error(&format!("check out revision '{}'", revision), e))?;
+
+        debug!("Checked out {} at {}", revision, commit.id());
+        Ok(())
+    }
+
+    fn tracks_revisions(&self, _dependency: &Dependency) -> bool {
+        true
+    }
+
+    fn keeps_upstream_paths(&self, _dependency: &Dependency) -> bool {
+        true
+    }
+
+    fn validate_dependency(&self, dependency: &Dependency) -> Vec<String> {
+        validate_git_dependency(dependency)
+    }
+
+    fn resolve_revision(&self, repo_path: &Path) -> Result<Option<String>, DomainError> {
+        let commit = Repository::open(repo_path)
+            .and_then(|repo| Ok(repo.head()?.peel_to_commit()?.id()))
+            .map_err(|e| git_error("resolve HEAD", e))?;
+        Ok(Some(commit.to_string()))
+    }
+
+    fn resolve_remote_revision(
+        &self,
+        url: &str,
+        revision: &str,
+    ) -> Result<Option<String>, DomainError> {
+        // A full commit hash cannot move
+        if revision.len() == 40 && revision.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(Some(revision.to_lowercase()));
+        }
+
+        // Match references ending in the revision like `git ls-remote`, and
+        // prefer the peeled commit of annotated tags
+        let suffix = format!("/{}", revision);
+        let mut resolved = None;
+        for (name, oid) in self.list_remote(url)? {
+            let (name, peeled) = match name.strip_suffix("^{}") {
+                Some(name) => (name, true),
+                None => (name.as_str(), false),
+            };
+            if name != revision && !name.ends_with(&suffix) {
+                continue;
+            }
+            if peeled {
+                return Ok(Some(oid.to_string()));
+            }
+            if resolved.is_none() {
+                resolved = Some(oid.to_string());
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    fn extract_paths(
+        &self,
+        repo_path: &Path,
+        patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        extract_matching(repo_path, patterns, target_path)
+    }
+}
+
+/// Credential store backed by the credential helpers in the user's git configuration
+pub struct LibGitCredentialStore;
+
+impl Default for LibGitCredentialStore {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl LibGitCredentialStore {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CredentialStore for LibGitCredentialStore {
+    fn fill(&self, url: &str) -> Result<Option<(String, String)>, DomainError> {
+        debug!(
+            "Asking credential helpers for {}",
+            AuthenticationService::redact_url(url)
+        );
+        let config = Config::open_default().map_err(|e| git_error("read git configuration", e))?;
+        Ok(CredentialHelper::new(url).config(&config).execute())
+    }
+}
+
+/// Answers a credential request, trying each credential type at most once
+///
+/// libgit2 asks again after a rejected credential, so remembering the types
+/// already tried turns bad credentials into an error instead of a loop.
+fn credentials(
+    auth: Option<&RepositoryAuth>,
+    url: &str,
+    username: Option<&str>,
+    allowed: CredentialType,
+    tried: &mut CredentialType,
+) -> Result<Cred, git2::Error> {
+    if allowed.contains(CredentialType::USERNAME) {
+        return Cred::username(username.unwrap_or("git"));
+    }
+
+    let Some(kind) = [
+        CredentialType::SSH_KEY,
+        CredentialType::USER_PASS_PLAINTEXT,
+        CredentialType::DEFAULT,
+    ]
+    .into_iter()
+    .find(|kind| allowed.contains(*kind) && !tried.contains(*kind)) else {
+        return Err(git2::Error::new(
+            ErrorCode::Auth,
+            ErrorClass::Net,
+            "no credentials left to try",
+        ));
+    };
+    tried.insert(kind);
+
+    let credentials = auth.and_then(|a| a.credentials.as_deref());
+    match kind {
+        CredentialType::SSH_KEY => {
+            let username = username.unwrap_or("git");
+            match auth.and_then(|a| a.ssh.key.as_deref()) {
+                Some(key) => Cred::ssh_key(username, None, key, None),
+                None => Cred::ssh_key_from_agent(username),
+            }
+        }
+        CredentialType::USER_PASS_PLAINTEXT => match (auth.map(|a| &a.auth_type), credentials) {
+            (Some(AuthType::HttpsToken), Some(token)) => {
+                Cred::userpass_plaintext(TOKEN_USERNAME, token)
+            }
+            (Some(AuthType::HttpsBasic), Some(credentials)) => {
+                let (username, password) = credentials.split_once(':').unwrap_or((credentials, ""));
+                Cred::userpass_plaintext(username, password)
+            }
+            _ => Cred::credential_helper(&Config::open_default()?, url, username),
+        },
+        _ => Cred::default(),
+    }
+}
+
+/// Verifies an SSH host key against the known hosts file, unless disabled
+///
+/// Both plain and hashed host entries are read, while lines with markers such
+/// as `@cert-authority` or `@revoked` are ignored.
+fn check_host_key(
+    ssh: &SshSettings,
+    cert: &Cert<'_>,
+    host: &str,
+) -> Result<CertificateCheckStatus, git2::Error> {
+    let Some(hostkey) = cert.as_hostkey() else {
+        return Ok(CertificateCheckStatus::CertificatePassthrough);
+    };
+    if !ssh.strict_host_key_checking.unwrap_or(true) {
+        return Ok(CertificateCheckStatus::CertificateOk);
+    }
+
+    let known_hosts = ssh.known_hosts.clone().or_else(|| {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".ssh").join("known_hosts"))
+    });
+    let unknown = |reason: String| {
+        Err(git2::Error::new(
+            ErrorCode::Certificate,
+            ErrorClass::Ssh,
+            reason,
+        ))
+    };
+    let Some(known_hosts) = known_hosts else {
+        return unknown(format!("no known hosts file to verify {} against", host));
+    };
+    let (Some(key), Some(key_type)) = (hostkey.hostkey(), hostkey.hostkey_type()) else {
+        return unknown(format!("{} sent no host key", host));
+    };
+
+    let key = base64::engine::general_purpose::STANDARD.encode(key);
+    let content = std::fs::read_to_string(&known_hosts).unwrap_or_default();
+    if content
+        .lines()
+        .any(|line| known_host_matches(line, host, key_type.name(), &key))
+    {
+        Ok(CertificateCheckStatus::CertificateOk)
+    } else {
+        unknown(format!(
+            "host key for {} is not in {}",
+            host,
+            known_hosts.display()
+        ))
+    }
+}
+
+/// Whether a known hosts line lists `host` with the given key
+///
+/// Hosts may be listed by name or, with `HashKnownHosts`, as
+/// `|1|<salt>|<hash>`: the HMAC-SHA1 of the name keyed with the salt.
+fn known_host_matches(line: &str, host: &str, key_type: &str, key: &str) -> bool {
+    let mut fields = line.split_whitespace();
+    let (Some(hosts), Some(line_type), Some(line_key)) =
+        (fields.next(), fields.next(), fields.next())
+    else {
+        return false;
+    };
+
+    line_type == key_type
+        && line_key == key
+        && hosts.split(',').any(|entry| {
+            if let Some(hashed) = entry.strip_prefix("|1|") {
+                return hashed_host_matches(hashed, host);
+            }
+            // Entries for non-standard ports read `[host]:port`
+            let entry = entry
+                .strip_prefix('[')
+                .and_then(|e| e.split_once("]:"))
+                .map_or(entry, |(name, _)| name);
+            entry == host
+        })
+}
+
+/// Whether a hashed known hosts entry, `<salt>|<hash>` in base64, is `host`
+fn hashed_host_matches(entry: &str, host: &str) -> bool {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let Some((salt, hash)) = entry.split_once('|') else {
+        return false;
+    };
+    let (Ok(salt), Ok(hash)) = (engine.decode(salt), engine.decode(hash)) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha1>::new_from_slice(&salt) else {
+        return false;
+    };
+    mac.update(host.as_bytes());
+    mac.verify_slice(&hash).is_ok()
+}
+
+/// Whether a commit changes a path matching the patterns
+///
+/// Patterns follow git's `:(glob)` pathspecs: `*` stops at `/`, and a pattern
+/// matching a directory matches everything below it. Merges count when they
+/// differ from every parent, as in git's default history simplification.
+fn touches_patterns(
+    repo: &Repository,
+    commit: &git2::Commit<'_>,
+    patterns: &[Pattern],
+) -> Result<bool, git2::Error> {
+    if patterns.is_empty() {
+        return Ok(true);
+    }
+
+    let tree = commit.tree()?;
+    let changed = |parent: Option<&git2::Tree<'_>>| -> Result<bool, git2::Error> {
+        let diff = repo.diff_tree_to_tree(parent, Some(&tree), None)?;
+        Ok(diff.deltas().any(|delta| {
+            [delta.old_file().path(), delta.new_file().path()]
+                .into_iter()
+                .flatten()
+                .any(|path| matches_pathspec(patterns, &path.to_string_lossy()))
+        }))
+    };
+
+    if commit.parent_count() == 0 {
+        return changed(None);
+    }
+    for parent in commit.parents() {
+        if !changed(Some(&parent.tree()?))? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn matches_pathspec(patterns: &[Pattern], path: &str) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+
+    let mut candidate = path;
+    loop {
+        if patterns
+            .iter()
+            .any(|pattern| pattern.matches_with(candidate, options))
+        {
+            return true;
+        }
+        match candidate.rfind('/') {
+            Some(index) => candidate = &candidate[..index],
+            None => return false,
+        }
+    }
+}
+
+/// Formats the date of a signature in its own time zone, like `--date=short`
+fn short_date(time: git2::Time) -> String {
+    let seconds = time.seconds() + i64::from(time.offset_minutes()) * 60;
+    let (year, month, day) = civil_date(seconds.div_euclid(86_400));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
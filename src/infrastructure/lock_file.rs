@@ -0,0 +1,155 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::repositories::LockFileRepository;
+use crate::domain::transform::Transform;
+use crate::domain::{DomainError, LockFile, LockedDependency, LockedPatch};
+use crate::infrastructure::configuration::TransformConfig;
+
+/// Current version of the lock file format
+const LOCK_FILE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LockFileToml {
+    version: u32,
+    #[serde(default)]
+    sources: Vec<LockedSourceToml>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LockedSourceToml {
+    name: String,
+    repo: String,
+    rev: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mirror: Option<String>,
+    sparse_paths: Vec<String>,
+    target: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    patches: Vec<LockedPatchToml>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    transforms: Vec<TransformConfig>,
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    options: toml::Table,
+    #[serde(default)]
+    files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LockedPatchToml {
+    path: String,
+    sha256: String,
+}
+
+pub struct TomlLockFileRepository;
+
+impl Default for TomlLockFileRepository {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl TomlLockFileRepository {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl LockFileRepository for TomlLockFileRepository {
+    fn load(&self, path: &Path) -> Result<LockFile, DomainError> {
+        if !path.exists() {
+            return Ok(LockFile::default());
+        }
+
+        let contents = fs::read_to_string(path).map_err(|e| {
+            DomainError::ConfigurationError(format!("Failed to read lock file: {}", e))
+        })?;
+
+        let lock_file: LockFileToml = toml::from_str(&contents).map_err(|e| {
+            DomainError::ConfigurationError(format!("Failed to parse lock file: {}", e))
+        })?;
+
+        if lock_file.version > LOCK_FILE_VERSION {
+            return Err(DomainError::ConfigurationError(format!(
+                "Unsupported lock file version: {}",
+                lock_file.version
+            )));
+        }
+
+        Ok(LockFile {
+            dependencies: lock_file
+                .sources
+                .into_iter()
+                .map(|source| LockedDependency {
+                    name: source.name,
+                    repository_url: source.repo,
+                    revision: source.rev,
+                    commit: source.commit,
+                    mirror: source.mirror,
+                    sparse_paths: source.sparse_paths,
+                    target_location: PathBuf::from(source.target),
+                    patches: source
+                        .patches
+                        .into_iter()
+                        .map(|patch| LockedPatch {
+                            path: patch.path,
+                            sha256: patch.sha256,
+                        })
+                        .collect(),
+                    transforms: source.transforms.into_iter().map(Transform::from).collect(),
+                    options: source.options,
+                    files: source.files,
+                })
+                .collect(),
+        })
+    }
+
+    fn save(&self, lock: &LockFile, path: &Path) -> Result<(), DomainError> {
+        let lock_file = LockFileToml {
+            version: LOCK_FILE_VERSION,
+            sources: lock
+                .dependencies
+                .iter()
+                .map(|dep| LockedSourceToml {
+                    name: dep.name.clone(),
+                    repo: dep.repository_url.clone(),
+                    rev: dep.revision.clone(),
+                    commit: dep.commit.clone(),
+                    mirror: dep.mirror.clone(),
+                    sparse_paths: dep.sparse_paths.clone(),
+                    target: dep.target_location.to_string_lossy().to_string(),
+                    patches: dep
+                        .patches
+                        .iter()
+                        .map(|patch| LockedPatchToml {
+                            path: patch.path.clone(),
+                            sha256: patch.sha256.clone(),
+                        })
+                        .collect(),
+                    transforms: dep.transforms.iter().map(TransformConfig::from).collect(),
+                    options: dep.options.clone(),
+                    files: dep.files.clone(),
+                })
+                .collect(),
+        };
+
+        let toml_string = toml::to_string_pretty(&lock_file).map_err(|e| {
+            DomainError::ConfigurationError(format!("Failed to serialize lock file: {}", e))
+        })?;
+
+        let contents = format!(
+            "# This file is generated by acdm. Do not edit it by hand.\n{}",
+            toml_string
+        );
+
+        fs::write(path, contents).map_err(|e| {
+            DomainError::ConfigurationError(format!("Failed to write lock file: {}", e))
+        })
+    }
+}
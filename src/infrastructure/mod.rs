@@ -1,5 +1,13 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
+pub mod archive;
 pub mod configuration;
 pub mod file_system;
 pub mod git;
+pub mod git_common;
+pub mod http;
+pub mod libgit2;
+pub mod lock_file;
+pub mod path;
+pub mod plugin;
+pub mod source;
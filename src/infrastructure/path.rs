@@ -0,0 +1,97 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use log::debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::domain::repositories::RepositoryFetcher;
+use crate::domain::DomainError;
+use crate::infrastructure::file_system::extract_matching;
+
+/// Fetcher copying sources from a local directory
+///
+/// Relative paths are resolved against the directory holding the
+/// configuration file. Version control metadata is never copied.
+pub struct LocalPathFetcher {
+    base_dir: PathBuf,
+}
+
+impl LocalPathFetcher {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self { base_dir }
+    }
+
+    /// Resolves a configured path, accepting `file://` URLs as well
+    fn source_path(&self, url: &str) -> PathBuf {
+        let path = Path::new(url.strip_prefix("file://").unwrap_or(url));
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.base_dir.join(path)
+        }
+    }
+}
+
+impl RepositoryFetcher for LocalPathFetcher {
+    fn fetch(&self, url: &str, _revision: &str, temp_path: &Path) -> Result<(), DomainError> {
+        let source = self.source_path(url);
+        debug!(
+            "Copying local directory {} to {}",
+            source.display(),
+            temp_path.display()
+        );
+
+        if !source.is_dir() {
+            return Err(DomainError::RepositoryError(format!(
+                "Source directory does not exist: {}",
+                source.display()
+            )));
+        }
+
+        let entries = WalkDir::new(&source)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|e| e.file_name() != ".git");
+
+        for entry in entries {
+            let entry = entry.map_err(|e| {
+                DomainError::FileSystemError(format!("Failed to walk directory: {}", e))
+            })?;
+            let relative = entry.path().strip_prefix(&source).map_err(|e| {
+                DomainError::FileSystemError(format!("Failed to strip prefix: {}", e))
+            })?;
+            let destination = temp_path.join(relative);
+
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&destination).map_err(|e| {
+                    DomainError::FileSystemError(format!("Failed to create directory: {}", e))
+                })?;
+            } else {
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent).map_err(|e| {
+                        DomainError::FileSystemError(format!("Failed to create directory: {}", e))
+                    })?;
+                }
+                fs::copy(entry.path(), &destination).map_err(|e| {
+                    DomainError::FileSystemError(format!(
+                        "Failed to copy {}: {}",
+                        entry.path().display(),
+                        e
+                    ))
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn extract_paths(
+        &self,
+        repo_path: &Path,
+        patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        extract_matching(repo_path, patterns, target_path)
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::domain::repositories::RepositoryFetcher;
+use crate::domain::{Dependency, DomainError, SourceOptions};
+use crate::infrastructure::file_system::extract_matching;
+
+/// Prefix of the executables providing source types
+const PLUGIN_PREFIX: &str = "acdm-source-";
+
+/// Version of the JSON protocol spoken with plugins
+const PROTOCOL_VERSION: u32 = 1;
+
+/// Time a plugin may take before it is killed, unless `ACDM_PLUGIN_TIMEOUT`
+/// sets another number of seconds
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Request written to a plugin's stdin
+#[derive(Debug, Serialize)]
+struct PluginRequest<'a> {
+    protocol: u32,
+    name: &'a str,
+    url: &'a str,
+    revision: &'a str,
+    destination: &'a Path,
+    options: &'a SourceOptions,
+}
+
+/// Result read from a plugin's stdout
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    /// Resolved version, recorded in the lock file like a git commit
+    #[serde(default)]
+    version: Option<String>,
+
+    /// SHA-256 of files written below the destination, by relative path
+    #[serde(default)]
+    files: BTreeMap<String, String>,
+}
+
+/// Fetcher delegating to an external `acdm-source-<type>` executable
+///
+/// The plugin receives a JSON request with the source's URL, revision,
+/// options and a destination directory on stdin, writes the content to the
+/// destination and prints a JSON result with the resolved version and the
+/// hashes of the files it wrote. acdm verifies those hashes.
+pub struct PluginFetcher {
+    program: PathBuf,
+    timeout: Duration,
+}
+
+impl PluginFetcher {
+    pub fn new(program: PathBuf) -> Self {
+        let timeout = std::env::var("ACDM_PLUGIN_TIMEOUT")
+            .ok()
+            .and_then(|seconds| seconds.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_TIMEOUT);
+        Self { program, timeout }
+    }
+
+    fn run(
+        &self,
+        name: &str,
+        url: &str,
+        revision: &str,
+        options: &SourceOptions,
+        destination: &Path,
+    ) -> Result<PluginResponse, DomainError> {
+        let plugin_error = |message: String| {
+            DomainError::RepositoryError(format!(
+                "Source plugin {} failed: {}",
+                self.program.display(),
+                message
+            ))
+        };
+
+        let request = serde_json::to_vec(&PluginRequest {
+            protocol: PROTOCOL_VERSION,
+            name,
+            url,
+            revision,
+            destination,
+            options,
+        })
+        .map_err(|e| plugin_error(format!("cannot encode request: {}", e)))?;
+
+        info!("Running source plugin {}", self.program.display());
+        let mut child = Command::new(&self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| plugin_error(e.to_string()))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // A plugin may exit without reading its request
+            thread::spawn(move || {
+                let _ = stdin.write_all(&request);
+            });
+        }
+        let stdout = child.stdout.take().map(read_all);
+        let stderr = child.stderr.take().map(read_all);
+
+        let deadline = Instant::now() + self.timeout;
+        let timed_out = || {
+            plugin_error(format!(
+                "timed out after {} seconds",
+                self.timeout.as_secs()
+            ))
+        };
+        let status = loop {
+            match child.try_wait().map_err(|e| plugin_error(e.to_string()))? {
+                Some(status) => break status,
+                None if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(timed_out());
+                }
+                None => thread::sleep(Duration::from_millis(20)),
+            }
+        };
+
+        // Children the plugin left running may still hold its pipes open, so
+        // its output is only awaited until the deadline. The readers of
+        // pipes that stay open are left behind.
+        let collect = |reader: Option<Receiver<Vec<u8>>>| match reader {
+            Some(reader) => reader
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .map_err(|_| timed_out()),
+            None => Ok(Vec::new()),
+        };
+        let output = std::process::Output {
+            status,
+            stdout: collect(stdout)?,
+            stderr: collect(stderr)?,
+        };
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if !output.status.success() {
+            return Err(plugin_error(format!(
+                "exited with {}: {}",
+                output.status,
+                stderr.trim()
+            )));
+        }
+        if !stderr.trim().is_empty() {
+            debug!("Source plugin output: {}", stderr.trim());
+        }
+
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| plugin_error(format!("invalid result: {}", e)))
+    }
+}
+
+impl RepositoryFetcher for PluginFetcher {
+    fn fetch(&self, url: &str, revision: &str, temp_path: &Path) -> Result<(), DomainError> {
+        self.run("", url, revision, &SourceOptions::new(), temp_path)
+            .map(|_| ())
+    }
+
+    fn extract_paths(
+        &self,
+        repo_path: &Path,
+        patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        extract_matching(repo_path, patterns, target_path)
+    }
+
+    fn fetch_dependency(
+        &self,
+        dependency: &Dependency,
+        url: &str,
+        temp_path: &Path,
+    ) -> Result<Option<String>, DomainError> {
+        let response = self.run(
+            &dependency.name,
+            url,
+            &dependency.revision,
+            &dependency.options,
+            temp_path,
+        )?;
+
+        for (path, expected) in &response.files {
+            let relative = Path::new(path);
+            if path.is_empty()
+                || !relative
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+            {
+                return Err(DomainError::RepositoryError(format!(
+                    "Source plugin {} reported {}, which is outside its destination",
+                    self.program.display(),
+                    path
+                )));
+            }
+            let content = fs::read(temp_path.join(path)).map_err(|e| {
+                DomainError::RepositoryError(format!(
+                    "Source plugin {} reported {} but it cannot be read: {}",
+                    self.program.display(),
+                    path,
+                    e
+                ))
+            })?;
+            let actual = hex::encode(Sha256::digest(&content));
+            if !actual.eq_ignore_ascii_case(expected) {
+                return Err(DomainError::RepositoryError(format!(
+                    "Checksum mismatch for {} from source plugin {}: expected sha256 {}, got {}",
+                    path,
+                    self.program.display(),
+                    expected,
+                    actual
+                )));
+            }
+        }
+
+        Ok(response.version)
+    }
+
+    fn validate_dependency(&self, _dependency: &Dependency) -> Vec<String> {
+        // Options belong to the plugin, which reports problems when run
+        Vec::new()
+    }
+}
+
+/// Reads a pipe to its end on a separate thread, which sends the content
+fn read_all(mut pipe: impl Read + Send + 'static) -> Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        let _ = sender.send(buffer);
+    });
+    receiver
+}
+
+/// Finds `acdm-source-<type>` executables on `PATH`, keyed by type
+///
+/// The first executable of a type in `PATH` order wins, as with commands.
+pub fn discover_plugins() -> BTreeMap<String, PathBuf> {
+    let mut plugins = BTreeMap::new();
+    let Some(path) = std::env::var_os("PATH") else {
+        return plugins;
+    };
+
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            let Some(source_type) = file_name.strip_prefix(PLUGIN_PREFIX) else {
+                continue;
+            };
+            let source_type = source_type
+                .strip_suffix(std::env::consts::EXE_SUFFIX)
+                .filter(|_| !std::env::consts::EXE_SUFFIX.is_empty())
+                .unwrap_or(source_type);
+
+            if source_type.is_empty() || !is_executable(&entry.path()) {
+                continue;
+            }
+            plugins
+                .entry(source_type.to_string())
+                .or_insert_with(|| entry.path());
+        }
+    }
+
+    plugins
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
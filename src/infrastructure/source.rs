@@ -0,0 +1,90 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+
+use crate::domain::auth::AuthenticationService;
+use crate::domain::repositories::RepositoryFetcher;
+use crate::domain::sources::SourceRegistry;
+use crate::domain::url::RepositoryUrl;
+use crate::domain::{Dependency, DomainError};
+use crate::infrastructure::archive::ArchiveFetcher;
+use crate::infrastructure::git::{GitBackend, GitRepositoryFetcher};
+use crate::infrastructure::http::HttpFileFetcher;
+use crate::infrastructure::libgit2::LibGitRepositoryFetcher;
+use crate::infrastructure::path::LocalPathFetcher;
+use crate::infrastructure::plugin::{discover_plugins, PluginFetcher};
+
+/// Registers the source types built into acdm and the plugins found on `PATH`
+///
+/// `base_dir` is the directory holding the configuration file, against which
+/// local sources are resolved. New backends are added here; built-in types
+/// take precedence over plugins of the same name, and `PATH` is only searched
+/// for plugins once a type that is not built in is needed. Git sources use the backend
+/// selected by `ACDM_GIT_BACKEND`.
+pub fn available_sources(base_dir: PathBuf, auth_service: AuthenticationService) -> SourceRegistry {
+    let registry = SourceRegistry::new().with_discovery(|| {
+        discover_plugins()
+            .into_iter()
+            .map(|(source_type, program)| {
+                let backend: Box<dyn RepositoryFetcher> = Box::new(PluginFetcher::new(program));
+                (source_type, backend)
+            })
+            .collect()
+    });
+
+    let registry = match GitBackend::from_env() {
+        GitBackend::Cli => registry
+            .with_backend(
+                "git",
+                GitRepositoryFetcher::new().with_auth_service(auth_service.clone()),
+            )
+            .with_history(GitRepositoryFetcher::new().with_auth_service(auth_service.clone())),
+        GitBackend::Libgit2 => registry
+            .with_backend(
+                "git",
+                LibGitRepositoryFetcher::new().with_auth_service(auth_service.clone()),
+            )
+            .with_history(LibGitRepositoryFetcher::new().with_auth_service(auth_service.clone())),
+    };
+
+    registry
+        .with_backend("path", LocalPathFetcher::new(base_dir.clone()))
+        .with_backend(
+            "archive",
+            ArchiveFetcher::new(base_dir).with_auth_service(auth_service.clone()),
+        )
+        .with_backend(
+            "http",
+            HttpFileFetcher::new().with_auth_service(auth_service),
+        )
+}
+
+/// Parses the options block of a dependency into a backend's option type
+///
+/// Errors name the source type and are suitable as validation messages.
+pub fn parse_options<T: DeserializeOwned>(dependency: &Dependency) -> Result<T, String> {
+    toml::Value::Table(dependency.options.clone())
+        .try_into()
+        .map_err(|e: toml::de::Error| {
+            format!(
+                "invalid {} options: {}",
+                dependency.repository_type,
+                e.message()
+            )
+        })
+}
+
+/// Checks the `repo` of a source fetched from a URL, returning the problem
+///
+/// An empty URL is already reported for every source type.
+pub fn validate_url(dependency: &Dependency) -> Option<String> {
+    if dependency.repository_url.trim().is_empty() {
+        return None;
+    }
+    match RepositoryUrl::parse(&dependency.repository_url) {
+        Ok(_) => None,
+        Err(DomainError::ConfigurationError(message)) => Some(message),
+        Err(e) => Some(e.to_string()),
+    }
+}
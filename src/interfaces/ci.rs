@@ -0,0 +1,92 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use thiserror::Error;
+
+use crate::application::dto::CheckReportDto;
+
+/// Exit code when the configuration cannot be loaded or is invalid
+pub const EXIT_CONFIG_INVALID: i32 = 3;
+
+/// Exit code when the lock file does not match the configuration
+pub const EXIT_LOCK_INCONSISTENT: i32 = 4;
+
+/// Exit code when vendored files do not match the lock file
+pub const EXIT_INTEGRITY_FAILED: i32 = 5;
+
+/// Exit code when a configured revision moved upstream
+pub const EXIT_UPSTREAM_MOVED: i32 = 6;
+
+/// Error carrying the process exit code for a failed check
+#[derive(Error, Debug)]
+#[error("{summary}")]
+pub struct CheckFailed {
+    pub exit_code: i32,
+    pub summary: String,
+}
+
+/// Picks the exit code of the most severe failure class in a report
+pub fn exit_code_for(report: &CheckReportDto) -> i32 {
+    let failed = |class: &str| report.checks.iter().any(|c| !c.passed && c.class == class);
+
+    [
+        ("config", EXIT_CONFIG_INVALID),
+        ("lock", EXIT_LOCK_INCONSISTENT),
+        ("integrity", EXIT_INTEGRITY_FAILED),
+        ("upstream", EXIT_UPSTREAM_MOVED),
+    ]
+    .iter()
+    .find(|(class, _)| failed(class))
+    .map(|(_, code)| *code)
+    .unwrap_or(0)
+}
+
+/// Renders a check report as a JUnit XML test suite
+pub fn junit_xml(report: &CheckReportDto) -> String {
+    let failures = report.checks.iter().filter(|c| !c.passed).count();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"acdm\" tests=\"{}\" failures=\"{}\">\n",
+        report.checks.len(),
+        failures
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"acdm check\" tests=\"{}\" failures=\"{}\">\n",
+        report.checks.len(),
+        failures
+    ));
+
+    for check in &report.checks {
+        let classname = format!("acdm.{}", check.class);
+        if check.passed {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\"/>\n",
+                escape(&classname),
+                escape(&check.name)
+            ));
+        } else {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\">\n",
+                escape(&classname),
+                escape(&check.name)
+            ));
+            xml.push_str(&format!(
+                "      <failure type=\"{}\" message=\"{}\"/>\n",
+                escape(&check.class),
+                escape(&check.message)
+            ));
+            xml.push_str("    </testcase>\n");
+        }
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -1,29 +1,139 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
-use crate::domain::repositories::{ConfigurationRepository, GitOperations};
+use crate::domain::repositories::{
+    ConfigurationRepository, FileSystemManager, GitOperations, LockFileRepository,
+    RepositoryFetcher,
+};
 use anyhow::{anyhow, Context, Result};
 use log::{debug, info, warn};
+use std::collections::BTreeMap;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::application::dto::{
-    AddDependencyDto, IncludePathsDto, InitConfigDto, UpdateDependenciesDto,
+    AddDependencyDto, CreatePatchDto, DependencyStatusDto, DiffDependenciesDto, ExportChangesDto,
+    IncludePathsDto, InitConfigDto, LogDependencyDto, RemoveDependencyDto, UpdateDependenciesDto,
+    UpdateEventDto, UpdatedDependencyDto,
 };
+use crate::application::status::GetDependencyStatusQuery;
 use crate::application::use_cases::{
-    AddDependencyUseCase, IncludePathsUseCase, InitConfigUseCase, UpdateDependenciesUseCase,
+    AddDependencyUseCase, CreatePatchUseCase, DiffDependenciesUseCase, ExportChangesUseCase,
+    IncludePathsUseCase, InitConfigUseCase, LogDependencyUseCase, RemoveDependencyUseCase,
+    UpdateDependenciesUseCase,
 };
+use crate::domain::auth::AuthenticationService;
+use crate::domain::commit::{CommitMessage, SourceChange};
+use crate::domain::diff::ChangeKind;
+use crate::domain::gitattributes::GITATTRIBUTES_FILE;
+use crate::domain::merge::CONFLICT_START;
+use crate::domain::mirrors::Mirrors;
+use crate::domain::sources::SourceRegistry;
+use crate::domain::{CleanPolicy, Configuration, LocalChanges, LockFile};
 use crate::infrastructure::configuration::TomlConfigurationRepository;
 use crate::infrastructure::file_system::FileSystemManagerImpl;
-use crate::infrastructure::git::{GitOperationsImpl, GitRepositoryFetcher};
+use crate::infrastructure::git::GitBackend;
+use crate::infrastructure::lock_file::TomlLockFileRepository;
+use crate::infrastructure::source::available_sources;
+use crate::interfaces::ci::{exit_code_for, junit_xml, CheckFailed};
+use crate::interfaces::output::{print_event, print_json, OutputFormat};
+
+/// Safety checks skipped by a command that modifies the working tree
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SafetyOverrides {
+    /// Skip the check for uncommitted changes (`--allow-dirty`)
+    pub allow_dirty: bool,
+
+    /// Answer yes to confirmation prompts (`--yes`)
+    pub assume_yes: bool,
+}
+
+impl SafetyOverrides {
+    /// Overrides from the global flags; `--force` implies both
+    pub fn from_flags(force: bool, allow_dirty: bool, yes: bool) -> Self {
+        Self {
+            allow_dirty: force || allow_dirty,
+            assume_yes: force || yes,
+        }
+    }
+}
+
+/// Message and target paths of a commit recording updated dependencies
+fn update_commit(
+    updated: &[UpdatedDependencyDto],
+    old_commits: &BTreeMap<String, Option<String>>,
+) -> (CommitMessage, Vec<PathBuf>) {
+    let subject = match updated {
+        [single] => format!("Update dependency {}", single.name),
+        _ => "Update dependencies".to_string(),
+    };
+    let mut message = CommitMessage::new(subject);
+    let mut targets = Vec::new();
+    for dep in updated {
+        targets.push(PathBuf::from(&dep.target_location));
+        message = message.with_change(SourceChange {
+            name: dep.name.clone(),
+            repository_url: dep.repository_url.clone(),
+            revision: dep.revision.clone(),
+            old_commit: old_commits.get(&dep.name).cloned().flatten(),
+            new_commit: dep.commit.clone(),
+        });
+    }
+    (message, targets)
+}
+
+/// Fail when an update left conflicts between local and upstream changes
+///
+/// The lock file is already saved, so the conflicted files only need to be
+/// resolved and committed.
+fn ensure_no_conflicts(updated: &[UpdatedDependencyDto]) -> Result<()> {
+    let mut conflicts = Vec::new();
+    for dep in updated {
+        let target = Path::new(&dep.target_location);
+        for path in &dep.kept_local {
+            info!(
+                "Kept local modifications of {}",
+                target.join(path).display()
+            );
+        }
+        conflicts.extend(
+            dep.conflicts
+                .iter()
+                .map(|path| target.join(path).display().to_string()),
+        );
+    }
+
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "Local modifications conflict with upstream changes in:\n  {}\n\nResolve the conflicts, marked with '{}' in text files, then review and commit the result",
+        conflicts.join("\n  "),
+        CONFLICT_START
+    ))
+}
 
 /// Adapter for the CLI interface
 pub struct CliAdapter {
     config_path: PathBuf,
+    output_format: OutputFormat,
 }
 
 impl CliAdapter {
     pub fn new(config_path: PathBuf) -> Self {
-        Self { config_path }
+        Self {
+            config_path,
+            output_format: OutputFormat::Text,
+        }
+    }
+
+    /// Set the format used to report command results
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    fn json(&self) -> bool {
+        self.output_format == OutputFormat::Json
     }
 
     /// Initialize a new configuration
@@ -53,49 +163,49 @@ impl CliAdapter {
     }
 
     /// Add a new dependency
+    #[allow(clippy::too_many_arguments)]
     pub fn add_dependency(
         &self,
         name: String,
         repository_url: String,
-        revision: String,
+        revision: Option<String>,
         target_location: String,
-        force: bool,
+        repository_type: String,
+        options: Vec<String>,
+        overrides: SafetyOverrides,
+        commit: bool,
     ) -> Result<()> {
         debug!(
-            "Adding dependency: name={}, url={}, rev={}, target={}",
-            name, repository_url, revision, target_location
+            "Adding dependency: name={}, url={}, rev={:?}, target={}, type={}, options={:?}",
+            name,
+            AuthenticationService::redact_url(&repository_url),
+            revision,
+            target_location,
+            repository_type,
+            options
         );
 
         // Create Git operations and verify clean status
-        let git_operations = GitOperationsImpl::new();
-
-        // Get the repository root - if we have a parent directory, use it, otherwise use the current directory
-        let repo_root = if let Some(parent) = self.config_path.parent() {
-            // If parent is empty, use current directory
-            if parent.as_os_str().is_empty() {
-                std::env::current_dir()
-                    .map_err(|e| anyhow!("Failed to get current directory: {}", e))?
-            } else {
-                parent.to_path_buf()
-            }
-        } else {
-            // No parent means the config is in the current directory
-            std::env::current_dir()
-                .map_err(|e| anyhow!("Failed to get current directory: {}", e))?
-        };
+        let git_operations = GitBackend::from_env().operations();
+
+        let repo_root = self.repo_root()?;
 
         debug!("Using repository root path: {}", repo_root.display());
 
-        // Skip this check if force is enabled
-        if !force {
-            if let Err(e) = self.ensure_clean_git_status(&git_operations, &repo_root) {
+        // Skip this check if dirty trees are allowed
+        if !overrides.allow_dirty {
+            let targets = [PathBuf::from(&target_location)];
+            if let Err(e) = self.ensure_clean_git_status(&git_operations, &repo_root, &targets) {
                 warn!("Git repository status is not clean: {}", e);
                 return Err(e);
             }
         }
+        if commit {
+            self.ensure_can_commit(&git_operations, &repo_root)?;
+        }
 
         let config_repo = TomlConfigurationRepository::new();
-        let use_case = AddDependencyUseCase::new(config_repo);
+        let use_case = AddDependencyUseCase::new(config_repo, self.repository_fetcher()?);
 
         use_case
             .execute(
@@ -104,15 +214,36 @@ impl CliAdapter {
                     name: name.clone(),
                     repository_url,
                     revision,
-                    repository_type: "git".to_string(),
+                    repository_type,
                     target_location,
+                    options,
                 },
             )
             .context("Failed to add dependency")?;
 
-        info!("Remember to commit your changes manually with 'git add . && git commit -m \"Add dependency {name}\"'");
+        if !commit {
+            info!("Remember to commit your changes manually with 'git add . && git commit -m \"Add dependency {name}\"'");
+            return Ok(());
+        }
 
-        Ok(())
+        let mut message = CommitMessage::new(format!("Add dependency {}", name));
+        let mut targets = Vec::new();
+        if let Some(dep) = self
+            .load_config()?
+            .dependencies
+            .into_iter()
+            .find(|d| d.name == name)
+        {
+            targets.push(dep.target_location.clone());
+            message = message.with_change(SourceChange {
+                name: dep.name,
+                repository_url: dep.repository_url,
+                revision: dep.revision,
+                old_commit: None,
+                new_commit: None,
+            });
+        }
+        self.commit_changes(&git_operations, &repo_root, &message, targets)
     }
 
     /// Include paths in a dependency
@@ -120,7 +251,8 @@ impl CliAdapter {
         &self,
         dependency_name: String,
         paths: Vec<String>,
-        force: bool,
+        overrides: SafetyOverrides,
+        commit: bool,
     ) -> Result<()> {
         debug!(
             "Including paths for dependency: {}, paths: {:?}",
@@ -128,33 +260,24 @@ impl CliAdapter {
         );
 
         // Create Git operations and verify clean status
-        let git_operations = GitOperationsImpl::new();
-
-        // Get the repository root - if we have a parent directory, use it, otherwise use the current directory
-        let repo_root = if let Some(parent) = self.config_path.parent() {
-            // If parent is empty, use current directory
-            if parent.as_os_str().is_empty() {
-                std::env::current_dir()
-                    .map_err(|e| anyhow!("Failed to get current directory: {}", e))?
-            } else {
-                parent.to_path_buf()
-            }
-        } else {
-            // No parent means the config is in the current directory
-            std::env::current_dir()
-                .map_err(|e| anyhow!("Failed to get current directory: {}", e))?
-        };
+        let git_operations = GitBackend::from_env().operations();
+
+        let repo_root = self.repo_root()?;
 
         debug!("Using repository root path: {}", repo_root.display());
 
-        // Skip this check if force is enabled
-        if !force {
-            if let Err(e) = self.ensure_clean_git_status(&git_operations, &repo_root) {
+        // Skip this check if dirty trees are allowed
+        if !overrides.allow_dirty {
+            if let Err(e) = self.ensure_clean_git_status(&git_operations, &repo_root, &[]) {
                 warn!("Git repository status is not clean: {}", e);
                 return Err(e);
             }
         }
 
+        if commit {
+            self.ensure_can_commit(&git_operations, &repo_root)?;
+        }
+
         let config_repo = TomlConfigurationRepository::new();
         let use_case = IncludePathsUseCase::new(config_repo);
 
@@ -168,51 +291,63 @@ impl CliAdapter {
             )
             .context("Failed to include paths")?;
 
-        info!("Remember to commit your changes manually with 'git add . && git commit -m \"Include paths for {dependency_name}\"'");
+        if !commit {
+            info!("Remember to commit your changes manually with 'git add . && git commit -m \"Include paths for {dependency_name}\"'");
+            return Ok(());
+        }
 
-        Ok(())
+        // The paths take effect on the next update, so the vendored commit stays
+        let mut message = CommitMessage::new(format!("Include paths for {}", dependency_name));
+        if let Some(dep) = self
+            .load_config()?
+            .dependencies
+            .into_iter()
+            .find(|d| d.name == dependency_name)
+        {
+            let locked = self.locked_commit(&dep.name);
+            message = message.with_change(SourceChange {
+                name: dep.name,
+                repository_url: dep.repository_url,
+                revision: dep.revision,
+                old_commit: locked.clone(),
+                new_commit: locked,
+            });
+        }
+        self.commit_changes(&git_operations, &repo_root, &message, Vec::new())
     }
 
     /// Update dependencies
     pub fn update_dependencies(
         &self,
         dependencies: Option<Vec<String>>,
-        force: bool,
+        overrides: SafetyOverrides,
+        dry_run: bool,
+        commit: bool,
+        branch: Option<String>,
+        local_changes: LocalChanges,
     ) -> Result<()> {
         debug!(
-            "Updating dependencies: {:?}, force: {}",
-            dependencies, force
+            "Updating dependencies: {:?}, overrides: {:?}, dry run: {}, commit: {}, branch: {:?}, local changes: {:?}",
+            dependencies, overrides, dry_run, commit, branch, local_changes
         );
 
+        // A dry run only previews the changes and never touches the working tree
+        if dry_run {
+            return self.diff_dependencies(dependencies, false);
+        }
+
         // Create required components
         let config_repo = TomlConfigurationRepository::new();
-        let repository_fetcher = GitRepositoryFetcher::new();
+        let repository_fetcher = self.repository_fetcher()?;
         let file_system_manager = FileSystemManagerImpl::new();
-        let git_operations = GitOperationsImpl::new();
-
-        // Get the repository root - if we have a parent directory, use it, otherwise use the current directory
-        let repo_root = if let Some(parent) = self.config_path.parent() {
-            // If parent is empty, use current directory
-            if parent.as_os_str().is_empty() {
-                std::env::current_dir()
-                    .map_err(|e| anyhow!("Failed to get current directory: {}", e))?
-            } else {
-                parent.to_path_buf()
-            }
-        } else {
-            // No parent means the config is in the current directory
-            std::env::current_dir()
-                .map_err(|e| anyhow!("Failed to get current directory: {}", e))?
-        };
+        let git_operations = GitBackend::from_env().operations();
+
+        let repo_root = self.repo_root()?;
 
         debug!("Using repository root path: {}", repo_root.display());
 
-        // Verify Git status if not in force mode
-        if !force {
-            if let Err(e) = self.ensure_clean_git_status(&git_operations, &repo_root) {
-                warn!("Git repository status is not clean: {}", e);
-                return Err(e);
-            }
+        if commit || branch.is_some() {
+            self.ensure_can_commit(&git_operations, &repo_root)?;
         }
 
         // Load configuration and determine what will be updated
@@ -241,8 +376,20 @@ impl CliAdapter {
             config.dependencies.iter().collect()
         };
 
+        // Verify Git status of the paths the update touches
+        if !overrides.allow_dirty {
+            let targets: Vec<PathBuf> = dependencies_to_update
+                .iter()
+                .map(|d| d.target_location.clone())
+                .collect();
+            if let Err(e) = self.ensure_clean_git_status(&git_operations, &repo_root, &targets) {
+                warn!("Git repository status is not clean: {}", e);
+                return Err(e);
+            }
+        }
+
         // Show warning about what mount points will be purged
-        if !force {
+        if !overrides.assume_yes {
             info!("The following mount points will be purged:");
             for dep in dependencies_to_update.iter() {
                 info!("  - {}", dep.target_location.display());
@@ -250,73 +397,507 @@ impl CliAdapter {
 
             if !self.prompt_yes_no("Do you want to continue with the update?")? {
                 info!("Update canceled by user");
+                if self.json() {
+                    print_event("update", &UpdateEventDto::Cancelled)?;
+                }
                 return Ok(());
             }
         }
 
+        // Commits vendored before the update, for the commit message
+        let old_commits: BTreeMap<String, Option<String>> = config
+            .dependencies
+            .iter()
+            .map(|d| (d.name.clone(), self.locked_commit(&d.name)))
+            .collect();
+
         // Run the update
         let use_case = UpdateDependenciesUseCase::new(
             config_repo,
+            TomlLockFileRepository::new(),
             repository_fetcher,
             file_system_manager,
-            git_operations,
-        );
+            GitBackend::from_env().operations(),
+        )
+        .with_mirrors(self.mirrors()?);
 
-        debug!("Executing update dependencies use case");
-        use_case
-            .execute(UpdateDependenciesDto {
+        // On a branch of its own, each dependency is updated and committed in turn
+        if let Some(branch) = branch {
+            // Conflicts found halfway would leave the branch half done
+            use_case.check_conflicts(&UpdateDependenciesDto {
                 config_path: self.config_path.clone(),
                 dependencies: dependencies.clone(),
-                force,
+                local_changes,
+                refuse_conflicts: true,
+            })?;
+
+            git_operations
+                .create_branch(&repo_root, &branch)
+                .context("Failed to create branch")?;
+            info!("Switched to a new branch '{}'", branch);
+
+            let mut updated = Vec::new();
+            for dep in &dependencies_to_update {
+                let dependency_updated = self
+                    .run_update(&use_case, Some(vec![dep.name.clone()]), local_changes, true)
+                    .and_then(|dependency_updated| {
+                        ensure_no_conflicts(&dependency_updated)?;
+                        let (message, targets) = update_commit(&dependency_updated, &old_commits);
+                        self.commit_changes(&git_operations, &repo_root, &message, targets)?;
+                        Ok(dependency_updated)
+                    });
+
+                // The branch keeps what was committed, so say how far it got
+                match dependency_updated {
+                    Ok(dependency_updated) => updated.extend(dependency_updated),
+                    Err(e) => {
+                        let committed: Vec<&str> =
+                            updated.iter().map(|d| d.name.as_str()).collect();
+                        return Err(e.context(format!(
+                            "Stopped at '{}' on branch '{}', which has commits for {}",
+                            dep.name,
+                            branch,
+                            if committed.is_empty() {
+                                "no sources".to_string()
+                            } else {
+                                committed.join(", ")
+                            }
+                        )));
+                    }
+                }
+            }
+            self.finish_update(updated)?;
+
+            info!("Review the commits on '{}' and merge it when ready", branch);
+            return Ok(());
+        }
+
+        let updated = self.run_update(&use_case, dependencies, local_changes, commit)?;
+        self.finish_update(updated.clone())?;
+        ensure_no_conflicts(&updated)?;
+
+        if !commit {
+            info!("Remember to commit your changes manually with 'git add . && git commit -m \"Update dependencies\"'");
+            return Ok(());
+        }
+
+        let (message, targets) = update_commit(&updated, &old_commits);
+        self.commit_changes(&git_operations, &repo_root, &message, targets)
+    }
+
+    /// Run an update, streaming progress events in JSON mode
+    fn run_update<C, L, R, F, G>(
+        &self,
+        use_case: &UpdateDependenciesUseCase<C, L, R, F, G>,
+        dependencies: Option<Vec<String>>,
+        local_changes: LocalChanges,
+        refuse_conflicts: bool,
+    ) -> Result<Vec<UpdatedDependencyDto>>
+    where
+        C: ConfigurationRepository,
+        L: LockFileRepository,
+        R: RepositoryFetcher,
+        F: FileSystemManager,
+        G: GitOperations,
+    {
+        debug!("Executing update dependencies use case");
+        let dto = UpdateDependenciesDto {
+            config_path: self.config_path.clone(),
+            dependencies,
+            local_changes,
+            refuse_conflicts,
+        };
+
+        if !self.json() {
+            return use_case
+                .execute(dto)
+                .context("Failed to update dependencies");
+        }
+
+        // Stream progress as NDJSON events
+        let mut event_error = None;
+        let updated = use_case
+            .execute_with_progress(dto, |event| {
+                if let Err(e) = print_event("update", &event) {
+                    event_error.get_or_insert(e);
+                }
             })
             .context("Failed to update dependencies")?;
+        match event_error {
+            Some(e) => Err(e),
+            None => Ok(updated),
+        }
+    }
 
-        info!("Remember to commit your changes manually with 'git add . && git commit -m \"Update dependencies\"'");
-
+    /// Report the end of an update in JSON mode
+    fn finish_update(&self, dependencies: Vec<UpdatedDependencyDto>) -> Result<()> {
+        if self.json() {
+            print_event("update", &UpdateEventDto::Finished { dependencies })?;
+        }
         Ok(())
     }
 
-    /// Show dependency status
-    pub fn show_dependency_status(
+    /// Remove a dependency and its vendored content
+    pub fn remove_dependency(
+        &self,
+        dependency_name: String,
+        overrides: SafetyOverrides,
+        commit: bool,
+    ) -> Result<()> {
+        debug!(
+            "Removing dependency: {}, overrides: {:?}, commit: {}",
+            dependency_name, overrides, commit
+        );
+
+        let git_operations = GitBackend::from_env().operations();
+        let repo_root = self.repo_root()?;
+        debug!("Using repository root path: {}", repo_root.display());
+
+        let config = TomlConfigurationRepository::new()
+            .load(&self.config_path)
+            .context("Failed to load configuration")?;
+        let target = config
+            .dependencies
+            .iter()
+            .find(|d| d.name == dependency_name)
+            .map(|d| d.target_location.clone());
+
+        // Verify Git status of the paths the removal touches
+        if !overrides.allow_dirty {
+            let targets: Vec<PathBuf> = target.iter().cloned().collect();
+            if let Err(e) = self.ensure_clean_git_status(&git_operations, &repo_root, &targets) {
+                warn!("Git repository status is not clean: {}", e);
+                return Err(e);
+            }
+        }
+        if commit {
+            self.ensure_can_commit(&git_operations, &repo_root)?;
+        }
+
+        if !overrides.assume_yes {
+            if let Some(target) = &target {
+                info!(
+                    "The files vendored into {} will be deleted",
+                    target.display()
+                );
+            }
+
+            if !self.prompt_yes_no("Do you want to continue with the removal?")? {
+                info!("Removal canceled by user");
+                return Ok(());
+            }
+        }
+
+        let use_case = RemoveDependencyUseCase::new(
+            TomlConfigurationRepository::new(),
+            TomlLockFileRepository::new(),
+            FileSystemManagerImpl::new(),
+        );
+        let removed = use_case
+            .execute(RemoveDependencyDto {
+                config_path: self.config_path.clone(),
+                dependency_name: dependency_name.clone(),
+            })
+            .context("Failed to remove dependency")?;
+
+        if !commit {
+            info!("Remember to commit your changes manually with 'git add . && git commit -m \"Remove dependency {dependency_name}\"'");
+            return Ok(());
+        }
+
+        let message = CommitMessage::new(format!("Remove dependency {}", removed.name))
+            .with_change(SourceChange {
+                name: removed.name,
+                repository_url: removed.repository_url,
+                revision: removed.revision,
+                old_commit: removed.commit,
+                new_commit: None,
+            });
+        self.commit_changes(
+            &git_operations,
+            &repo_root,
+            &message,
+            vec![PathBuf::from(removed.target_location)],
+        )
+    }
+
+    /// Preview the changes an update would make
+    pub fn diff_dependencies(
         &self,
         dependencies: Option<Vec<String>>,
-        detailed: bool,
+        text_diffs: bool,
     ) -> Result<()> {
-        // Add error handler for better error messages
-        let error_handler = crate::domain::examples::ErrorHandler::new();
-        // Create components
-        let config_repo = TomlConfigurationRepository::new();
-        let status_query = crate::application::status::GetDependencyStatusQuery::new(config_repo);
+        debug!(
+            "Previewing dependencies: {:?}, text diffs: {}",
+            dependencies, text_diffs
+        );
 
-        // Get statuses
-        let statuses = match status_query.get_all_statuses(&self.config_path) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!(
-                    "{}",
-                    error_handler.display_error(&crate::domain::DomainError::ConfigurationError(
-                        e.to_string()
-                    ))
+        let use_case = DiffDependenciesUseCase::new(
+            TomlConfigurationRepository::new(),
+            self.repository_fetcher()?,
+            FileSystemManagerImpl::new(),
+            GitBackend::from_env().operations(),
+        )
+        .with_mirrors(self.mirrors()?);
+
+        let diffs = use_case
+            .execute(DiffDependenciesDto {
+                config_path: self.config_path.clone(),
+                dependencies,
+                text_diffs,
+            })
+            .context("Failed to preview dependencies")?;
+
+        if self.json() {
+            return print_json("diff", &diffs);
+        }
+
+        for diff in diffs {
+            println!("{} ({}):", diff.name, diff.target_location);
+
+            for transform in &diff.transforms {
+                println!(
+                    "  transform: {} ({} files)",
+                    transform.step,
+                    transform.files.len()
                 );
-                return Err(e);
+                for file in &transform.files {
+                    println!("    {}", file);
+                }
             }
-        };
 
-        // Filter by dependencies if specified
-        let filtered_statuses = if let Some(dep_names) = dependencies {
-            statuses
-                .into_iter()
-                .filter(|s| dep_names.contains(&s.name))
-                .collect::<Vec<_>>()
-        } else {
-            statuses
+            if diff.changes.is_empty() {
+                println!("  No changes");
+                continue;
+            }
+
+            let count = |kind: ChangeKind| diff.changes.iter().filter(|c| c.change == kind).count();
+            for change in &diff.changes {
+                let marker = match change.change {
+                    ChangeKind::Added => "A",
+                    ChangeKind::Removed => "D",
+                    ChangeKind::Modified => "M",
+                };
+                println!("  {} {}", marker, change.path);
+            }
+            println!(
+                "  {} files changed: {} added, {} modified, {} removed",
+                diff.changes.len(),
+                count(ChangeKind::Added),
+                count(ChangeKind::Modified),
+                count(ChangeKind::Removed)
+            );
+
+            for change in &diff.changes {
+                if let Some(text) = &change.diff {
+                    println!();
+                    print!("{}", text);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Show upstream commits between the vendored and a new revision
+    pub fn log_dependency(
+        &self,
+        dependency_name: String,
+        to: Option<String>,
+        markdown: bool,
+    ) -> Result<()> {
+        debug!(
+            "Showing upstream log for dependency: {}, to: {:?}",
+            dependency_name, to
+        );
+
+        let use_case = LogDependencyUseCase::new(
+            TomlConfigurationRepository::new(),
+            TomlLockFileRepository::new(),
+            self.repository_fetcher()?,
+        )
+        .with_mirrors(self.mirrors()?);
+
+        let log = use_case
+            .execute(LogDependencyDto {
+                config_path: self.config_path.clone(),
+                dependency_name,
+                to,
+            })
+            .context("Failed to read upstream history")?;
+
+        if self.json() {
+            return print_json("log", &log);
+        }
+
+        let short = |id: &str| id.chars().take(12).collect::<String>();
+
+        if markdown {
+            println!("### {}: {}..{}", log.name, short(&log.from), short(&log.to));
+            println!();
+            println!("Upstream: {}", log.repository_url);
+            println!();
+            if log.commits.is_empty() {
+                println!("_No upstream commits touched the vendored paths._");
+            }
+            for commit in &log.commits {
+                println!(
+                    "- `{}` {} ({}, {})",
+                    short(&commit.id),
+                    commit.subject,
+                    commit.author,
+                    commit.date
+                );
+            }
+            return Ok(());
+        }
+
+        println!("{} ({}..{}):", log.name, short(&log.from), short(&log.to));
+        if log.commits.is_empty() {
+            println!("  No upstream commits touched the vendored paths");
+        }
+        for commit in &log.commits {
+            println!(
+                "  {} {} {} ({})",
+                short(&commit.id),
+                commit.date,
+                commit.subject,
+                commit.author
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Turn the local modifications of a dependency into a new patch file
+    pub fn create_patch(&self, dependency_name: String, output: Option<PathBuf>) -> Result<()> {
+        debug!(
+            "Creating patch for dependency: {}, output: {:?}",
+            dependency_name, output
+        );
+
+        let use_case = CreatePatchUseCase::new(
+            TomlConfigurationRepository::new(),
+            TomlLockFileRepository::new(),
+            self.repository_fetcher()?,
+            FileSystemManagerImpl::new(),
+            GitBackend::from_env().operations(),
+        )
+        .with_mirrors(self.mirrors()?);
+
+        let patch = use_case
+            .execute(CreatePatchDto {
+                config_path: self.config_path.clone(),
+                dependency_name,
+                output,
+            })
+            .context("Failed to create patch")?;
+
+        if self.json() {
+            return print_json("patch", &patch);
+        }
+
+        println!("Created {} for {}:", patch.path, patch.name);
+        for file in &patch.files {
+            println!("  {}", file);
+        }
+        info!("Patch will be applied on every update of '{}'", patch.name);
+
+        Ok(())
+    }
+
+    /// Export the changes made to a dependency as a patch series for upstream
+    ///
+    /// Without an output directory the series is printed instead of written.
+    pub fn export_changes(
+        &self,
+        dependency_name: String,
+        output_dir: Option<PathBuf>,
+        author: Option<String>,
+    ) -> Result<()> {
+        debug!(
+            "Exporting changes to dependency: {}, output directory: {:?}",
+            dependency_name, output_dir
+        );
+
+        let git_ops = GitBackend::from_env().operations();
+        let author = match author {
+            Some(author) => author,
+            None => git_ops.author(&self.repo_root()?)?.ok_or_else(|| {
+                anyhow!(
+                    "No author identity found. Set user.name and user.email in git or pass --author"
+                )
+            })?,
         };
 
+        let use_case = ExportChangesUseCase::new(
+            TomlConfigurationRepository::new(),
+            TomlLockFileRepository::new(),
+            self.repository_fetcher()?,
+            FileSystemManagerImpl::new(),
+            git_ops,
+        )
+        .with_mirrors(self.mirrors()?);
+
+        let print_series = output_dir.is_none();
+        let exported = use_case
+            .execute(ExportChangesDto {
+                config_path: self.config_path.clone(),
+                dependency_name,
+                author,
+                output_dir,
+            })
+            .context("Failed to export changes")?;
+
+        if self.json() {
+            return print_json("export-changes", &exported);
+        }
+
+        if print_series {
+            for patch in &exported.patches {
+                print!("{}", patch.content.as_deref().unwrap_or_default());
+            }
+            return Ok(());
+        }
+
+        if exported.patches.is_empty() {
+            println!("No changes to export for {}", exported.name);
+            return Ok(());
+        }
+        println!(
+            "Exported {} patches for {} against {}:",
+            exported.patches.len(),
+            exported.name,
+            exported.base_commit
+        );
+        for patch in &exported.patches {
+            println!("  {}", patch.path);
+        }
+        info!("Apply them in the upstream repository with 'git am'");
+
+        Ok(())
+    }
+
+    /// Show dependency status
+    pub fn show_dependency_status(
+        &self,
+        dependencies: Option<Vec<String>>,
+        detailed: bool,
+        check_remote: bool,
+    ) -> Result<()> {
+        let filtered_statuses = self.load_statuses(dependencies, check_remote)?;
+
+        if self.json() {
+            return print_json("status", &filtered_statuses);
+        }
+
         if filtered_statuses.is_empty() {
             println!("No dependencies found");
             return Ok(());
         }
 
+        let auth_service = self.auth_service()?;
+
         // Display the statuses
         println!("Dependencies:");
         for status in filtered_statuses {
@@ -327,13 +908,39 @@ impl CliAdapter {
                 println!("    Revision:   {}", status.revision);
                 println!("    Target:     {}", status.target_location);
                 println!("    Paths:      {}", status.sparse_paths.join(", "));
+                println!(
+                    "    Locked:     {}",
+                    status.locked_commit.as_deref().unwrap_or("(not locked)")
+                );
+                if let Some(provenance) = &status.provenance {
+                    println!(
+                        "    Vendored:   {} by acdm {}",
+                        provenance.commit.as_deref().unwrap_or(&provenance.revision),
+                        provenance.tool_version
+                    );
+                    if let Some(updated_at) = &provenance.updated_at {
+                        println!("    Updated:    {}", updated_at);
+                    }
+                }
 
-                // Use auth service to get auth info
-                let auth_service = crate::domain::auth::AuthenticationService::new();
-                if let Some(auth) = auth_service.get_auth_for_repository(&status.repository_url) {
-                    let auth_helper = crate::domain::examples::AuthHelper::new();
-                    let auth_info = auth_helper.format_credentials(&auth);
-                    println!("    Auth:       {}", auth_info);
+                // Use auth service to get auth info, without asking a
+                // credential helper for the credentials themselves
+                if auth_service.uses_credential_helper(&status.repository_url) {
+                    println!("    Auth:       git credential helper");
+                } else {
+                    match auth_service.get_auth_for_repository(&status.repository_url) {
+                        Ok(Some(auth)) => {
+                            let auth_helper = crate::domain::examples::AuthHelper::new();
+                            let auth_info = auth_helper.format_credentials(&auth);
+                            println!("    Auth:       {}", auth_info);
+                        }
+                        Ok(None) => {}
+                        Err(e) => println!("    Auth:       {}", e),
+                    }
+                }
+
+                for detail in &status.details {
+                    println!("    {}", detail);
                 }
             }
         }
@@ -341,11 +948,295 @@ impl CliAdapter {
         Ok(())
     }
 
+    /// Verify that vendored content matches the lock file
+    pub fn verify_dependencies(&self, dependencies: Option<Vec<String>>) -> Result<()> {
+        debug!("Verifying dependencies: {:?}", dependencies);
+
+        let report = self
+            .status_query()?
+            .verify(&self.config_path, dependencies.as_deref())
+            .context("Failed to verify dependencies")?;
+
+        if self.json() {
+            print_json("verify", &report)?;
+        } else {
+            for status in &report.dependencies {
+                if report.failed.contains(&status.name) {
+                    println!("  - {}: FAILED ({})", status.name, status.states.join(", "));
+                    for detail in &status.details {
+                        println!("    {}", detail);
+                    }
+                } else {
+                    println!("  - {}: ok", status.name);
+                }
+            }
+        }
+
+        if !report.verified {
+            return Err(anyhow!(
+                "Verification failed for: {}",
+                report.failed.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Run configuration, lock and integrity checks for CI
+    ///
+    /// In CI mode a failure is reported with an exit code specific to the
+    /// most severe failure class.
+    pub fn check(&self, ci: bool, junit: Option<PathBuf>, check_remote: bool) -> Result<()> {
+        debug!(
+            "Running checks: ci: {}, junit: {:?}, remote: {}",
+            ci, junit, check_remote
+        );
+
+        // A configuration that cannot be loaded is reported by the check
+        // itself, so the query then runs without credentials or mirrors
+        let query = self.status_query().unwrap_or_else(|_| {
+            GetDependencyStatusQuery::new(
+                TomlConfigurationRepository::new(),
+                TomlLockFileRepository::new(),
+                available_sources(self.base_dir(), AuthenticationService::new()),
+            )
+        });
+        let report = query
+            .check(&self.config_path, check_remote)
+            .context("Failed to run checks")?;
+
+        if let Some(path) = junit {
+            std::fs::write(&path, junit_xml(&report))
+                .with_context(|| format!("Failed to write JUnit report to {}", path.display()))?;
+            info!("JUnit report written to {}", path.display());
+        }
+
+        if self.json() {
+            print_json("check", &report)?;
+        } else {
+            for check in &report.checks {
+                let outcome = if check.passed { "ok" } else { "FAILED" };
+                println!(
+                    "  [{}] {}: {} ({})",
+                    check.class, check.name, outcome, check.message
+                );
+            }
+        }
+
+        if report.passed {
+            return Ok(());
+        }
+
+        let failed = report.checks.iter().filter(|c| !c.passed).count();
+        let summary = format!("{} of {} checks failed", failed, report.checks.len());
+        if ci {
+            return Err(CheckFailed {
+                exit_code: exit_code_for(&report),
+                summary,
+            }
+            .into());
+        }
+
+        Err(anyhow!(summary))
+    }
+
+    /// Show dependencies whose configured revision moved upstream
+    pub fn show_outdated(&self, dependencies: Option<Vec<String>>) -> Result<()> {
+        debug!("Checking for outdated dependencies: {:?}", dependencies);
+
+        let outdated = self
+            .status_query()?
+            .outdated(&self.config_path, dependencies.as_deref())
+            .context("Failed to check for outdated dependencies")?;
+
+        if self.json() {
+            return print_json("outdated", &outdated);
+        }
+
+        if outdated.is_empty() {
+            println!("All dependencies are up to date with upstream");
+            return Ok(());
+        }
+
+        println!("Outdated dependencies:");
+        for dep in outdated {
+            println!(
+                "  - {} ({}): {} -> {}",
+                dep.name,
+                dep.revision,
+                dep.locked_commit.as_deref().unwrap_or("(not locked)"),
+                dep.upstream_commit.as_deref().unwrap_or("(unknown)")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Commit vendored for a dependency according to the lock file
+    fn locked_commit(&self, name: &str) -> Option<String> {
+        TomlLockFileRepository::new()
+            .load(&LockFile::path_for(&self.config_path))
+            .ok()?
+            .dependencies
+            .into_iter()
+            .find(|l| l.name == name)?
+            .commit
+    }
+
+    /// Fail before changing anything when `--commit` cannot be honored
+    fn ensure_can_commit(&self, git_ops: &dyn GitOperations, repo_root: &Path) -> Result<()> {
+        if !git_ops.is_git_repository(repo_root)? {
+            return Err(anyhow!(
+                "--commit requires {} to be inside a Git repository",
+                repo_root.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Commit the configuration, lock file, `.gitattributes` and `targets`,
+    /// all relative to `repo_root`, and nothing else
+    fn commit_changes(
+        &self,
+        git_ops: &dyn GitOperations,
+        repo_root: &Path,
+        message: &CommitMessage,
+        targets: Vec<PathBuf>,
+    ) -> Result<()> {
+        let paths = self.acdm_paths(targets);
+        match git_ops
+            .commit(repo_root, &paths, &message.render())
+            .context("Failed to commit changes")?
+        {
+            Some(commit) => info!("Committed {}: {}", commit, message.subject()),
+            None => info!("Nothing to commit"),
+        }
+        Ok(())
+    }
+
+    /// The configuration, lock file, `.gitattributes` and `targets`, relative
+    /// to the directory of the configuration file
+    fn acdm_paths(&self, targets: Vec<PathBuf>) -> Vec<PathBuf> {
+        let lock_path = LockFile::path_for(&self.config_path);
+        let mut paths: Vec<PathBuf> = [self.config_path.as_path(), lock_path.as_path()]
+            .into_iter()
+            .filter_map(|path| path.file_name().map(PathBuf::from))
+            .collect();
+        paths.push(PathBuf::from(GITATTRIBUTES_FILE));
+        paths.extend(targets);
+        paths
+    }
+
+    /// Directory of the configuration file, or the current directory
+    fn repo_root(&self) -> Result<PathBuf> {
+        match self.config_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => Ok(parent.to_path_buf()),
+            _ => std::env::current_dir()
+                .map_err(|e| anyhow!("Failed to get current directory: {}", e)),
+        }
+    }
+
+    /// Authentication service using the credential sources from the configuration
+    fn auth_service(&self) -> Result<AuthenticationService> {
+        let config = self.load_config()?;
+
+        Ok(AuthenticationService::new()
+            .with_hosts(config.auth)
+            .with_sources(&config.dependencies)
+            .with_credential_store(GitBackend::from_env().credential_store()))
+    }
+
+    /// Mirror rules from the configuration
+    fn mirrors(&self) -> Result<Mirrors> {
+        Ok(Mirrors::new(self.load_config()?.mirrors))
+    }
+
+    /// Loads the configuration, or the defaults when there is no configuration
+    /// file yet
+    ///
+    /// A configuration file that cannot be read or parsed is an error.
+    fn load_config(&self) -> Result<Configuration> {
+        if !self.config_path.exists() {
+            return Ok(Configuration::default());
+        }
+        TomlConfigurationRepository::new()
+            .load(&self.config_path)
+            .context("Failed to load configuration")
+    }
+
+    fn repository_fetcher(&self) -> Result<SourceRegistry> {
+        Ok(available_sources(self.base_dir(), self.auth_service()?))
+    }
+
+    /// Directory local sources are relative to: that of the configuration file
+    fn base_dir(&self) -> PathBuf {
+        self.config_path
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default()
+    }
+
+    fn status_query(
+        &self,
+    ) -> Result<
+        GetDependencyStatusQuery<
+            TomlConfigurationRepository,
+            TomlLockFileRepository,
+            SourceRegistry,
+        >,
+    > {
+        Ok(GetDependencyStatusQuery::new(
+            TomlConfigurationRepository::new(),
+            TomlLockFileRepository::new(),
+            self.repository_fetcher()?,
+        )
+        .with_mirrors(self.mirrors()?))
+    }
+
+    /// Load the status of the selected dependencies
+    fn load_statuses(
+        &self,
+        dependencies: Option<Vec<String>>,
+        check_remote: bool,
+    ) -> Result<Vec<DependencyStatusDto>> {
+        // Add error handler for better error messages
+        let error_handler = crate::domain::examples::ErrorHandler::new();
+
+        // Get statuses
+        let statuses = match self
+            .status_query()?
+            .get_all_statuses(&self.config_path, check_remote)
+        {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    error_handler.display_error(&crate::domain::DomainError::ConfigurationError(
+                        e.to_string()
+                    ))
+                );
+                return Err(e);
+            }
+        };
+
+        // Filter by dependencies if specified
+        Ok(if let Some(dep_names) = dependencies {
+            statuses
+                .into_iter()
+                .filter(|s| dep_names.contains(&s.name))
+                .collect::<Vec<_>>()
+        } else {
+            statuses
+        })
+    }
+
     /// Prompt user for confirmation with yes/no
+    ///
+    /// The prompt goes to stderr so it never mixes with command output.
     fn prompt_yes_no(&self, message: &str) -> Result<bool> {
         let mut input = String::new();
-        print!("{} [y/N]: ", message);
-        std::io::stdout().flush()?;
+        eprint!("{} [y/N]: ", message);
+        std::io::stderr().flush()?;
         std::io::stdin().read_line(&mut input)?;
 
         Ok(input.trim().to_lowercase() == "y")
@@ -354,29 +1245,52 @@ impl CliAdapter {
     /// Ensure the Git repository has a clean status and exists
     fn ensure_clean_git_status(
         &self,
-        git_ops: &GitOperationsImpl,
+        git_ops: &dyn GitOperations,
         repo_path: &std::path::Path,
+        targets: &[PathBuf],
     ) -> Result<()> {
+        let policy = self.load_config()?.require_clean;
         debug!(
-            "Verifying Git repository status for path: {}",
-            repo_path.display()
+            "Verifying Git repository status for path: {} (policy: {:?})",
+            repo_path.display(),
+            policy
         );
+        // `--commit` checks for a repository of its own in `ensure_can_commit`
+        if policy == CleanPolicy::None {
+            return Ok(());
+        }
 
         // First check if it's a git repository at all
         if !git_ops.is_git_repository(repo_path)? {
             return Err(anyhow!("Directory is not a Git repository. This tool requires operations to be performed within a Git repository."));
         }
 
-        // Check if it has a clean status
-        let status = git_ops.get_status(repo_path)?;
+        // Check if the repository, or the paths acdm writes, have a clean status
+        let paths = match policy {
+            CleanPolicy::Paths => self.acdm_paths(targets.to_vec()),
+            _ => Vec::new(),
+        };
+        let status = if paths.is_empty() {
+            git_ops.get_status(repo_path)?
+        } else {
+            git_ops.get_paths_status(repo_path, &paths)?
+        };
         if !status.is_clean() {
             // Run git status to show the user what's going on
             let git_status_output = std::process::Command::new("git")
-                .args(["status", "--short"])
+                .args(["--literal-pathspecs", "status", "--short", "--"])
+                .args(&paths)
                 .current_dir(repo_path)
                 .output();
 
-            let mut error_msg = String::from("Git repository has uncommitted changes. Please commit your changes before proceeding or use the --force flag (NOT RECOMMENDED)");
+            let mut error_msg = String::from(if paths.is_empty() {
+                "Git repository has uncommitted changes."
+            } else {
+                "Paths modified by acdm have uncommitted changes."
+            });
+            error_msg.push_str(
+                " Please commit your changes before proceeding or use the --allow-dirty flag",
+            );
 
             // Add git status output if available
             if let Ok(output) = git_status_output {
@@ -1,3 +1,5 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
+pub mod ci;
 pub mod cli;
+pub mod output;
@@ -0,0 +1,62 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// Version of the JSON output schema, bumped on incompatible changes
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Output format for command results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// Machine-readable JSON (NDJSON events for long-running commands)
+    Json,
+}
+
+/// Envelope wrapping every JSON document
+#[derive(Serialize)]
+struct JsonDocument<'a, T: Serialize> {
+    schema_version: u32,
+    command: &'a str,
+    data: &'a T,
+}
+
+/// Envelope wrapping every NDJSON event
+#[derive(Serialize)]
+struct JsonEvent<'a, T: Serialize> {
+    schema_version: u32,
+    command: &'a str,
+    #[serde(flatten)]
+    event: &'a T,
+}
+
+/// Prints the result of a command as a single JSON document
+pub fn print_json<T: Serialize>(command: &str, data: &T) -> Result<()> {
+    let document = JsonDocument {
+        schema_version: JSON_SCHEMA_VERSION,
+        command,
+        data,
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&document).context("Failed to serialize JSON output")?
+    );
+    Ok(())
+}
+
+/// Prints a single event of an NDJSON stream
+pub fn print_event<T: Serialize>(command: &str, event: &T) -> Result<()> {
+    let event = JsonEvent {
+        schema_version: JSON_SCHEMA_VERSION,
+        command,
+        event,
+    };
+    println!(
+        "{}",
+        serde_json::to_string(&event).context("Failed to serialize JSON event")?
+    );
+    Ok(())
+}
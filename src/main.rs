@@ -14,9 +14,16 @@ fn main() {
     match cli::run() {
         Ok(_) => process::exit(0),
         Err(err) => {
-            error!("Error: {}", err);
-            eprintln!("Error: {}", err);
-            process::exit(1);
+            // Use the alternate format so the full error chain is shown
+            error!("Error: {:#}", err);
+            eprintln!("Error: {:#}", err);
+
+            // Failed CI checks carry their own exit code
+            let code = err
+                .downcast_ref::<interfaces::ci::CheckFailed>()
+                .map(|e| e.exit_code)
+                .unwrap_or(1);
+            process::exit(code);
         }
     }
 }
@@ -0,0 +1,217 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::tempdir;
+
+use super::common::{sha256, tar_gz};
+
+// Helper function to run acdm against a configuration file
+fn acdm(config_path: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .arg("--quiet")
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+// Files of a release archive, nested in a top-level directory like GitHub's
+const FILES: &[(&str, &str)] = &[
+    ("release-1.0/docs/guide.md", "guide\n"),
+    ("release-1.0/docs/nested/deep.md", "deep\n"),
+    ("release-1.0/src/lib.rs", "// code\n"),
+];
+
+fn zip() -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (path, content) in FILES {
+        writer
+            .start_file(*path, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+fn write_config(config_path: &Path, repo: &str, sha256: &str) {
+    fs::write(
+        config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "release"
+type = "archive"
+sparse_paths = ["docs/**"]
+target = "vendor/release"
+
+[sources.archive]
+sha256 = "{}"
+strip_components = 1
+"#,
+            repo, sha256
+        ),
+    )
+    .unwrap();
+}
+
+fn assert_vendored(project: &Path) {
+    let vendored = project.join("vendor/release");
+    assert_eq!(
+        fs::read_to_string(vendored.join("docs/guide.md")).unwrap(),
+        "guide\n"
+    );
+    assert!(vendored.join("docs/nested/deep.md").exists());
+    assert!(!vendored.join("src").exists());
+    assert!(!vendored.join("release-1.0").exists());
+}
+
+#[test]
+fn test_archive_source_unpacks_local_tarball() {
+    let project = tempdir().unwrap();
+    let archive = tar_gz(FILES);
+    fs::write(project.path().join("release-1.0.tar.gz"), &archive).unwrap();
+    let config_path = project.path().join("acdm.toml");
+    write_config(&config_path, "release-1.0.tar.gz", &sha256(&archive));
+
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_vendored(project.path());
+
+    let output = acdm(&config_path, &["status", "--remote"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("release: up-to-date"), "Output: {}", stdout);
+
+    // The lock records the options, so changing them fails the CI check
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        config.replace("strip_components = 1", "strip_components = 0"),
+    )
+    .unwrap();
+    let output = acdm(&config_path, &["check", "--ci"]);
+    assert_eq!(output.status.code(), Some(4));
+    let output = acdm(&config_path, &["status", "--detailed"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("differs from the last update (options)"),
+        "Output: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_archive_source_downloads_zip_over_http() {
+    let project = tempdir().unwrap();
+    let archive = zip();
+    let checksum = sha256(&archive);
+
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let port = server.server_addr().to_ip().unwrap().port();
+    let handle = std::thread::spawn(move || {
+        let request = server.recv().unwrap();
+        assert_eq!(request.url(), "/releases/release-1.0.zip");
+        request
+            .respond(tiny_http::Response::from_data(archive))
+            .unwrap();
+    });
+
+    let config_path = project.path().join("acdm.toml");
+    write_config(
+        &config_path,
+        &format!("http://127.0.0.1:{}/releases/release-1.0.zip", port),
+        &checksum,
+    );
+
+    let output = acdm(&config_path, &["--force", "update"]);
+    handle.join().unwrap();
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_vendored(project.path());
+}
+
+#[test]
+fn test_archive_source_rejects_checksum_mismatch() {
+    let project = tempdir().unwrap();
+    fs::write(project.path().join("release-1.0.tar.gz"), tar_gz(FILES)).unwrap();
+    let config_path = project.path().join("acdm.toml");
+    write_config(&config_path, "release-1.0.tar.gz", &"0".repeat(64));
+
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Checksum mismatch"), "Stderr: {}", stderr);
+    assert!(!project.path().join("vendor/release").exists());
+}
+
+#[test]
+fn test_archive_source_requires_sha256() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        r#"[[sources]]
+repo = "release-1.0.tar.gz"
+name = "release"
+type = "archive"
+sparse_paths = []
+target = "vendor/release"
+"#,
+    )
+    .unwrap();
+
+    let output = acdm(&config_path, &["check"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stdout.contains("missing field `sha256`") || stderr.contains("missing field `sha256`"),
+        "Output: {}{}",
+        stdout,
+        stderr
+    );
+}
+
+#[test]
+fn test_archive_source_checks_its_url() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "ftp://example.com/release-1.0.tar.gz"
+name = "release"
+type = "archive"
+sparse_paths = []
+target = "vendor/release"
+
+[sources.archive]
+sha256 = "{}"
+"#,
+            "0".repeat(64)
+        ),
+    )
+    .unwrap();
+
+    let output = acdm(&config_path, &["check"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("unsupported scheme 'ftp'"),
+        "Output: {}",
+        stdout
+    );
+}
@@ -0,0 +1,234 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+use super::common::{acdm, assert_success, commit_file, for_each_backend, git, init_repo};
+
+// Files changed by the last commit, sorted
+fn committed_files(project: &Path) -> Vec<String> {
+    let mut files: Vec<String> = git(
+        project,
+        &["show", "--name-only", "--format=", "--no-renames", "HEAD"],
+    )
+    .lines()
+    .map(str::to_string)
+    .collect();
+    files.sort();
+    files
+}
+
+fn commits_only_acdm_paths(backend: &str) {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    let first = commit_file(upstream.path(), "docs/spec.md", "v1\n");
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    commit_file(project.path(), "README.md", "readme\n");
+    let config_path = project.path().join("acdm.toml");
+    fs::write(&config_path, "sources = []\n").unwrap();
+    git(project.path(), &["add", "acdm.toml"]);
+    git(project.path(), &["commit", "-m", "Configure acdm"]);
+
+    // Unrelated work in progress stays out of acdm's commits
+    fs::write(project.path().join("README.md"), "staged\n").unwrap();
+    git(project.path(), &["add", "README.md"]);
+    fs::write(project.path().join("notes.txt"), "untracked\n").unwrap();
+
+    let output = acdm(
+        backend,
+        &config_path,
+        &[
+            "add",
+            &upstream.path().to_string_lossy(),
+            "--name",
+            "upstream",
+            "--target",
+            "vendor/upstream",
+            "--commit",
+        ],
+    );
+    assert_success(&output);
+    assert_eq!(committed_files(project.path()), vec!["acdm.toml"]);
+    let message = git(project.path(), &["log", "-1", "--format=%B"]);
+    assert!(
+        message.starts_with("Add dependency upstream\n"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("- upstream (main): none -> none"),
+        "{}",
+        message
+    );
+
+    let output = acdm(
+        backend,
+        &config_path,
+        &["include", "upstream", "docs/**", "--commit"],
+    );
+    assert_success(&output);
+    assert_eq!(
+        git(project.path(), &["log", "-1", "--format=%s"]),
+        "Include paths for upstream"
+    );
+
+    let output = acdm(backend, &config_path, &["update", "--commit"]);
+    assert_success(&output);
+    assert_eq!(
+        committed_files(project.path()),
+        vec![
+            "acdm.lock",
+            "vendor/upstream/.acdm-source.toml",
+            "vendor/upstream/docs/spec.md"
+        ]
+    );
+    let trailers = git(
+        project.path(),
+        &[
+            "log",
+            "-1",
+            "--format=%(trailers:key=Acdm-Source,valueonly)",
+        ],
+    );
+    assert_eq!(
+        trailers,
+        format!(
+            "name=upstream repo={} rev=main old=none new={}",
+            upstream.path().display(),
+            first
+        )
+    );
+
+    let second = commit_file(upstream.path(), "docs/spec.md", "v2\n");
+    let output = acdm(backend, &config_path, &["update", "upstream", "--commit"]);
+    assert_success(&output);
+    let message = git(project.path(), &["log", "-1", "--format=%B"]);
+    assert!(
+        message.starts_with("Update dependency upstream\n"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains(&format!(
+            "- upstream (main): {} -> {}",
+            &first[..12],
+            &second[..12]
+        )),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains(&format!("old={} new={}", first, second)),
+        "{}",
+        message
+    );
+
+    let output = acdm(backend, &config_path, &["remove", "upstream", "--commit"]);
+    assert_success(&output);
+    assert!(!project.path().join("vendor/upstream").exists());
+    assert_eq!(
+        committed_files(project.path()),
+        vec![
+            "acdm.lock",
+            "acdm.toml",
+            "vendor/upstream/.acdm-source.toml",
+            "vendor/upstream/docs/spec.md"
+        ]
+    );
+    let message = git(project.path(), &["log", "-1", "--format=%B"]);
+    assert!(
+        message.contains(&format!("rev=main old={} new=none", second)),
+        "{}",
+        message
+    );
+
+    // The work in progress is still there, as it was
+    let status = git(project.path(), &["status", "--porcelain"]);
+    assert_eq!(status, "M  README.md\n?? notes.txt");
+}
+
+#[test]
+fn test_commit_option() {
+    for_each_backend(commits_only_acdm_paths);
+}
+
+#[test]
+fn test_commit_option_requires_a_git_repository() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(&config_path, "sources = []\n").unwrap();
+
+    let output = acdm(
+        "cli",
+        &config_path,
+        &[
+            "add",
+            "https://example.com/repo.git",
+            "--name",
+            "example",
+            "--target",
+            "vendor/example",
+            "--commit",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--commit requires"));
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), "sources = []\n");
+}
+
+#[test]
+fn test_remove_deletes_only_the_vendored_files() {
+    let root = tempdir().unwrap();
+    let upstream = root.path().join("upstream");
+    fs::create_dir_all(upstream.join("src")).unwrap();
+    fs::write(upstream.join("src/lib.c"), "int x;\n").unwrap();
+
+    let project = root.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let config_path = project.join("acdm.toml");
+    fs::write(
+        &config_path,
+        "[[sources]]\nrepo = \"../upstream\"\nname = \"upstream\"\ntype = \"path\"\nsparse_paths = []\ntarget = \"vendor\"\n",
+    )
+    .unwrap();
+    assert_success(&acdm("cli", &config_path, &["--yes", "update"]));
+    assert!(project.join("vendor/src/lib.c").exists());
+
+    // A file added next to the vendored ones survives the removal
+    fs::write(project.join("vendor/LOCAL.md"), "ours\n").unwrap();
+    assert_success(&acdm("cli", &config_path, &["--yes", "remove", "upstream"]));
+    assert!(!project.join("vendor/src").exists());
+    assert!(!project.join("vendor/.acdm-source.toml").exists());
+    assert_eq!(
+        fs::read_to_string(project.join("vendor/LOCAL.md")).unwrap(),
+        "ours\n"
+    );
+
+    // Targets naming the repository itself are refused before anything changes
+    for target in [".", "../elsewhere"] {
+        let output = acdm(
+            "cli",
+            &config_path,
+            &[
+                "add",
+                "../upstream",
+                "--type",
+                "path",
+                "--name",
+                "root",
+                "--target",
+                target,
+            ],
+        );
+        assert!(!output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains(&format!("target '{}'", target)),
+            "Stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    assert!(!fs::read_to_string(&config_path).unwrap().contains("root"));
+}
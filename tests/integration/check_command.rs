@@ -0,0 +1,162 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::common::vendoring_project;
+
+// Helper function to create a project with its upstream vendored
+fn setup() -> (tempfile::TempDir, tempfile::TempDir, PathBuf) {
+    let (upstream, project, config_path) = vendoring_project();
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--force", "--config"])
+        .arg(&config_path)
+        .arg("update")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    (upstream, project, config_path)
+}
+
+// Helper function to run the CI check and return its exit code
+fn check(config_path: &Path, extra: &[&str]) -> i32 {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--config"])
+        .arg(config_path)
+        .args(["check", "--ci"])
+        .args(extra)
+        .output()
+        .unwrap()
+        .status
+        .code()
+        .unwrap()
+}
+
+#[test]
+fn test_check_passes_and_writes_junit() {
+    let (_upstream, project, config_path) = setup();
+    let junit = project.path().join("acdm-junit.xml");
+
+    assert_eq!(
+        check(&config_path, &["--junit", junit.to_str().unwrap()]),
+        0
+    );
+
+    let xml = fs::read_to_string(&junit).unwrap();
+    assert!(
+        xml.contains("<testsuite name=\"acdm check\""),
+        "XML: {}",
+        xml
+    );
+    assert!(xml.contains("classname=\"acdm.integrity\" name=\"upstream\""));
+    assert!(xml.contains("failures=\"0\""), "XML: {}", xml);
+}
+
+#[test]
+fn test_check_exit_codes_per_failure_class() {
+    let (_upstream, project, config_path) = setup();
+
+    // Integrity failure
+    let vendored = project.path().join("vendor/upstream/docs/spec.md");
+    fs::write(&vendored, "hotfix\n").unwrap();
+    assert_eq!(check(&config_path, &[]), 5);
+
+    // Lock inconsistency takes precedence over integrity
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(&config_path, config.replace("docs/**", "docs/*.md")).unwrap();
+    assert_eq!(check(&config_path, &[]), 4);
+
+    // So does a lock file that cannot be read
+    let lock_path = project.path().join("acdm.lock");
+    let lock = fs::read_to_string(&lock_path).unwrap();
+    fs::write(&lock_path, "not = [valid").unwrap();
+    assert_eq!(check(&config_path, &[]), 4);
+    fs::write(&lock_path, lock).unwrap();
+
+    // Invalid configuration takes precedence over everything else
+    fs::write(&config_path, "not = [valid").unwrap();
+    assert_eq!(check(&config_path, &[]), 3);
+}
+
+#[test]
+fn test_check_reports_failures_in_junit() {
+    let (_upstream, project, config_path) = setup();
+    let junit = project.path().join("acdm-junit.xml");
+
+    fs::remove_dir_all(project.path().join("vendor/upstream")).unwrap();
+    assert_eq!(
+        check(&config_path, &["--junit", junit.to_str().unwrap()]),
+        5
+    );
+
+    let xml = fs::read_to_string(&junit).unwrap();
+    assert!(xml.contains("<failure type=\"integrity\""), "XML: {}", xml);
+}
+
+#[test]
+fn test_check_fails_when_patches_change() {
+    let (_upstream, project, config_path) = vendoring_project();
+    let patch_path = project.path().join("patches/spec.patch");
+    fs::create_dir_all(patch_path.parent().unwrap()).unwrap();
+    fs::write(
+        &patch_path,
+        "--- a/docs/spec.md\n+++ b/docs/spec.md\n@@ -1 +1 @@\n-v1\n+v1 (patched)\n",
+    )
+    .unwrap();
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        format!("{}patches = [\"patches/*.patch\"]\n", config),
+    )
+    .unwrap();
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--force", "--config"])
+        .arg(&config_path)
+        .arg("update")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let lock = fs::read_to_string(project.path().join("acdm.lock")).unwrap();
+    assert!(
+        lock.contains("path = \"patches/spec.patch\""),
+        "Lock: {}",
+        lock
+    );
+    assert_eq!(check(&config_path, &[]), 0);
+
+    // Editing a patch changes the configuration without touching acdm.toml
+    fs::write(
+        &patch_path,
+        "--- a/docs/spec.md\n+++ b/docs/spec.md\n@@ -1 +1 @@\n-v1\n+v1 (patched again)\n",
+    )
+    .unwrap();
+    assert_eq!(check(&config_path, &[]), 4);
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--config"])
+        .arg(&config_path)
+        .args(["status", "--detailed"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("differs from the last update (patches)"),
+        "Output: {}",
+        stdout
+    );
+
+    // So does adding a patch
+    fs::write(
+        &patch_path,
+        "--- a/docs/spec.md\n+++ b/docs/spec.md\n@@ -1 +1 @@\n-v1\n+v1 (patched)\n",
+    )
+    .unwrap();
+    assert_eq!(check(&config_path, &[]), 0);
+    fs::write(project.path().join("patches/more.patch"), "").unwrap();
+    assert_eq!(check(&config_path, &[]), 4);
+}
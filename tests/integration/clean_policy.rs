@@ -0,0 +1,143 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+use super::common::{assert_success, for_each_backend, git, init_repo};
+
+fn commit_all(path: &Path) {
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", "Snapshot"]);
+}
+
+// Helper function to run acdm with a git backend, without --force
+fn acdm(backend: &str, config_path: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .env("ACDM_GIT_BACKEND", backend)
+        .args(["--quiet", "--config"])
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_blocked(output: &std::process::Output, path: &str) {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "acdm should have failed");
+    assert!(stderr.contains("--allow-dirty"), "{}", stderr);
+    assert!(stderr.contains(path), "{}", stderr);
+}
+
+const SOURCE: &str = r#"[[sources]]
+repo = "../upstream"
+name = "upstream"
+type = "path"
+sparse_paths = []
+target = "vendor/upstream"
+"#;
+
+fn checks_only_the_paths_acdm_modifies(backend: &str) {
+    let root = tempdir().unwrap();
+    let upstream = root.path().join("upstream");
+    fs::create_dir_all(&upstream).unwrap();
+    fs::write(upstream.join("lib.c"), "int x;\n").unwrap();
+
+    let project = root.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    init_repo(&project);
+    fs::write(project.join("README.md"), "readme\n").unwrap();
+    let config_path = project.join("acdm.toml");
+    fs::write(&config_path, SOURCE).unwrap();
+    commit_all(&project);
+
+    // Unrelated work in progress does not get in the way
+    fs::write(project.join("README.md"), "work in progress\n").unwrap();
+    fs::write(project.join("notes.txt"), "untracked\n").unwrap();
+    assert_success(&acdm(backend, &config_path, &["--yes", "update"]));
+    git(&project, &["add", "vendor", "acdm.lock"]);
+    git(&project, &["commit", "-m", "Vendor upstream"]);
+
+    // Local edits to a target or the configuration do
+    fs::write(project.join("vendor/upstream/lib.c"), "int y;\n").unwrap();
+    assert_blocked(
+        &acdm(backend, &config_path, &["--yes", "update"]),
+        "vendor/upstream/lib.c",
+    );
+    assert_blocked(
+        &acdm(backend, &config_path, &["--yes", "remove", "upstream"]),
+        "vendor/upstream/lib.c",
+    );
+    git(&project, &["checkout", "--", "vendor"]);
+    fs::write(&config_path, format!("{}\n", SOURCE)).unwrap();
+    assert_blocked(
+        &acdm(backend, &config_path, &["--yes", "update"]),
+        "acdm.toml",
+    );
+
+    // --allow-dirty lets the command through
+    assert_success(&acdm(
+        backend,
+        &config_path,
+        &["--allow-dirty", "--yes", "update"],
+    ));
+
+    // The whole repository is checked when configured so
+    fs::write(
+        &config_path,
+        format!("{}\n[safety]\nrequire_clean = \"repository\"\n", SOURCE),
+    )
+    .unwrap();
+    git(&project, &["add", "acdm.toml"]);
+    git(&project, &["commit", "-m", "Check the whole repository"]);
+    assert_blocked(
+        &acdm(backend, &config_path, &["--yes", "update"]),
+        "notes.txt",
+    );
+}
+
+#[test]
+fn test_clean_policy() {
+    for_each_backend(checks_only_the_paths_acdm_modifies);
+}
+
+#[test]
+fn test_clean_policy_none_skips_the_check() {
+    let root = tempdir().unwrap();
+    let upstream = root.path().join("upstream");
+    fs::create_dir_all(&upstream).unwrap();
+    fs::write(upstream.join("lib.c"), "int x;\n").unwrap();
+
+    // Not even a Git repository is required
+    let project = root.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let config_path = project.join("acdm.toml");
+    fs::write(&config_path, SOURCE).unwrap();
+    assert!(!acdm("cli", &config_path, &["--yes", "update"])
+        .status
+        .success());
+
+    fs::write(
+        &config_path,
+        format!("{}\n[safety]\nrequire_clean = \"none\"\n", SOURCE),
+    )
+    .unwrap();
+    assert_success(&acdm("cli", &config_path, &["--yes", "update"]));
+    assert!(project.join("vendor/upstream/lib.c").exists());
+
+    // Committing still needs one
+    for args in [
+        &["--yes", "update", "--commit"][..],
+        &["--yes", "update", "--branch", "acdm/update-test"][..],
+    ] {
+        let output = acdm("cli", &config_path, args);
+        assert!(!output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("--commit requires"),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+// Helpers shared by the integration tests
+
+use assert_cmd::Command;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command as StdCommand, Output};
+use tempfile::{tempdir, TempDir};
+
+/// Git backends the end-to-end scenarios run against
+pub const BACKENDS: [&str; 2] = ["cli", "libgit2"];
+
+/// Runs a scenario once with every git backend
+pub fn for_each_backend(scenario: impl Fn(&str)) {
+    for backend in BACKENDS {
+        // Shown with the output of a failing test
+        eprintln!("Running with the {} backend", backend);
+        scenario(backend);
+    }
+}
+
+/// Runs a git command, returning its trimmed stdout
+pub fn git(path: &Path, args: &[&str]) -> String {
+    let output = StdCommand::new("git")
+        .args(args)
+        .current_dir(path)
+        .output()
+        .expect("Failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Creates a repository on `main` with a committer configured
+pub fn init_repo(path: &Path) {
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.name", "Test User"]);
+    git(path, &["config", "user.email", "test@example.com"]);
+    git(path, &["config", "commit.gpgsign", "false"]);
+}
+
+/// Commits a file, returning the new commit
+pub fn commit_file(path: &Path, file: &str, content: &str) -> String {
+    commit_file_with_message(path, file, content, &format!("Change {}", file))
+}
+
+/// Commits a file with a message, returning the new commit
+pub fn commit_file_with_message(path: &Path, file: &str, content: &str, message: &str) -> String {
+    let file_path = path.join(file);
+    fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+    fs::write(file_path, content).unwrap();
+    git(path, &["add", "-A"]);
+    git(path, &["commit", "-m", message]);
+    git(path, &["rev-parse", "HEAD"])
+}
+
+/// Runs acdm with a git backend, skipping the clean checks
+pub fn acdm(backend: &str, config_path: &Path, args: &[&str]) -> Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .env("ACDM_GIT_BACKEND", backend)
+        .args(["--quiet", "--force", "--config"])
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+pub fn assert_success(output: &Output) {
+    assert!(output.status.success(), "acdm failed: {}", stderr(output));
+}
+
+/// An upstream repository with `docs/spec.md`, and a project whose
+/// configuration vendors its docs, not updated yet
+///
+/// Returns both directories and the path of the configuration.
+pub fn vendoring_project() -> (TempDir, TempDir, PathBuf) {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file(upstream.path(), "docs/spec.md", "v1\n");
+
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = ["docs/**"]
+target = "vendor/upstream"
+"#,
+            upstream.path().display()
+        ),
+    )
+    .unwrap();
+
+    (upstream, project, config_path)
+}
+
+/// A gzipped tarball of files, by their path in the archive
+pub fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for (path, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, content.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+pub fn sha256(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
@@ -4,7 +4,7 @@ use std::fs;
 use std::path::PathBuf;
 use tempfile::tempdir;
 
-use wrale_acdm::domain::models::{Configuration, Dependency, RepositoryType};
+use wrale_acdm::domain::models::{Configuration, Dependency};
 use wrale_acdm::domain::repositories::ConfigurationRepository;
 use wrale_acdm::infrastructure::configuration::TomlConfigurationRepository;
 
@@ -54,6 +54,7 @@ fn test_config_save_and_load() {
     let mut config = Configuration {
         default_location: Some(PathBuf::from("vendor")),
         dependencies: vec![],
+        ..Default::default()
     };
 
     // Add a dependency
@@ -61,9 +62,10 @@ fn test_config_save_and_load() {
         name: "test-dep".to_string(),
         repository_url: "git@github.com:example/repo.git".to_string(),
         revision: "main".to_string(),
-        repository_type: RepositoryType::Git,
+        repository_type: "git".to_string(),
         sparse_paths: vec!["docs/**".to_string(), "src/**".to_string()],
         target_location: PathBuf::from("vendor/test"),
+        ..Default::default()
     });
 
     // Save the configuration
@@ -141,3 +143,73 @@ fn test_invalid_config() {
         "Expected error loading invalid configuration"
     );
 }
+
+#[test]
+fn test_config_rejects_targets_outside_the_repository() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("acdm.toml");
+    let config_repo = TomlConfigurationRepository::new();
+
+    for target in ["", ".", "./", "/vendor", "../vendor", "vendor/../.."] {
+        fs::write(
+            &config_path,
+            format!(
+                "[[sources]]\nrepo = \"../upstream\"\nname = \"upstream\"\ntype = \"path\"\nsparse_paths = []\ntarget = \"{}\"\n",
+                target
+            ),
+        )
+        .expect("Failed to write file");
+
+        let error = config_repo
+            .load(&config_path)
+            .expect_err("Expected the target to be rejected")
+            .to_string();
+        assert!(
+            error.contains(&format!("target '{}'", target)),
+            "Error: {}",
+            error
+        );
+    }
+}
+
+#[test]
+fn test_auth_sections_survive_save() {
+    // Create a temporary directory for the test
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("acdm.toml");
+
+    fs::write(
+        &config_path,
+        r#"sources = []
+
+[auth."gitlab.example.com"]
+env = "GITLAB_TOKEN"
+
+[auth."gitea.internal"]
+username = "deploy"
+credential-helper = true
+"#,
+    )
+    .expect("Failed to write file");
+
+    let config_repo = TomlConfigurationRepository::new();
+    let config = config_repo
+        .load(&config_path)
+        .expect("Failed to load configuration");
+
+    assert_eq!(
+        config.auth["gitlab.example.com"].env.as_deref(),
+        Some("GITLAB_TOKEN")
+    );
+    assert!(config.auth["gitea.internal"].credential_helper);
+
+    // Saving, as add and include do, must keep the auth sections
+    config_repo
+        .save(&config, &config_path)
+        .expect("Failed to save configuration");
+    let reloaded = config_repo
+        .load(&config_path)
+        .expect("Failed to reload configuration");
+
+    assert_eq!(reloaded.auth, config.auth);
+}
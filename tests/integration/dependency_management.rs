@@ -9,9 +9,10 @@ use tempfile::tempdir;
 use mockall::mock;
 use mockall::predicate::*;
 use wrale_acdm::domain::error::DomainError;
-use wrale_acdm::domain::models::{Dependency, RepositoryType};
+use wrale_acdm::domain::models::{Dependency, LocalChanges, LockFile, LockedDependency};
 use wrale_acdm::domain::repositories::{FileSystemManager, RepositoryFetcher};
-use wrale_acdm::domain::services::DependencyUpdater;
+use wrale_acdm::domain::services::{DependencyManager, DependencyUpdater};
+use wrale_acdm::domain::transform::{LineEnding, Transform, TransformAction};
 
 // Mock the repository fetcher
 mock! {
@@ -30,6 +31,7 @@ mock! {
         fn copy_content(&self, source: &Path, destination: &Path) -> Result<(), DomainError>;
         fn create_temp_directory(&self) -> Result<PathBuf, DomainError>;
         fn remove_temp_directory(&self, path: &Path) -> Result<(), DomainError>;
+        fn remove_files(&self, root: &Path, files: &[PathBuf]) -> Result<(), DomainError>;
     }
 }
 
@@ -39,6 +41,9 @@ mock! {
     impl wrale_acdm::domain::repositories::GitOperations for GitOperationsMock {
         fn is_git_repository(&self, path: &Path) -> Result<bool, DomainError>;
         fn get_status(&self, repo_path: &Path) -> Result<wrale_acdm::domain::repositories::GitStatus, DomainError>;
+        fn get_paths_status(&self, repo_path: &Path, paths: &[PathBuf]) -> Result<wrale_acdm::domain::repositories::GitStatus, DomainError>;
+        fn commit(&self, repo_path: &Path, paths: &[PathBuf], message: &str) -> Result<Option<String>, DomainError>;
+        fn create_branch(&self, repo_path: &Path, name: &str) -> Result<(), DomainError>;
     }
 }
 
@@ -99,9 +104,10 @@ fn test_dependency_update_with_mocks() {
         name: "example-dep".to_string(),
         repository_url: "git@github.com:example/repo.git".to_string(),
         revision: "main".to_string(),
-        repository_type: RepositoryType::Git,
+        repository_type: "git".to_string(),
         sparse_paths: vec!["docs/**".to_string(), "src/**".to_string()],
         target_location: PathBuf::from("vendor/example"),
+        ..Default::default()
     };
 
     // Create the updater with our mocks
@@ -115,7 +121,13 @@ fn test_dependency_update_with_mocks() {
     fs::create_dir_all(&target_dir).unwrap();
 
     // Call the updater
-    let result = updater.update(&dependency, repo_root.path());
+    let result = updater.update(
+        &dependency,
+        repo_root.path(),
+        None,
+        LocalChanges::default(),
+        false,
+    );
 
     // Assert that the update was successful
     assert!(
@@ -125,6 +137,213 @@ fn test_dependency_update_with_mocks() {
     );
 }
 
+#[test]
+fn test_preview_removes_temp_directories_when_fetch_fails() {
+    let mut repo_fetcher = MockRepositoryFetcherMock::new();
+    let mut fs_manager = MockFileSystemManagerMock::new();
+    let git_ops = MockGitOperationsMock::new();
+
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path().to_path_buf();
+
+    // Both the checkout and the staging directory are created up front
+    fs_manager
+        .expect_create_temp_directory()
+        .times(2)
+        .returning(move || Ok(temp_path.clone()));
+
+    repo_fetcher
+        .expect_fetch()
+        .times(1)
+        .returning(|_, _, _| Err(DomainError::GitError("unreachable".to_string())));
+
+    // ...and both are removed although the fetch failed
+    fs_manager
+        .expect_remove_temp_directory()
+        .times(2)
+        .returning(|_| Ok(()));
+
+    let dependency = Dependency {
+        name: "example-dep".to_string(),
+        repository_url: "git@github.com:example/repo.git".to_string(),
+        revision: "main".to_string(),
+        repository_type: "git".to_string(),
+        target_location: PathBuf::from("vendor/example"),
+        ..Default::default()
+    };
+
+    let updater = DependencyUpdater::new(repo_fetcher, fs_manager, git_ops);
+    let repo_root = tempdir().unwrap();
+
+    let result = updater.preview(&dependency, repo_root.path(), false);
+    assert!(matches!(result, Err(DomainError::GitError(_))));
+}
+
+#[test]
+fn test_rebuild_removes_temp_directories_when_fetch_fails() {
+    let mut repo_fetcher = MockRepositoryFetcherMock::new();
+    let mut fs_manager = MockFileSystemManagerMock::new();
+    let git_ops = MockGitOperationsMock::new();
+
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path().to_path_buf();
+
+    // The checkout and the content directory are created up front...
+    fs_manager
+        .expect_create_temp_directory()
+        .times(2)
+        .returning(move || Ok(temp_path.clone()));
+
+    repo_fetcher
+        .expect_fetch()
+        .times(1)
+        .returning(|_, _, _| Err(DomainError::GitError("unreachable".to_string())));
+
+    // ...and both are removed although fetching the locked commit failed
+    fs_manager
+        .expect_remove_temp_directory()
+        .times(2)
+        .returning(|_| Ok(()));
+
+    let dependency = Dependency {
+        name: "example-dep".to_string(),
+        repository_url: "git@github.com:example/repo.git".to_string(),
+        revision: "main".to_string(),
+        repository_type: "git".to_string(),
+        target_location: PathBuf::from("vendor/example"),
+        ..Default::default()
+    };
+    let previous = LockedDependency {
+        name: "example-dep".to_string(),
+        repository_url: "git@github.com:example/repo.git".to_string(),
+        revision: "main".to_string(),
+        commit: Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+        mirror: None,
+        sparse_paths: Vec::new(),
+        target_location: PathBuf::from("vendor/example"),
+        patches: Vec::new(),
+        transforms: Vec::new(),
+        options: Default::default(),
+        files: Default::default(),
+    };
+
+    let updater = DependencyUpdater::new(repo_fetcher, fs_manager, git_ops);
+    let repo_root = tempdir().unwrap();
+
+    let result = updater.rebuild(&dependency, &previous, repo_root.path());
+    assert!(matches!(result, Err(DomainError::GitError(_))));
+}
+
+#[test]
+fn test_prepare_removes_the_checkout_when_staging_cannot_start() {
+    let mut repo_fetcher = MockRepositoryFetcherMock::new();
+    let mut fs_manager = MockFileSystemManagerMock::new();
+    let git_ops = MockGitOperationsMock::new();
+
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path().to_path_buf();
+
+    // The checkout is created and fetched, but the staging directory is not...
+    let mut created = 0;
+    fs_manager
+        .expect_create_temp_directory()
+        .times(2)
+        .returning(move || {
+            created += 1;
+            if created == 1 {
+                Ok(temp_path.clone())
+            } else {
+                Err(DomainError::FileSystemError("no space left".to_string()))
+            }
+        });
+    repo_fetcher
+        .expect_fetch()
+        .times(1)
+        .returning(|_, _, _| Ok(()));
+
+    // ...and the checkout is removed
+    fs_manager
+        .expect_remove_temp_directory()
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let dependency = Dependency {
+        name: "example-dep".to_string(),
+        repository_url: "https://example.com/repo.git".to_string(),
+        revision: "main".to_string(),
+        repository_type: "git".to_string(),
+        target_location: PathBuf::from("vendor/example"),
+        transforms: vec![Transform {
+            action: TransformAction::LineEndings(LineEnding::Lf),
+            paths: Vec::new(),
+        }],
+        ..Default::default()
+    };
+
+    let updater = DependencyUpdater::new(repo_fetcher, fs_manager, git_ops);
+    let repo_root = tempdir().unwrap();
+
+    let result = updater.prepare(&dependency, repo_root.path());
+    assert!(matches!(result, Err(DomainError::FileSystemError(_))));
+}
+
+#[test]
+fn test_update_all_discards_prepared_dependencies_when_an_install_fails() {
+    let mut repo_fetcher = MockRepositoryFetcherMock::new();
+    let mut fs_manager = MockFileSystemManagerMock::new();
+    let git_ops = MockGitOperationsMock::new();
+
+    let temp_dir = tempdir().unwrap();
+    let temp_path = temp_dir.path().to_path_buf();
+
+    // Both dependencies are fetched before either is installed
+    fs_manager
+        .expect_create_temp_directory()
+        .times(2)
+        .returning(move || Ok(temp_path.clone()));
+    repo_fetcher
+        .expect_fetch()
+        .times(2)
+        .returning(|_, _, _| Ok(()));
+
+    // The first install fails while cleaning its target...
+    fs_manager
+        .expect_clean_directory()
+        .times(1)
+        .returning(|_| Err(DomainError::FileSystemError("read-only".to_string())));
+
+    // ...and the checkouts of both dependencies are removed
+    fs_manager
+        .expect_remove_temp_directory()
+        .times(2)
+        .returning(|_| Ok(()));
+
+    let dependencies: Vec<Dependency> = ["first", "second"]
+        .iter()
+        .map(|name| Dependency {
+            name: name.to_string(),
+            repository_url: format!("https://example.com/{}.git", name),
+            revision: "main".to_string(),
+            repository_type: "git".to_string(),
+            target_location: PathBuf::from("vendor").join(name),
+            ..Default::default()
+        })
+        .collect();
+
+    let manager = DependencyManager::new(repo_fetcher, fs_manager, git_ops);
+    let repo_root = tempdir().unwrap();
+
+    let result = manager.update_all(
+        &dependencies,
+        repo_root.path(),
+        &LockFile::default(),
+        LocalChanges::default(),
+        false,
+        &mut |_| {},
+    );
+    assert!(matches!(result, Err(DomainError::FileSystemError(_))));
+}
+
 #[test]
 fn test_real_command_with_temp_dir() {
     // Create a temporary directory for testing
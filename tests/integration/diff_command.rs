@@ -0,0 +1,117 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+use super::common::{git, init_repo};
+
+// Helper function to create an upstream repository with a single commit
+fn init_upstream(path: &Path) {
+    init_repo(path);
+
+    fs::create_dir_all(path.join("docs")).unwrap();
+    fs::write(path.join("docs/spec.md"), "line one\nline two\n").unwrap();
+    fs::write(path.join("docs/old.md"), "obsolete\n").unwrap();
+    git(path, &["add", "."]);
+    git(path, &["commit", "-m", "Initial upstream commit"]);
+}
+
+// Helper function to write a configuration pointing at the upstream repository
+fn write_config(project: &Path, upstream: &Path) -> std::path::PathBuf {
+    let config_path = project.join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = ["docs/**"]
+target = "vendor/upstream"
+"#,
+            upstream.display()
+        ),
+    )
+    .unwrap();
+    config_path
+}
+
+// Helper function to run acdm against a configuration file
+fn acdm(config_path: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .arg("--quiet")
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_diff_reports_file_level_changes() {
+    let upstream = tempdir().unwrap();
+    init_upstream(upstream.path());
+
+    let project = tempdir().unwrap();
+    let config_path = write_config(project.path(), upstream.path());
+
+    // Vendor the initial content
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Change the upstream repository
+    fs::write(upstream.path().join("docs/spec.md"), "line one\nline 2\n").unwrap();
+    fs::write(upstream.path().join("docs/new.md"), "fresh\n").unwrap();
+    fs::remove_file(upstream.path().join("docs/old.md")).unwrap();
+    git(upstream.path(), &["add", "-A"]);
+    git(upstream.path(), &["commit", "-m", "Change docs"]);
+
+    let output = acdm(&config_path, &["diff", "--text"]);
+    assert!(
+        output.status.success(),
+        "Diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("A docs/new.md"), "Output: {}", stdout);
+    assert!(stdout.contains("D docs/old.md"), "Output: {}", stdout);
+    assert!(stdout.contains("M docs/spec.md"), "Output: {}", stdout);
+    assert!(stdout.contains("3 files changed: 1 added, 1 modified, 1 removed"));
+    assert!(stdout.contains("-line two"), "Output: {}", stdout);
+    assert!(stdout.contains("+line 2"), "Output: {}", stdout);
+
+    // The working tree must be untouched
+    let vendored = project.path().join("vendor/upstream/docs");
+    assert!(vendored.join("old.md").exists());
+    assert!(!vendored.join("new.md").exists());
+}
+
+#[test]
+fn test_update_dry_run_does_not_touch_working_tree() {
+    let upstream = tempdir().unwrap();
+    init_upstream(upstream.path());
+
+    let project = tempdir().unwrap();
+    let config_path = write_config(project.path(), upstream.path());
+
+    // No git repository and no --force: a dry run must still work
+    let output = acdm(&config_path, &["update", "--dry-run"]);
+    assert!(
+        output.status.success(),
+        "Dry run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("A docs/spec.md"), "Output: {}", stdout);
+    assert!(!project.path().join("vendor/upstream").exists());
+}
@@ -0,0 +1,322 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::fs;
+use tempfile::tempdir;
+
+use super::common::{acdm, commit_file, for_each_backend, git, init_repo, stderr};
+
+const FIX_PATCH: &str = "\
+From 0000000000000000000000000000000000000000 Mon Sep 17 00:00:00 2001
+From: Someone <someone@example.com>
+Subject: [PATCH] Fix the second line
+
+The second line was wrong.
+---
+ src/lib.txt | 2 +-
+
+--- a/src/lib.txt
++++ b/src/lib.txt
+@@ -1,3 +1,3 @@
+ one
+-two
++two (fixed)
+ three
+";
+
+fn exports_changes_as_series(backend: &str) {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file(upstream.path(), "README.md", "upstream\n");
+    commit_file(upstream.path(), "src/lib.txt", "one\ntwo\nthree\nfour\n");
+    let base = git(upstream.path(), &["rev-parse", "HEAD"]);
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    fs::create_dir_all(project.path().join("patches")).unwrap();
+    fs::write(project.path().join("patches/0001-fix.patch"), FIX_PATCH).unwrap();
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = ["src/**"]
+target = "vendor/upstream"
+patches = ["patches/*.patch"]
+"#,
+            upstream.path().display()
+        ),
+    )
+    .unwrap();
+    let output = acdm(backend, &config_path, &["update"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // Nothing but the patch yet
+    let output = acdm(
+        backend,
+        &config_path,
+        &["export-changes", "upstream", "--stdout"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let series = String::from_utf8_lossy(&output.stdout).to_string();
+    assert!(
+        series.contains("Subject: [PATCH] Fix the second line"),
+        "{}",
+        series
+    );
+    assert!(
+        series.contains("From: Test User <test@example.com>"),
+        "{}",
+        series
+    );
+    assert!(
+        series.contains(&format!("base-commit: {}", base)),
+        "{}",
+        series
+    );
+
+    // Printed as JSON, the series comes with the patches themselves, dated
+    // when they were written
+    let output = acdm(
+        backend,
+        &config_path,
+        &["--format", "json", "export-changes", "upstream", "--stdout"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let undated = |text: &str| {
+        text.lines()
+            .filter(|line| !line.starts_with("Date: "))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    assert_eq!(
+        document["data"]["patches"][0]["content"]
+            .as_str()
+            .map(undated),
+        Some(undated(&series))
+    );
+
+    // A local fix and a new file made while vendoring
+    let vendored = project.path().join("vendor/upstream");
+    fs::write(
+        vendored.join("src/lib.txt"),
+        "one\ntwo (fixed)\nthree\nfour (local)\n",
+    )
+    .unwrap();
+    fs::write(vendored.join("src/new.txt"), "new\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(
+            vendored.join("src/new.txt"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+    }
+
+    // Upstream moving on does not change what the series applies to
+    commit_file(upstream.path(), "src/other.txt", "other\n");
+
+    let out_dir = tempdir().unwrap();
+    let output = acdm(
+        backend,
+        &config_path,
+        &[
+            "export-changes",
+            "upstream",
+            "--output-dir",
+            out_dir.path().to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let mut files: Vec<_> = fs::read_dir(out_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    let names: Vec<_> = files
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "0001-Fix-the-second-line.patch",
+            "0002-Local-modifications-made-while-vendoring-upstream.patch"
+        ]
+    );
+
+    // The series applies upstream at the locked commit with git am
+    git(upstream.path(), &["checkout", "-q", "-b", "contrib", &base]);
+    let mut am = vec!["am".to_string()];
+    am.extend(files.iter().map(|path| path.to_string_lossy().to_string()));
+    let am: Vec<&str> = am.iter().map(String::as_str).collect();
+    git(upstream.path(), &am);
+
+    assert_eq!(
+        fs::read_to_string(upstream.path().join("src/lib.txt")).unwrap(),
+        "one\ntwo (fixed)\nthree\nfour (local)\n"
+    );
+    assert_eq!(
+        fs::read_to_string(upstream.path().join("src/new.txt")).unwrap(),
+        "new\n"
+    );
+    #[cfg(unix)]
+    assert!(git(upstream.path(), &["ls-files", "-s", "src/new.txt"]).starts_with("100755 "));
+    assert_eq!(
+        git(upstream.path(), &["log", "--format=%an|%s", "-2"]),
+        "Test User|Local modifications made while vendoring upstream\nTest User|Fix the second line"
+    );
+    assert_eq!(
+        git(upstream.path(), &["log", "--format=%b", "-1", "HEAD~1"]),
+        "The second line was wrong."
+    );
+}
+
+#[test]
+fn test_export_changes() {
+    for_each_backend(exports_changes_as_series);
+}
+
+#[test]
+fn test_export_changes_requires_a_locked_commit() {
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        r#"[[sources]]
+repo = "https://github.com/example/repo.git"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/upstream"
+"#,
+    )
+    .unwrap();
+
+    let output = acdm("cli", &config_path, &["export-changes", "upstream"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Run 'acdm update upstream' first"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_export_changes_undo_the_transforms() {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file(
+        upstream.path(),
+        "src/main.go",
+        "// Copyright Upstream Authors\n\nimport \"github.com/upstream/lib\"\n\nfunc a() {}\n\nfunc b() {}\n",
+    );
+    commit_file(upstream.path(), "docs/guide.md", "# Guide\n");
+    commit_file(upstream.path(), "docs/notes.markdown", "# Notes\n");
+    let base = git(upstream.path(), &["rev-parse", "HEAD"]);
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/upstream"
+
+[[sources.transforms]]
+type = "replace"
+from = "github.com/upstream/lib"
+to = "example.com/vendor/lib"
+
+[[sources.transforms]]
+type = "strip-header"
+contains = "Copyright"
+
+[[sources.transforms]]
+type = "rename-extension"
+from = "md"
+to = "markdown"
+"#,
+            upstream.path().display()
+        ),
+    )
+    .unwrap();
+    let output = acdm("cli", &config_path, &["update"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let vendored = project.path().join("vendor/upstream");
+    fs::write(
+        vendored.join("src/main.go"),
+        "import \"example.com/vendor/lib\"\n\nfunc a() {}\n\nfunc b() { fix() }\n",
+    )
+    .unwrap();
+    fs::write(vendored.join("docs/guide.markdown"), "# Guide\n\nFixed\n").unwrap();
+    fs::write(vendored.join("docs/notes.markdown"), "# Notes\n\nFixed\n").unwrap();
+
+    let out_dir = tempdir().unwrap();
+    let output = acdm(
+        "cli",
+        &config_path,
+        &[
+            "export-changes",
+            "upstream",
+            "--output-dir",
+            out_dir.path().to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // Only the local changes reach upstream, not the rewritten text
+    git(upstream.path(), &["checkout", "-q", "-b", "contrib", &base]);
+    let patch = fs::read_dir(out_dir.path())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    git(upstream.path(), &["am", patch.to_str().unwrap()]);
+    assert_eq!(
+        fs::read_to_string(upstream.path().join("src/main.go")).unwrap(),
+        "// Copyright Upstream Authors\n\nimport \"github.com/upstream/lib\"\n\nfunc a() {}\n\nfunc b() { fix() }\n"
+    );
+    assert_eq!(
+        fs::read_to_string(upstream.path().join("docs/guide.md")).unwrap(),
+        "# Guide\n\nFixed\n"
+    );
+    assert_eq!(
+        fs::read_to_string(upstream.path().join("docs/notes.markdown")).unwrap(),
+        "# Notes\n\nFixed\n"
+    );
+
+    // A change to rewritten text has no upstream counterpart
+    fs::write(
+        vendored.join("src/main.go"),
+        "import \"example.com/vendor/lib/v2\"\n\nfunc a() {}\n\nfunc b() { fix() }\n",
+    )
+    .unwrap();
+    let output = acdm(
+        "cli",
+        &config_path,
+        &["export-changes", "upstream", "--stdout"],
+    );
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("src/main.go changed where the transforms rewrote it"),
+        "{}",
+        stderr(&output)
+    );
+}
@@ -0,0 +1,381 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::tempdir;
+
+const TOKEN: &str = "s3cr3t-token-value";
+
+// Helper function to install a fake git that records its arguments and asks the
+// real git for the credentials the configured helpers would provide
+fn install_fake_git(bin_dir: &Path, log_path: &Path) {
+    let script = format!(
+        r#"#!/bin/sh
+if [ "$1" = credential ]; then exec /usr/bin/git "$@"; fi
+echo "args: $*" >> "{log}"
+echo "ssh: $GIT_SSH_COMMAND" >> "{log}"
+printf 'protocol=https\nhost=example.invalid\n\n' | /usr/bin/git credential fill >> "{log}" 2>&1
+exit 128
+"#,
+        log = log_path.display()
+    );
+    let git_path = bin_dir.join("git");
+    fs::write(&git_path, script).unwrap();
+    fs::set_permissions(&git_path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+// Helper function to write a configuration with a single HTTPS source
+fn write_config(project: &Path) -> std::path::PathBuf {
+    write_config_with_auth(project, "")
+}
+
+// Helper function to write a configuration with two SSH sources on the same host
+fn write_ssh_config(project: &Path) -> std::path::PathBuf {
+    let config_path = project.join("acdm.toml");
+    fs::write(
+        &config_path,
+        r#"[[sources]]
+repo = "git@example.invalid:org/deploy.git"
+name = "deploy"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/deploy"
+ssh_key = "/keys/deploy_key"
+
+[[sources]]
+repo = "git@example.invalid:org/shared.git"
+name = "shared"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/shared"
+
+[auth."example.invalid"]
+ssh_key = "/keys/host_key"
+known_hosts = "/keys/known_hosts"
+"#,
+    )
+    .unwrap();
+    config_path
+}
+
+// Helper function to write a configuration with extra auth sections
+fn write_config_with_auth(project: &Path, auth: &str) -> std::path::PathBuf {
+    let config_path = project.join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "https://example.invalid/private/repo.git"
+name = "private"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/private"
+{}"#,
+            auth
+        ),
+    )
+    .unwrap();
+    config_path
+}
+
+// Helper function to run an update through the fake git and return the output and git log
+fn update_with_fake_git(
+    project: &Path,
+    config_path: &Path,
+    envs: &[(&str, &str)],
+) -> (std::process::Output, String) {
+    let bin_dir = tempdir().unwrap();
+    let log_path = project.join("git.log");
+    install_fake_git(bin_dir.path(), &log_path);
+
+    let path = format!(
+        "{}:{}",
+        bin_dir.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env("PATH", path)
+        .env_remove("GIT_TOKEN")
+        .env_remove("GIT_SSH_COMMAND")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("ACDM_CACHE_DIR", project.join("cache"))
+        .envs(envs.iter().copied())
+        .arg("--quiet")
+        .arg("--config")
+        .arg(config_path)
+        .args(["--force", "update"])
+        .output()
+        .unwrap();
+
+    let log = fs::read_to_string(&log_path).unwrap_or_default();
+    (output, log)
+}
+
+#[test]
+fn test_token_is_passed_through_credential_helper() {
+    let project = tempdir().unwrap();
+    let bin_dir = tempdir().unwrap();
+    let log_path = project.path().join("git.log");
+    install_fake_git(bin_dir.path(), &log_path);
+    let config_path = write_config(project.path());
+
+    let path = format!(
+        "{}:{}",
+        bin_dir.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env("PATH", path)
+        .env("GIT_TOKEN", TOKEN)
+        .env("RUST_LOG", "debug")
+        .env("ACDM_CACHE_DIR", project.path().join("cache"))
+        .arg("--config")
+        .arg(&config_path)
+        .args(["--force", "update"])
+        .output()
+        .unwrap();
+
+    // The fake git always fails, so the update must fail too
+    assert!(!output.status.success());
+
+    let log = fs::read_to_string(&log_path).unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // The helper hands the token to git...
+    assert!(log.contains("username=x-access-token"), "Log: {}", log);
+    assert!(log.contains(&format!("password={}", TOKEN)), "Log: {}", log);
+
+    // ...but it never appears on the command line or in acdm's logs
+    for line in log.lines().filter(|l| l.starts_with("args:")) {
+        assert!(!line.contains(TOKEN), "Token leaked in arguments: {}", line);
+    }
+    assert!(!stderr.contains(TOKEN), "Token leaked in logs: {}", stderr);
+}
+
+#[test]
+fn test_no_credentials_without_token() {
+    let project = tempdir().unwrap();
+    let bin_dir = tempdir().unwrap();
+    let log_path = project.path().join("git.log");
+    install_fake_git(bin_dir.path(), &log_path);
+    let config_path = write_config(project.path());
+
+    let path = format!(
+        "{}:{}",
+        bin_dir.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .env("PATH", path)
+        .env_remove("GIT_TOKEN")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_ASKPASS", "/bin/false")
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("HOME", project.path())
+        .env("ACDM_CACHE_DIR", project.path().join("cache"))
+        .arg("--quiet")
+        .arg("--config")
+        .arg(&config_path)
+        .args(["--force", "update"])
+        .output()
+        .unwrap();
+
+    let log = fs::read_to_string(&log_path).unwrap();
+    assert!(!log.contains("x-access-token"), "Log: {}", log);
+}
+
+#[test]
+fn test_host_auth_from_env_with_username_uses_basic_auth() {
+    let project = tempdir().unwrap();
+    let config_path = write_config_with_auth(
+        project.path(),
+        r#"
+[auth."example.invalid"]
+username = "deploy"
+env = "ACDM_TEST_GITEA_PASSWORD"
+"#,
+    );
+
+    let (_, log) = update_with_fake_git(
+        project.path(),
+        &config_path,
+        &[
+            ("ACDM_TEST_GITEA_PASSWORD", "gitea-pass"),
+            ("GIT_TOKEN", "global-token"),
+        ],
+    );
+
+    // The host entry wins over the global token
+    assert!(log.contains("username=deploy"), "Log: {}", log);
+    assert!(log.contains("password=gitea-pass"), "Log: {}", log);
+    assert!(!log.contains("global-token"), "Log: {}", log);
+}
+
+#[test]
+fn test_host_auth_from_file_uses_token() {
+    let project = tempdir().unwrap();
+    let token_path = project.path().join("token");
+    fs::write(&token_path, "file-token\n").unwrap();
+    let config_path = write_config_with_auth(
+        project.path(),
+        &format!(
+            r#"
+[auth."example.invalid"]
+file = "{}"
+"#,
+            token_path.display()
+        ),
+    );
+
+    let (_, log) = update_with_fake_git(project.path(), &config_path, &[]);
+
+    assert!(log.contains("username=x-access-token"), "Log: {}", log);
+    assert!(log.contains("password=file-token\n"), "Log: {}", log);
+}
+
+#[test]
+fn test_host_auth_from_credential_helper() {
+    let project = tempdir().unwrap();
+    let config_path = write_config_with_auth(
+        project.path(),
+        r#"
+[auth."example.invalid"]
+credential-helper = true
+"#,
+    );
+
+    // A helper configured for git, as a user would in ~/.gitconfig
+    let (_, log) = update_with_fake_git(
+        project.path(),
+        &config_path,
+        &[
+            ("GIT_CONFIG_COUNT", "1"),
+            ("GIT_CONFIG_KEY_0", "credential.helper"),
+            (
+                "GIT_CONFIG_VALUE_0",
+                "!f() { echo username=helper-user; echo password=helper-pass; }; f",
+            ),
+        ],
+    );
+
+    assert!(log.contains("username=helper-user"), "Log: {}", log);
+    assert!(log.contains("password=helper-pass"), "Log: {}", log);
+}
+
+#[test]
+fn test_host_auth_with_missing_env_var_fails() {
+    let project = tempdir().unwrap();
+    let config_path = write_config_with_auth(
+        project.path(),
+        r#"
+[auth."example.invalid"]
+env = "ACDM_TEST_UNSET_TOKEN"
+"#,
+    );
+
+    let (output, _) = update_with_fake_git(project.path(), &config_path, &[]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("ACDM_TEST_UNSET_TOKEN"),
+        "Stderr: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_ssh_key_and_known_hosts_are_applied() {
+    let project = tempdir().unwrap();
+    let config_path = write_ssh_config(project.path());
+
+    let (output, log) = update_with_fake_git(project.path(), &config_path, &[]);
+    assert!(!output.status.success());
+
+    // The source key overrides the host key; known_hosts comes from the host
+    assert!(
+        log.contains(
+            "ssh: ssh -i '/keys/deploy_key' -o IdentitiesOnly=yes \
+             -o UserKnownHostsFile='/keys/known_hosts' -o StrictHostKeyChecking=yes"
+        ),
+        "Log: {}",
+        log
+    );
+}
+
+#[test]
+fn test_status_detailed_shows_ssh_key() {
+    let project = tempdir().unwrap();
+    let config_path = write_ssh_config(project.path());
+
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .arg("--quiet")
+        .arg("--config")
+        .arg(&config_path)
+        .args(["status", "--detailed"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Auth:       SSH key /keys/deploy_key, known hosts /keys/known_hosts"),
+        "Output: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Auth:       SSH key /keys/host_key, known hosts /keys/known_hosts"),
+        "Output: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_status_detailed_does_not_run_the_credential_helper() {
+    let project = tempdir().unwrap();
+    let config_path = write_config_with_auth(
+        project.path(),
+        r#"
+[auth."example.invalid"]
+credential-helper = true
+"#,
+    );
+
+    // A helper leaving a trace whenever git asks it for credentials
+    let marker = project.path().join("helper-ran");
+    let helper = format!(
+        "!f() {{ touch '{}'; echo username=u; echo password=p; }}; f",
+        marker.display()
+    );
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "credential.helper")
+        .env("GIT_CONFIG_VALUE_0", &helper)
+        .arg("--quiet")
+        .arg("--config")
+        .arg(&config_path)
+        .args(["status", "--detailed"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Auth:       git credential helper"),
+        "Output: {}",
+        stdout
+    );
+    assert!(!marker.exists());
+}
@@ -0,0 +1,293 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+// The same suite runs against the git executable and libgit2, so both
+// backends are held to identical behavior.
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+use super::common::{commit_file_with_message, git, init_repo, BACKENDS};
+
+use wrale_acdm::domain::repositories::{GitOperations, RepositoryFetcher};
+use wrale_acdm::domain::DomainError;
+use wrale_acdm::infrastructure::git::{GitOperationsImpl, GitRepositoryFetcher};
+use wrale_acdm::infrastructure::libgit2::{LibGitOperations, LibGitRepositoryFetcher};
+
+/// Upstream repository with a branch, an annotated and a lightweight tag
+struct Upstream {
+    dir: TempDir,
+    initial: String,
+    code: String,
+    head: String,
+}
+
+impl Upstream {
+    fn new() -> Self {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path());
+        let initial = commit_file_with_message(dir.path(), "docs/spec.md", "v1\n", "Initial spec");
+        git(dir.path(), &["tag", "-a", "v1.0", "-m", "Release 1.0"]);
+        let code = commit_file_with_message(dir.path(), "src/main.c", "int main;\n", "Add code");
+        git(dir.path(), &["tag", "light"]);
+        let head = commit_file_with_message(dir.path(), "docs/spec.md", "v2\n", "Revise spec");
+
+        Self {
+            dir,
+            initial,
+            code,
+            head,
+        }
+    }
+
+    fn url(&self) -> String {
+        self.dir.path().to_string_lossy().to_string()
+    }
+}
+
+fn fetch(fetcher: &impl RepositoryFetcher, upstream: &Upstream, revision: &str) -> TempDir {
+    let checkout = tempdir().unwrap();
+    fetcher
+        .fetch(&upstream.url(), revision, checkout.path())
+        .unwrap_or_else(|e| panic!("Fetching {} failed: {}", revision, e));
+    checkout
+}
+
+fn fetches_branches(fetcher: impl RepositoryFetcher) {
+    let upstream = Upstream::new();
+    let checkout = fetch(&fetcher, &upstream, "main");
+
+    assert_eq!(
+        fs::read_to_string(checkout.path().join("docs/spec.md")).unwrap(),
+        "v2\n"
+    );
+    assert_eq!(
+        fetcher.resolve_revision(checkout.path()).unwrap(),
+        Some(upstream.head.clone())
+    );
+}
+
+fn fetches_tags(fetcher: impl RepositoryFetcher) {
+    let upstream = Upstream::new();
+
+    let checkout = fetch(&fetcher, &upstream, "v1.0");
+    assert_eq!(
+        fs::read_to_string(checkout.path().join("docs/spec.md")).unwrap(),
+        "v1\n"
+    );
+    assert!(!checkout.path().join("src/main.c").exists());
+    assert_eq!(
+        fetcher.resolve_revision(checkout.path()).unwrap(),
+        Some(upstream.initial.clone())
+    );
+
+    let checkout = fetch(&fetcher, &upstream, "light");
+    assert_eq!(
+        fetcher.resolve_revision(checkout.path()).unwrap(),
+        Some(upstream.code.clone())
+    );
+}
+
+fn fetches_commits(fetcher: impl RepositoryFetcher) {
+    let upstream = Upstream::new();
+
+    for revision in [upstream.code.clone(), upstream.code[..10].to_string()] {
+        let checkout = fetch(&fetcher, &upstream, &revision);
+        assert!(checkout.path().join("src/main.c").exists());
+        assert_eq!(
+            fs::read_to_string(checkout.path().join("docs/spec.md")).unwrap(),
+            "v1\n"
+        );
+        assert_eq!(
+            fetcher.resolve_revision(checkout.path()).unwrap(),
+            Some(upstream.code.clone())
+        );
+    }
+}
+
+fn rejects_unknown_revisions(fetcher: impl RepositoryFetcher) {
+    let upstream = Upstream::new();
+    let checkout = tempdir().unwrap();
+
+    let result = fetcher.fetch(&upstream.url(), "no-such-branch", checkout.path());
+    assert!(
+        matches!(result, Err(DomainError::GitError(_))),
+        "Result: {:?}",
+        result
+    );
+}
+
+fn resolves_remote_revisions(fetcher: impl RepositoryFetcher) {
+    let upstream = Upstream::new();
+    let url = upstream.url();
+    let resolve = |revision: &str| fetcher.resolve_remote_revision(&url, revision).unwrap();
+
+    assert_eq!(resolve("main"), Some(upstream.head.clone()));
+    // Annotated tags resolve to the commit they point to
+    assert_eq!(resolve("v1.0"), Some(upstream.initial.clone()));
+    assert_eq!(resolve("light"), Some(upstream.code.clone()));
+    assert_eq!(
+        resolve(&upstream.code.to_uppercase()),
+        Some(upstream.code.clone())
+    );
+    assert_eq!(resolve("no-such-branch"), None);
+}
+
+fn reports_repository_status(operations: impl GitOperations) {
+    let repo = tempdir().unwrap();
+
+    assert!(!operations.is_git_repository(repo.path()).unwrap());
+    assert!(!operations
+        .is_git_repository(&repo.path().join("missing"))
+        .unwrap());
+
+    init_repo(repo.path());
+    commit_file_with_message(repo.path(), "README.md", "readme\n", "Initial commit");
+    fs::create_dir(repo.path().join("docs")).unwrap();
+    assert!(operations.is_git_repository(repo.path()).unwrap());
+    assert!(operations
+        .is_git_repository(&repo.path().join("docs"))
+        .unwrap());
+    assert!(operations.get_status(repo.path()).unwrap().is_clean());
+
+    // Empty directories do not count as untracked
+    fs::create_dir(repo.path().join("empty")).unwrap();
+    assert!(operations.get_status(repo.path()).unwrap().is_clean());
+
+    // Untracked files only count below the checked directory
+    fs::write(repo.path().join("notes.txt"), "notes\n").unwrap();
+    let status = operations.get_status(repo.path()).unwrap();
+    assert!(status.has_untracked_files);
+    assert!(!status.has_staged_changes && !status.has_unstaged_changes);
+    assert!(operations
+        .get_status(&repo.path().join("docs"))
+        .unwrap()
+        .is_clean());
+
+    git(repo.path(), &["add", "notes.txt"]);
+    let status = operations.get_status(repo.path()).unwrap();
+    assert!(status.has_staged_changes);
+    assert!(!status.has_unstaged_changes && !status.has_untracked_files);
+
+    git(repo.path(), &["commit", "-m", "Add notes"]);
+    fs::write(repo.path().join("README.md"), "changed\n").unwrap();
+    let status = operations.get_status(repo.path()).unwrap();
+    assert!(status.has_unstaged_changes);
+    assert!(!status.has_staged_changes && !status.has_untracked_files);
+}
+
+macro_rules! backend_suite {
+    ($backend:ident, $fetcher:expr, $operations:expr) => {
+        mod $backend {
+            use super::*;
+
+            #[test]
+            fn test_fetches_branches() {
+                fetches_branches($fetcher);
+            }
+
+            #[test]
+            fn test_fetches_tags() {
+                fetches_tags($fetcher);
+            }
+
+            #[test]
+            fn test_fetches_commits() {
+                fetches_commits($fetcher);
+            }
+
+            #[test]
+            fn test_rejects_unknown_revisions() {
+                rejects_unknown_revisions($fetcher);
+            }
+
+            #[test]
+            fn test_resolves_remote_revisions() {
+                resolves_remote_revisions($fetcher);
+            }
+
+            #[test]
+            fn test_reports_repository_status() {
+                reports_repository_status($operations);
+            }
+        }
+    };
+}
+
+backend_suite!(cli, GitRepositoryFetcher::new(), GitOperationsImpl::new());
+backend_suite!(
+    libgit2,
+    LibGitRepositoryFetcher::new(),
+    LibGitOperations::new()
+);
+
+// Helper function to run acdm with a git backend and its own cache
+fn acdm(backend: &str, cache_dir: &Path, config_path: &Path, args: &[&str]) -> String {
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env("ACDM_GIT_BACKEND", backend)
+        .env("ACDM_CACHE_DIR", cache_dir)
+        .args(["--quiet", "--force", "--format", "json", "--config"])
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{} {:?} failed: {}",
+        backend,
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_backends_agree_on_updates_status_and_log() {
+    let upstream = Upstream::new();
+    let mut results = Vec::new();
+
+    for backend in BACKENDS {
+        let project = tempdir().unwrap();
+        let cache = tempdir().unwrap();
+        let config_path = project.path().join("acdm.toml");
+        fs::write(
+            &config_path,
+            format!(
+                r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "v1.0"
+type = "git"
+sparse_paths = ["docs/**"]
+target = "vendor/upstream"
+"#,
+                upstream.url()
+            ),
+        )
+        .unwrap();
+
+        acdm(backend, cache.path(), &config_path, &["update"]);
+        let lock = fs::read_to_string(project.path().join("acdm.lock")).unwrap();
+        assert!(
+            lock.contains(&format!("commit = \"{}\"", upstream.initial)),
+            "Lock: {}",
+            lock
+        );
+
+        let status = acdm(backend, cache.path(), &config_path, &["status", "--remote"]);
+        let log = acdm(
+            backend,
+            cache.path(),
+            &config_path,
+            &["log", "upstream", "--to", "main"],
+        );
+        assert!(log.contains("Revise spec"), "Log: {}", log);
+        assert!(!log.contains("Add code"), "Log: {}", log);
+
+        results.push((lock, status, log));
+    }
+
+    assert_eq!(results[0], results[1]);
+}
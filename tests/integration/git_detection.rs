@@ -1,12 +1,5 @@
 // Copyright (c) 2025 Wrale LTD <contact@wrale.com>
 
-// Using raw strings to avoid prefixed identifier errors in tests
-macro_rules! raw {
-    ($s:expr) => {
-        concat!("", $s, "")
-    };
-}
-
 use std::fs;
 use std::path::PathBuf;
 use std::process::Command as StdCommand;
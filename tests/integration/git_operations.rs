@@ -12,20 +12,20 @@ use wrale_acdm::infrastructure::git::GitOperationsImpl;
 // Helper function to initialize a git repository
 fn init_git_repo(path: &Path) {
     Command::new("git")
-        .args(&["init"])
+        .args(["init"])
         .current_dir(path)
         .output()
         .expect("Failed to initialize git repository");
 
     // Configure git user for the test repository
     Command::new("git")
-        .args(&["config", "user.name", "Test User"])
+        .args(["config", "user.name", "Test User"])
         .current_dir(path)
         .output()
         .expect("Failed to configure git user name");
 
     Command::new("git")
-        .args(&["config", "user.email", "test@example.com"])
+        .args(["config", "user.email", "test@example.com"])
         .current_dir(path)
         .output()
         .expect("Failed to configure git user email");
@@ -41,13 +41,13 @@ fn commit_file(repo_path: &Path, filename: &str, content: &str, message: &str) {
 
     // Stage and commit
     Command::new("git")
-        .args(&["add", filename])
+        .args(["add", filename])
         .current_dir(repo_path)
         .output()
         .expect("Failed to stage file");
 
     Command::new("git")
-        .args(&["commit", "-m", message])
+        .args(["commit", "-m", message])
         .current_dir(repo_path)
         .output()
         .expect("Failed to commit file");
@@ -113,7 +113,7 @@ fn test_git_operations_status() {
 
     // Stage the file
     Command::new("git")
-        .args(&["add", "file2.txt"])
+        .args(["add", "file2.txt"])
         .current_dir(repo_dir.path())
         .output()
         .expect("Failed to stage file");
@@ -0,0 +1,222 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+// Helper function to run acdm against a configuration file
+fn acdm(config_path: &Path, args: &[&str]) -> std::process::Output {
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .arg("--quiet")
+        .arg("--force")
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{:?} failed: {}{}",
+        args,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn path_source(name: &str, target: &str) -> String {
+    format!(
+        r#"
+[[sources]]
+repo = "../upstream"
+name = "{}"
+type = "path"
+sparse_paths = []
+target = "{}"
+"#,
+        name, target
+    )
+}
+
+#[test]
+fn test_gitattributes_block_follows_the_configured_targets() {
+    let root = tempdir().unwrap();
+    let upstream = root.path().join("upstream");
+    fs::create_dir_all(&upstream).unwrap();
+    fs::write(upstream.join("lib.c"), "int x;\n").unwrap();
+
+    let project = root.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let config_path = project.join("acdm.toml");
+    let gitattributes_path = project.join(".gitattributes");
+    fs::write(&gitattributes_path, "*.sh text eol=lf\n").unwrap();
+    let settings = "[gitattributes]\nmanage = true\ndiff = false\n";
+    fs::write(
+        &config_path,
+        format!("{}{}", path_source("one", "vendor/one"), settings),
+    )
+    .unwrap();
+
+    acdm(&config_path, &["update"]);
+    let attributes = fs::read_to_string(&gitattributes_path).unwrap();
+    assert!(attributes.starts_with("*.sh text eol=lf\n\n# BEGIN acdm managed block"));
+    assert!(
+        attributes.contains("\n/vendor/one/** linguist-vendored -diff\n"),
+        "Attributes: {}",
+        attributes
+    );
+
+    // Adding a source marks its target and keeps the setting
+    acdm(
+        &config_path,
+        &[
+            "add",
+            "../upstream",
+            "--type",
+            "path",
+            "--name",
+            "two",
+            "--target",
+            "vendor/second copy",
+        ],
+    );
+    let config = fs::read_to_string(&config_path).unwrap();
+    assert!(config.contains("[gitattributes]"), "Config: {}", config);
+    let attributes = fs::read_to_string(&gitattributes_path).unwrap();
+    assert!(
+        attributes.contains("/vendor/second[[:space:]]copy/** linguist-vendored -diff\n"),
+        "Attributes: {}",
+        attributes
+    );
+    acdm(&config_path, &["update"]);
+    acdm(&config_path, &["check"]);
+
+    // Edits by hand are picked up by the next update, and flagged until then
+    fs::write(
+        &config_path,
+        format!(
+            "{}{}",
+            path_source("one", "third_party/one"),
+            settings.replace("diff = false", "diff = true")
+        ),
+    )
+    .unwrap();
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--config"])
+        .arg(&config_path)
+        .arg("check")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("acdm block in .gitattributes"));
+
+    acdm(&config_path, &["update"]);
+    let attributes = fs::read_to_string(&gitattributes_path).unwrap();
+    assert_eq!(
+        attributes,
+        "*.sh text eol=lf\n\n\
+         # BEGIN acdm managed block: do not edit, run acdm to update\n\
+         /third_party/one/** linguist-vendored\n\
+         # END acdm managed block\n"
+    );
+
+    // Turning the setting off removes the block and nothing else
+    fs::write(&config_path, path_source("one", "third_party/one")).unwrap();
+    acdm(&config_path, &["update"]);
+    assert_eq!(
+        fs::read_to_string(&gitattributes_path).unwrap(),
+        "*.sh text eol=lf\n"
+    );
+}
+
+#[test]
+fn test_gitattributes_file_is_left_alone_unless_opted_in() {
+    let root = tempdir().unwrap();
+    let upstream = root.path().join("upstream");
+    fs::create_dir_all(&upstream).unwrap();
+    fs::write(upstream.join("lib.c"), "int x;\n").unwrap();
+
+    let project = root.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let config_path = project.join("acdm.toml");
+    fs::write(&config_path, path_source("one", "vendor/one")).unwrap();
+
+    acdm(&config_path, &["update"]);
+    assert!(!project.join(".gitattributes").exists());
+
+    // A file holding only the block goes away with it
+    fs::write(
+        &config_path,
+        format!(
+            "{}[gitattributes]\nmanage = true\n",
+            path_source("one", "vendor/one")
+        ),
+    )
+    .unwrap();
+    acdm(&config_path, &["update"]);
+    assert!(project.join(".gitattributes").exists());
+
+    fs::write(&config_path, path_source("one", "vendor/one")).unwrap();
+    acdm(&config_path, &["update"]);
+    assert!(!project.join(".gitattributes").exists());
+}
+
+#[test]
+fn test_gitattributes_patterns_match_targets_with_glob_characters_literally() {
+    let root = tempdir().unwrap();
+    let upstream = root.path().join("upstream");
+    fs::create_dir_all(&upstream).unwrap();
+    fs::write(upstream.join("lib.c"), "int x;\n").unwrap();
+
+    let project = root.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let config_path = project.join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            "{}[gitattributes]\nmanage = true\n",
+            path_source("one", "vendor/lib[v2]*?")
+        ),
+    )
+    .unwrap();
+
+    acdm(&config_path, &["update"]);
+    let attributes = fs::read_to_string(project.join(".gitattributes")).unwrap();
+    assert!(
+        attributes.contains("\n/vendor/lib\\[v2]\\*\\?/** linguist-vendored\n"),
+        "Attributes: {}",
+        attributes
+    );
+
+    // Git reads the pattern as the target itself, not as a glob over its siblings
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .current_dir(&project)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    git(&["init", "--quiet"]);
+    let vendored = git(&[
+        "check-attr",
+        "linguist-vendored",
+        "--",
+        "vendor/lib[v2]*?/lib.c",
+        "vendor/libv*x/lib.c",
+    ]);
+    assert!(
+        vendored.contains("vendor/lib[v2]*?/lib.c: linguist-vendored: set"),
+        "Attributes: {}",
+        vendored
+    );
+    assert!(
+        vendored.contains("vendor/libv*x/lib.c: linguist-vendored: unspecified"),
+        "Attributes: {}",
+        vendored
+    );
+}
@@ -0,0 +1,321 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tempfile::tempdir;
+
+const SCHEMA: &str = "{\"type\": \"object\"}\n";
+const LICENSES: &str = "MIT\nApache-2.0\n";
+
+// Helper function to run acdm with its own download cache
+fn acdm(config_path: &Path, cache_dir: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .env("ACDM_CACHE_DIR", cache_dir)
+        .arg("--quiet")
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn sha256(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+// Serves the schema and license list with validators, answering conditional
+// requests with 304 and recording the If-None-Match header of each request
+fn serve(requests: usize) -> (u16, Arc<Mutex<Vec<String>>>, JoinHandle<()>) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let port = server.server_addr().to_ip().unwrap().port();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+
+    let handle = std::thread::spawn(move || {
+        for _ in 0..requests {
+            let request = server.recv().unwrap();
+            let (body, etag) = match request.url() {
+                "/schemas/v1/schema.json" => (SCHEMA, "\"schema-1\""),
+                "/licenses.txt" => (LICENSES, "\"licenses-1\""),
+                other => panic!("Unexpected request for {}", other),
+            };
+            let if_none_match = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("If-None-Match"))
+                .map(|h| h.value.to_string())
+                .unwrap_or_default();
+            log.lock()
+                .unwrap()
+                .push(format!("{} {}", request.url(), if_none_match));
+
+            let header = tiny_http::Header::from_bytes("ETag", etag).unwrap();
+            let response = if if_none_match == etag {
+                tiny_http::Response::from_data(Vec::new())
+                    .with_status_code(304)
+                    .with_header(header)
+            } else {
+                tiny_http::Response::from_data(body.as_bytes().to_vec()).with_header(header)
+            };
+            request.respond(response).unwrap();
+        }
+    });
+
+    (port, seen, handle)
+}
+
+fn write_config(config_path: &Path, port: u16, schema_sha256: &str) {
+    fs::write(
+        config_path,
+        format!(
+            r#"[[sources]]
+repo = "http://127.0.0.1:{port}/schemas"
+name = "schemas"
+type = "http"
+sparse_paths = []
+target = "vendor/schemas"
+
+[[sources.http.files]]
+url = "v1/schema.json"
+path = "schema.json"
+sha256 = "{schema_sha256}"
+
+[[sources.http.files]]
+url = "http://127.0.0.1:{port}/licenses.txt"
+path = "data/licenses.txt"
+sha256 = "{licenses_sha256}"
+"#,
+            port = port,
+            schema_sha256 = schema_sha256,
+            licenses_sha256 = sha256(LICENSES),
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_http_source_downloads_files_and_revalidates() {
+    let project = tempdir().unwrap();
+    let cache = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    let (port, seen, handle) = serve(4);
+    write_config(&config_path, port, &sha256(SCHEMA));
+
+    for _ in 0..2 {
+        let output = acdm(&config_path, cache.path(), &["--force", "update"]);
+        assert!(
+            output.status.success(),
+            "Update failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let vendored = project.path().join("vendor/schemas");
+        assert_eq!(
+            fs::read_to_string(vendored.join("schema.json")).unwrap(),
+            SCHEMA
+        );
+        assert_eq!(
+            fs::read_to_string(vendored.join("data/licenses.txt")).unwrap(),
+            LICENSES
+        );
+    }
+    handle.join().unwrap();
+
+    // The second update sent the cached ETags and was served from the cache
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            "/schemas/v1/schema.json ".to_string(),
+            "/licenses.txt ".to_string(),
+            "/schemas/v1/schema.json \"schema-1\"".to_string(),
+            "/licenses.txt \"licenses-1\"".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_http_source_rejects_checksum_mismatch() {
+    let project = tempdir().unwrap();
+    let cache = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    let (port, _, handle) = serve(1);
+    write_config(&config_path, port, &"0".repeat(64));
+
+    let output = acdm(&config_path, cache.path(), &["--force", "update"]);
+    handle.join().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Checksum mismatch"), "Stderr: {}", stderr);
+    assert!(!project.path().join("vendor/schemas").exists());
+}
+
+#[test]
+fn test_http_source_requires_files() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        r#"[[sources]]
+repo = "https://example.com/schemas"
+name = "schemas"
+type = "http"
+sparse_paths = []
+target = "vendor/schemas"
+
+[sources.http]
+files = []
+"#,
+    )
+    .unwrap();
+
+    let output = acdm(&config_path, project.path(), &["check"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stdout.contains("must list at least one file")
+            || stderr.contains("must list at least one file"),
+        "Output: {}{}",
+        stdout,
+        stderr
+    );
+}
+
+// Serves `requests` requests, recording the URL and Authorization header of
+// each, and answering them with `respond`
+fn serve_recording(
+    requests: usize,
+    respond: impl Fn(&str) -> tiny_http::ResponseBox + Send + 'static,
+) -> (u16, Arc<Mutex<Vec<String>>>, JoinHandle<()>) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let port = server.server_addr().to_ip().unwrap().port();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+
+    let handle = std::thread::spawn(move || {
+        for _ in 0..requests {
+            let request = server.recv().unwrap();
+            let authorization = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .map(|h| h.value.to_string())
+                .unwrap_or_default();
+            log.lock()
+                .unwrap()
+                .push(format!("{} {}", request.url(), authorization));
+            request.respond(respond(&authorization)).unwrap();
+        }
+    });
+
+    (port, seen, handle)
+}
+
+#[test]
+fn test_http_source_sends_credentials_only_when_challenged() {
+    // Another host, serving the content the private host redirects to
+    let (public_port, public_seen, public_handle) = serve_recording(1, |_| {
+        tiny_http::Response::from_data(SCHEMA.as_bytes().to_vec()).boxed()
+    });
+    let (private_port, private_seen, private_handle) = serve_recording(2, move |authorization| {
+        if authorization.is_empty() {
+            tiny_http::Response::empty(401).boxed()
+        } else {
+            let location = format!("http://127.0.0.1:{}/schema.json", public_port);
+            tiny_http::Response::empty(302)
+                .with_header(tiny_http::Header::from_bytes("Location", location).unwrap())
+                .boxed()
+        }
+    });
+
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "http://127.0.0.1:{private_port}"
+name = "schemas"
+type = "http"
+sparse_paths = []
+target = "vendor/schemas"
+
+[[sources.http.files]]
+url = "schema.json"
+path = "schema.json"
+sha256 = "{schema_sha256}"
+
+[auth."127.0.0.1:{private_port}"]
+env = "ACDM_TEST_TOKEN"
+"#,
+            private_port = private_port,
+            schema_sha256 = sha256(SCHEMA),
+        ),
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env("ACDM_CACHE_DIR", project.path().join("cache"))
+        .env("ACDM_TEST_TOKEN", "s3cret")
+        .env("GIT_TOKEN", "global")
+        .args(["--quiet", "--force", "--config"])
+        .arg(&config_path)
+        .arg("update")
+        .output()
+        .unwrap();
+    private_handle.join().unwrap();
+    public_handle.join().unwrap();
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The token went to the configured host once it asked, and not further
+    assert_eq!(
+        *private_seen.lock().unwrap(),
+        vec![
+            "/schema.json ".to_string(),
+            "/schema.json Bearer s3cret".to_string()
+        ]
+    );
+    assert_eq!(
+        *public_seen.lock().unwrap(),
+        vec!["/schema.json ".to_string()]
+    );
+}
+
+#[test]
+fn test_http_source_refuses_paths_outside_the_target() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "http://127.0.0.1:9/schemas"
+name = "schemas"
+type = "http"
+sparse_paths = []
+target = "vendor/schemas"
+
+[[sources.http.files]]
+url = "schema.json"
+path = "../../escaped.json"
+sha256 = "{}"
+"#,
+            sha256(SCHEMA)
+        ),
+    )
+    .unwrap();
+
+    let output = acdm(&config_path, project.path(), &["--force", "update"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("must be relative"), "Stderr: {}", stderr);
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use super::common::{git, vendoring_project};
+
+// Helper function to run acdm with JSON output
+fn acdm_json(config_path: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--format", "json", "--config"])
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_update_emits_ndjson_events() {
+    let (_upstream, _project, config_path) = vendoring_project();
+
+    let output = acdm_json(&config_path, &["--force", "update"]);
+    assert!(output.status.success());
+
+    let events: Vec<Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Each line must be a JSON document"))
+        .collect();
+
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        ["dependency_started", "dependency_updated", "finished"]
+    );
+    assert!(events.iter().all(|e| e["schema_version"] == 1));
+    assert_eq!(events[1]["name"], "upstream");
+    assert_eq!(events[1]["files"], 1);
+    assert_eq!(events[2]["dependencies"][0]["name"], "upstream");
+}
+
+#[test]
+fn test_status_and_verify_emit_json_documents() {
+    let (_upstream, project, config_path) = vendoring_project();
+    assert!(acdm_json(&config_path, &["--force", "update"])
+        .status
+        .success());
+
+    let output = acdm_json(&config_path, &["status"]);
+    assert!(output.status.success());
+    let status: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(status["schema_version"], 1);
+    assert_eq!(status["command"], "status");
+    assert_eq!(status["data"][0]["name"], "upstream");
+    assert_eq!(status["data"][0]["status"], "up-to-date");
+
+    let output = acdm_json(&config_path, &["verify"]);
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["data"]["verified"], true);
+
+    // Local modifications make verification fail
+    fs::write(
+        project.path().join("vendor/upstream/docs/spec.md"),
+        "hotfix\n",
+    )
+    .unwrap();
+
+    let output = acdm_json(&config_path, &["verify"]);
+    assert!(!output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["data"]["verified"], false);
+    assert_eq!(report["data"]["failed"][0], "upstream");
+}
+
+#[test]
+fn test_outdated_reports_moved_upstream() {
+    let (upstream, _project, config_path) = vendoring_project();
+    assert!(acdm_json(&config_path, &["--force", "update"])
+        .status
+        .success());
+
+    let output = acdm_json(&config_path, &["outdated"]);
+    let outdated: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(outdated["data"].as_array().unwrap().len(), 0);
+
+    fs::write(upstream.path().join("docs/spec.md"), "v2\n").unwrap();
+    git(upstream.path(), &["commit", "-am", "Revise"]);
+
+    let output = acdm_json(&config_path, &["outdated"]);
+    assert!(output.status.success());
+    let outdated: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(outdated["command"], "outdated");
+    assert_eq!(outdated["data"][0]["name"], "upstream");
+    assert_ne!(
+        outdated["data"][0]["locked_commit"],
+        outdated["data"][0]["upstream_commit"]
+    );
+}
+
+#[test]
+fn test_outdated_resolves_only_named_dependencies() {
+    let (upstream, project, config_path) = vendoring_project();
+    let other = project.path().join("other-upstream");
+    git(
+        project.path(),
+        &[
+            "clone",
+            "--quiet",
+            upstream.path().to_str().unwrap(),
+            other.to_str().unwrap(),
+        ],
+    );
+    let mut config = fs::read_to_string(&config_path).unwrap();
+    config.push_str(&format!(
+        "\n[[sources]]\nrepo = \"{}\"\nname = \"other\"\nrev = \"main\"\ntype = \"git\"\nsparse_paths = [\"docs/**\"]\ntarget = \"vendor/other\"\n",
+        other.display()
+    ));
+    fs::write(&config_path, config).unwrap();
+    assert!(acdm_json(&config_path, &["--force", "update"])
+        .status
+        .success());
+
+    // The other upstream becomes unreachable
+    fs::remove_dir_all(&other).unwrap();
+    assert!(!acdm_json(&config_path, &["outdated"]).status.success());
+
+    let output = acdm_json(&config_path, &["outdated", "upstream"]);
+    assert!(
+        output.status.success(),
+        "Outdated failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let outdated: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(outdated["data"].as_array().unwrap().len(), 0);
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::fs;
+use tempfile::tempdir;
+
+use super::common::{acdm, commit_file, for_each_backend, git, init_repo, stderr};
+use wrale_acdm::domain::merge::merge;
+
+fn carries_local_modifications_forward(backend: &str) {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file(upstream.path(), "lib.txt", "one\ntwo\nthree\nfour\nfive\n");
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/upstream"
+"#,
+            upstream.path().display()
+        ),
+    )
+    .unwrap();
+    assert!(acdm(backend, &config_path, &["update"]).status.success());
+
+    // A hotfix to a vendored file and a file of our own
+    let lib = project.path().join("vendor/upstream/lib.txt");
+    let own = project.path().join("vendor/upstream/local.txt");
+    fs::write(&lib, "one\ntwo (hotfix)\nthree\nfour\nfive\n").unwrap();
+    fs::write(&own, "ours\n").unwrap();
+    commit_file(
+        upstream.path(),
+        "lib.txt",
+        "one\ntwo\nthree\nfour (upstream)\nfive\n",
+    );
+
+    // By default the update refuses to overwrite them
+    let output = acdm(backend, &config_path, &["update"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Local modifications would be overwritten"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(stderr(&output).contains("vendor/upstream/lib.txt"));
+    assert!(stderr(&output).contains("vendor/upstream/local.txt"));
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "one\ntwo (hotfix)\nthree\nfour\nfive\n"
+    );
+
+    // --keep-local merges them with the upstream changes
+    let output = acdm(backend, &config_path, &["update", "--keep-local"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "one\ntwo (hotfix)\nthree\nfour (upstream)\nfive\n"
+    );
+    assert_eq!(fs::read_to_string(&own).unwrap(), "ours\n");
+
+    // Changes to the same lines conflict, and are marked
+    commit_file(
+        upstream.path(),
+        "lib.txt",
+        "one\ntwo (upstream)\nthree\nfour (upstream)\nfive\n",
+    );
+
+    // Committing updates refuse them before the lock file or branches change
+    let lock_before = fs::read_to_string(project.path().join("acdm.lock")).unwrap();
+    for args in [
+        &["update", "--keep-local", "--commit"][..],
+        &["update", "--keep-local", "--branch", "acdm/conflicted"][..],
+    ] {
+        let output = acdm(backend, &config_path, args);
+        assert!(!output.status.success());
+        assert!(
+            stderr(&output).contains(
+                "conflict with upstream changes: vendor/upstream/lib.txt. Nothing was changed"
+            ),
+            "{}",
+            stderr(&output)
+        );
+        assert_eq!(
+            fs::read_to_string(&lib).unwrap(),
+            "one\ntwo (hotfix)\nthree\nfour (upstream)\nfive\n"
+        );
+        assert_eq!(
+            fs::read_to_string(project.path().join("acdm.lock")).unwrap(),
+            lock_before
+        );
+    }
+    assert_eq!(git(project.path(), &["branch", "--show-current"]), "main");
+    assert_eq!(
+        git(project.path(), &["branch", "--list", "acdm/conflicted"]),
+        ""
+    );
+
+    let output = acdm(backend, &config_path, &["update", "--keep-local"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("conflict with upstream changes in:\n  vendor/upstream/lib.txt"),
+        "{}",
+        stderr(&output)
+    );
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "one\n<<<<<<< local\ntwo (hotfix)\n=======\ntwo (upstream)\n>>>>>>> upstream\nthree\nfour (upstream)\nfive\n"
+    );
+    let lock = fs::read_to_string(project.path().join("acdm.lock")).unwrap();
+    assert!(
+        lock.contains(&git(upstream.path(), &["rev-parse", "HEAD"])),
+        "Lock: {}",
+        lock
+    );
+
+    // --discard-local overwrites them with the upstream content
+    let output = acdm(backend, &config_path, &["update", "--discard-local"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "one\ntwo (upstream)\nthree\nfour (upstream)\nfive\n"
+    );
+    assert!(!own.exists());
+}
+
+#[test]
+fn test_local_changes() {
+    for_each_backend(carries_local_modifications_forward);
+}
+
+#[test]
+fn test_merge_takes_changes_from_both_sides() {
+    let base = b"a\nb\nc\n";
+
+    let merged = merge(base, b"a\nB\nc\n", b"a\nb\nc\nd\n").unwrap();
+    assert!(!merged.conflicted);
+    assert_eq!(merged.content, b"a\nB\nc\nd\n");
+
+    // The same change on both sides is taken once
+    let merged = merge(base, b"A\nb\nc\n", b"A\nb\nc\n").unwrap();
+    assert!(!merged.conflicted);
+    assert_eq!(merged.content, b"A\nb\nc\n");
+
+    // Without a base, differing files conflict as a whole
+    let merged = merge(b"", b"x", b"y\n").unwrap();
+    assert!(merged.conflicted);
+    assert_eq!(
+        merged.content,
+        b"<<<<<<< local\nx\n=======\ny\n>>>>>>> upstream\n"
+    );
+
+    // Binary content is not merged
+    assert!(merge(base, b"a\0b", base).is_none());
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+use super::common::{git, init_repo};
+
+// Helper function to create an upstream repository with a single commit
+fn init_upstream(path: &Path) {
+    fs::create_dir_all(path).unwrap();
+    init_repo(path);
+
+    fs::create_dir_all(path.join("docs")).unwrap();
+    fs::write(path.join("docs/spec.md"), "spec\n").unwrap();
+    git(path, &["add", "."]);
+    git(path, &["commit", "-m", "Initial upstream commit"]);
+}
+
+// Helper function to write a configuration with a source and mirror rules
+fn write_config(project: &Path, repo: &str, mirrors: &str) -> std::path::PathBuf {
+    let config_path = project.join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = ["docs/**"]
+target = "vendor/upstream"
+
+[mirrors]
+{}
+"#,
+            repo, mirrors
+        ),
+    )
+    .unwrap();
+    config_path
+}
+
+// Helper function to run an update and return the lock file contents
+fn update(project: &Path, config_path: &Path) -> String {
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env("ACDM_CACHE_DIR", project.join("cache"))
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .arg("--config")
+        .arg(config_path)
+        .args(["--force", "update"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project.join("vendor/upstream/docs/spec.md").exists());
+
+    fs::read_to_string(project.join("acdm.lock")).unwrap()
+}
+
+#[test]
+fn test_update_fetches_from_mirror() {
+    let mirror_root = tempdir().unwrap();
+    init_upstream(&mirror_root.path().join("org/upstream"));
+
+    let project = tempdir().unwrap();
+    let config_path = write_config(
+        project.path(),
+        "https://canonical.invalid/org/upstream",
+        &format!(
+            r#""https://canonical.invalid/" = "{}/""#,
+            mirror_root.path().display()
+        ),
+    );
+
+    let lock = update(project.path(), &config_path);
+
+    // The canonical URL stays in the lock file, next to the mirror used
+    assert!(
+        lock.contains(r#"repo = "https://canonical.invalid/org/upstream""#),
+        "Lock: {}",
+        lock
+    );
+    assert!(
+        lock.contains(&format!(
+            r#"mirror = "{}/org/upstream""#,
+            mirror_root.path().display()
+        )),
+        "Lock: {}",
+        lock
+    );
+}
+
+#[test]
+fn test_update_tries_mirrors_in_order_and_falls_back() {
+    let upstream_root = tempdir().unwrap();
+    let upstream = upstream_root.path().join("upstream");
+    init_upstream(&upstream);
+
+    let missing = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    let config_path = write_config(
+        project.path(),
+        &upstream.display().to_string(),
+        &format!(
+            r#""{}/" = ["{}/first/", "{}/second/"]"#,
+            upstream_root.path().display(),
+            missing.path().display(),
+            missing.path().display()
+        ),
+    );
+
+    // Both mirrors fail, so the canonical URL is used and no mirror is recorded
+    let lock = update(project.path(), &config_path);
+    assert!(!lock.contains("mirror ="), "Lock: {}", lock);
+}
+
+#[test]
+fn test_longest_prefix_wins() {
+    let mirror_root = tempdir().unwrap();
+    init_upstream(&mirror_root.path().join("specific/upstream"));
+
+    let project = tempdir().unwrap();
+    let config_path = write_config(
+        project.path(),
+        "https://canonical.invalid/org/upstream",
+        &format!(
+            r#""https://canonical.invalid/" = "{root}/generic/"
+"https://canonical.invalid/org/" = "{root}/specific/""#,
+            root = mirror_root.path().display()
+        ),
+    );
+
+    let lock = update(project.path(), &config_path);
+    assert!(lock.contains("/specific/upstream"), "Lock: {}", lock);
+}
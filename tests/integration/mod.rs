@@ -1,7 +1,32 @@
 // Integration test module
+mod archive_source;
+mod auto_commit;
+mod check_command;
+mod clean_policy;
+mod common;
 mod config_management;
+#[allow(unused_imports)]
 mod current_dir_tests;
 mod dependency_management;
+mod diff_command;
+mod export_changes;
+mod git_authentication;
+mod git_backends;
 mod git_detection;
 mod git_operations;
+mod gitattributes;
+mod http_source;
 mod init_command;
+mod json_output;
+mod local_changes;
+mod mirrors;
+mod patches;
+mod path_source;
+mod provenance;
+mod repository_url;
+mod source_plugins;
+mod source_registry;
+mod status_command;
+mod transforms;
+mod update_branch;
+mod upstream_log;
@@ -0,0 +1,254 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+use super::common::{acdm, commit_file, for_each_backend, init_repo, stderr};
+use wrale_acdm::domain::patch::apply;
+
+fn write_config(config_path: &Path, upstream: &Path, patches: &str) {
+    fs::write(
+        config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/upstream"
+{}
+"#,
+            upstream.display(),
+            patches
+        ),
+    )
+    .unwrap();
+}
+
+const FIX_PATCH: &str = "\
+--- a/lib.txt
++++ b/lib.txt
+@@ -1,3 +1,3 @@
+ one
+-two
++two (fixed)
+ three
+";
+
+fn applies_patches_on_update(backend: &str) {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file(upstream.path(), "lib.txt", "one\ntwo\nthree\nfour\nfive\n");
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    fs::create_dir_all(project.path().join("patches/upstream")).unwrap();
+    fs::write(
+        project.path().join("patches/upstream/0001-fix.patch"),
+        FIX_PATCH,
+    )
+    .unwrap();
+    write_config(
+        &config_path,
+        upstream.path(),
+        r#"patches = ["patches/upstream/*.patch"]"#,
+    );
+
+    let output = acdm(backend, &config_path, &["update"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let lib = project.path().join("vendor/upstream/lib.txt");
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "one\ntwo (fixed)\nthree\nfour\nfive\n"
+    );
+
+    // The patched content is what the lock records, so it is not a local modification
+    let output = acdm(backend, &config_path, &["verify"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // Upstream changes elsewhere keep the patch applying
+    commit_file(
+        upstream.path(),
+        "lib.txt",
+        "one\ntwo\nthree\nfour\nfive (upstream)\n",
+    );
+    let output = acdm(backend, &config_path, &["update"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "one\ntwo (fixed)\nthree\nfour\nfive (upstream)\n"
+    );
+
+    // A patch that no longer applies fails the update and leaves everything in place
+    let lock_before = fs::read_to_string(project.path().join("acdm.lock")).unwrap();
+    commit_file(
+        upstream.path(),
+        "lib.txt",
+        "one\ntwo (upstream)\nthree\nfour\nfive (upstream)\n",
+    );
+    let output = acdm(backend, &config_path, &["update"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("0001-fix.patch no longer applies"),
+        "{}",
+        stderr(&output)
+    );
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "one\ntwo (fixed)\nthree\nfour\nfive (upstream)\n"
+    );
+    assert_eq!(
+        fs::read_to_string(project.path().join("acdm.lock")).unwrap(),
+        lock_before
+    );
+}
+
+#[test]
+fn test_patches() {
+    for_each_backend(applies_patches_on_update);
+}
+
+#[test]
+fn test_patch_pattern_matching_nothing_fails_the_update() {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file(upstream.path(), "lib.txt", "one\ntwo\nthree\n");
+
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    write_config(
+        &config_path,
+        upstream.path(),
+        r#"patches = ["patchs/upstream/*.patch"]"#,
+    );
+
+    let output = acdm("cli", &config_path, &["update"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Patch pattern 'patchs/upstream/*.patch' matches no files"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!project.path().join("vendor/upstream").exists());
+}
+
+#[test]
+fn test_patch_create_records_local_modifications() {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file(upstream.path(), "lib.txt", "one\ntwo\nthree\nfour\nfive\n");
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    write_config(&config_path, upstream.path(), "");
+    assert!(acdm("cli", &config_path, &["update"]).status.success());
+
+    // Without local modifications there is nothing to record
+    let output = acdm("cli", &config_path, &["patch", "create", "upstream"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("no local modifications"),
+        "{}",
+        stderr(&output)
+    );
+
+    let lib = project.path().join("vendor/upstream/lib.txt");
+    fs::write(&lib, "one\ntwo (hotfix)\nthree\nfour\nfive\n").unwrap();
+    fs::write(project.path().join("vendor/upstream/new.txt"), "ours\n").unwrap();
+
+    let output = acdm("cli", &config_path, &["patch", "create", "upstream"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let patch_path = project.path().join("patches/upstream/0001.patch");
+    let patch = fs::read_to_string(&patch_path).unwrap();
+    assert!(patch.contains("+two (hotfix)"), "{}", patch);
+    assert!(patch.contains("+ours"), "{}", patch);
+
+    let config = fs::read_to_string(&config_path).unwrap();
+    assert!(config.contains("patches/upstream/0001.patch"), "{}", config);
+    let provenance =
+        fs::read_to_string(project.path().join("vendor/upstream/.acdm-source.toml")).unwrap();
+    assert!(
+        provenance.contains("patches = [\"patches/upstream/0001.patch\"]"),
+        "{}",
+        provenance
+    );
+
+    // The target now matches the lock, and updates carry the patch forward
+    let output = acdm("cli", &config_path, &["verify"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    commit_file(
+        upstream.path(),
+        "lib.txt",
+        "one\ntwo\nthree\nfour\nfive\nsix\n",
+    );
+    let output = acdm("cli", &config_path, &["update"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(&lib).unwrap(),
+        "one\ntwo (hotfix)\nthree\nfour\nfive\nsix\n"
+    );
+
+    // Later patches are numbered after the existing ones
+    fs::write(&lib, "zero\none\ntwo (hotfix)\nthree\nfour\nfive\nsix\n").unwrap();
+    let output = acdm("cli", &config_path, &["patch", "create", "upstream"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(project.path().join("patches/upstream/0002.patch").exists());
+}
+
+#[test]
+fn test_apply_finds_moved_hunks_and_refuses_mismatches() {
+    let root = tempdir().unwrap();
+    fs::write(root.path().join("lib.txt"), "zero\none\ntwo\nthree\n").unwrap();
+
+    // The hunk matches one line later than recorded
+    apply(FIX_PATCH, root.path()).unwrap();
+    assert_eq!(
+        fs::read_to_string(root.path().join("lib.txt")).unwrap(),
+        "zero\none\ntwo (fixed)\nthree\n"
+    );
+
+    // Applying it again finds no matching context and writes nothing
+    let error = apply(FIX_PATCH, root.path()).unwrap_err();
+    assert!(error.contains("does not match"), "{}", error);
+
+    // New files are created, and no newline at the end of file is kept
+    let new_file = "\
+--- /dev/null
++++ b/docs/notes.txt
+@@ -0,0 +1 @@
++notes
+\\ No newline at end of file
+";
+    apply(new_file, root.path()).unwrap();
+    assert_eq!(
+        fs::read_to_string(root.path().join("docs/notes.txt")).unwrap(),
+        "notes"
+    );
+}
+
+#[test]
+fn test_apply_refuses_paths_outside_the_patched_files() {
+    let root = tempdir().unwrap();
+    let target = root.path().join("target");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(root.path().join("outside.txt"), "one\n").unwrap();
+
+    for (old_path, new_path) in [
+        ("a/../outside.txt", "b/../outside.txt"),
+        ("/dev/null", "b/../escaped.txt"),
+        ("/dev/null", "/tmp/escaped.txt"),
+    ] {
+        let patch = format!("--- {}\n+++ {}\n@@ -0,0 +1 @@\n+two\n", old_path, new_path);
+        let error = apply(&patch, &target).unwrap_err();
+        assert!(error.contains("is outside the patched files"), "{}", error);
+    }
+    assert_eq!(
+        fs::read_to_string(root.path().join("outside.txt")).unwrap(),
+        "one\n"
+    );
+    assert!(!root.path().join("escaped.txt").exists());
+}
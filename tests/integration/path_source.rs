@@ -0,0 +1,148 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+// Helper function to run acdm against a configuration file
+fn acdm(config_path: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .arg("--quiet")
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+// Helper function to create a sibling checkout next to the project
+fn create_sibling(root: &Path) {
+    let sibling = root.join("sibling");
+    fs::create_dir_all(sibling.join("docs/nested")).unwrap();
+    fs::create_dir_all(sibling.join("src")).unwrap();
+    fs::create_dir_all(sibling.join(".git")).unwrap();
+    fs::write(sibling.join("docs/guide.md"), "guide\n").unwrap();
+    fs::write(sibling.join("docs/nested/deep.md"), "deep\n").unwrap();
+    fs::write(sibling.join("src/lib.rs"), "// code\n").unwrap();
+    fs::write(sibling.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+}
+
+#[test]
+fn test_path_source_copies_matching_files() {
+    let root = tempdir().unwrap();
+    create_sibling(root.path());
+
+    let project = root.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let config_path = project.join("acdm.toml");
+    fs::write(
+        &config_path,
+        r#"[[sources]]
+repo = "../sibling"
+name = "sibling"
+type = "path"
+sparse_paths = ["docs/**"]
+target = "vendor/sibling"
+"#,
+    )
+    .unwrap();
+
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let vendored = project.join("vendor/sibling");
+    assert!(vendored.join("docs/guide.md").exists());
+    assert!(vendored.join("docs/nested/deep.md").exists());
+    assert!(!vendored.join("src").exists());
+
+    // The lock file records the content but no commit
+    let lock = fs::read_to_string(project.join("acdm.lock")).unwrap();
+    assert!(lock.contains("docs/guide.md"), "Lock: {}", lock);
+    assert!(!lock.contains("commit ="), "Lock: {}", lock);
+
+    // Remote checks do not apply to local directories
+    let output = acdm(&config_path, &["status", "--remote"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("sibling: up-to-date"), "Output: {}", stdout);
+}
+
+#[test]
+fn test_path_source_without_patterns_skips_git_metadata() {
+    let root = tempdir().unwrap();
+    create_sibling(root.path());
+
+    let project = root.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let config_path = project.join("acdm.toml");
+
+    let output = acdm(&config_path, &["init"]);
+    assert!(output.status.success());
+
+    let output = acdm(
+        &config_path,
+        &[
+            "--force",
+            "add",
+            "../sibling",
+            "--type",
+            "path",
+            "--name",
+            "sibling",
+            "--target",
+            "vendor/sibling",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "Add failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let config = fs::read_to_string(&config_path).unwrap();
+    assert!(config.contains(r#"type = "path""#), "Config: {}", config);
+    assert!(!config.contains("rev ="), "Config: {}", config);
+
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let vendored = project.join("vendor/sibling");
+    assert!(vendored.join("src/lib.rs").exists());
+    assert!(!vendored.join(".git").exists());
+}
+
+#[test]
+fn test_path_source_missing_directory_fails() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        r#"[[sources]]
+repo = "does-not-exist"
+name = "missing"
+type = "path"
+sparse_paths = []
+target = "vendor/missing"
+"#,
+    )
+    .unwrap();
+
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Source directory does not exist"),
+        "Stderr: {}",
+        stderr
+    );
+}
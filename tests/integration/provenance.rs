@@ -0,0 +1,122 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+use super::common::{commit_file_with_message, init_repo};
+
+// Helper function to run acdm against a configuration file
+fn acdm(config_path: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .arg("--quiet")
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_update_writes_provenance_read_by_status_and_verify() {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    let commit = commit_file_with_message(upstream.path(), "docs/spec.md", "v1\n", "Initial spec");
+
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = ["docs/**"]
+target = "vendor/upstream"
+"#,
+            upstream.path().display()
+        ),
+    )
+    .unwrap();
+
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The target records where it came from, outside the locked manifest
+    let provenance_path = project.path().join("vendor/upstream/.acdm-source.toml");
+    let provenance = fs::read_to_string(&provenance_path).unwrap();
+    assert!(
+        provenance.contains(&format!("commit = \"{}\"", commit)),
+        "Provenance: {}",
+        provenance
+    );
+    assert!(provenance.contains("type = \"git\""));
+    assert!(provenance.contains("sparse_paths = [\"docs/**\"]"));
+    assert!(provenance.contains(&format!("acdm_version = \"{}\"", env!("CARGO_PKG_VERSION"))));
+    assert!(
+        provenance.contains("updated_at = \"20"),
+        "Provenance: {}",
+        provenance
+    );
+
+    // An update that changes nothing leaves the file as it was, even once
+    // the clock has moved on
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&provenance_path).unwrap(), provenance);
+    let lock = fs::read_to_string(project.path().join("acdm.lock")).unwrap();
+    assert!(!lock.contains(".acdm-source.toml"), "Lock: {}", lock);
+
+    let output = acdm(&config_path, &["--format", "json", "status"]);
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let entry = &status["data"][0];
+    assert_eq!(entry["status"], "up-to-date");
+    assert_eq!(entry["provenance"]["commit"], commit.as_str());
+    assert_eq!(
+        entry["provenance"]["tool_version"],
+        env!("CARGO_PKG_VERSION")
+    );
+    assert!(entry["provenance"]["updated_at"]
+        .as_str()
+        .is_some_and(|time| provenance.contains(time)));
+
+    let output = acdm(&config_path, &["diff"]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains(".acdm-source.toml"));
+
+    // A record disagreeing with the lock file fails verification
+    fs::write(
+        &provenance_path,
+        provenance.replace(&commit, &"0".repeat(40)),
+    )
+    .unwrap();
+    let output = acdm(&config_path, &["verify"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(".acdm-source.toml does not match the lock file"),
+        "Output: {}",
+        stdout
+    );
+
+    // Updating keeps the file through cleaning and regenerates it
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(output.status.success());
+    let provenance = fs::read_to_string(&provenance_path).unwrap();
+    assert!(provenance.contains(&format!("commit = \"{}\"", commit)));
+    let output = acdm(&config_path, &["verify"]);
+    assert!(
+        output.status.success(),
+        "Verify failed: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
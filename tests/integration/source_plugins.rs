@@ -0,0 +1,245 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::tempdir;
+
+// Helper function to run acdm with plugins from `bin_dir` on PATH
+fn acdm(bin_dir: &Path, config_path: &Path, args: &[&str]) -> std::process::Output {
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .env("PATH", path)
+        .arg("--quiet")
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+// Installs an `acdm-source-<name>` script that saves its request next to it
+fn install_plugin(bin_dir: &Path, name: &str, body: &str) {
+    let script = bin_dir.join(format!("acdm-source-{}", name));
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\nrequest=$(cat)\nprintf '%s' \"$request\" > \"{}/request.json\"\n\
+             destination=$(printf '%s' \"$request\" | sed -n 's/.*\"destination\":\"\\([^\"]*\\)\".*/\\1/p')\n{}",
+            bin_dir.display(),
+            body
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn write_config(config_path: &Path) {
+    fs::write(
+        config_path,
+        r#"[[sources]]
+repo = "artifacts://store/schemas"
+name = "schemas"
+rev = "1.x"
+type = "artifacts"
+sparse_paths = []
+target = "vendor/schemas"
+
+[sources.artifacts]
+channel = "stable"
+"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_plugin_fetches_source_and_reports_version() {
+    let bin = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    install_plugin(
+        bin.path(),
+        "artifacts",
+        r#"mkdir -p "$destination/json"
+printf 'schema\n' > "$destination/json/schema.json"
+hash=$(sha256sum "$destination/json/schema.json" | cut -d' ' -f1)
+printf '{"version": "1.4.2", "files": {"json/schema.json": "%s"}}' "$hash"
+"#,
+    );
+    let config_path = project.path().join("acdm.toml");
+    write_config(&config_path);
+
+    let output = acdm(bin.path(), &config_path, &["--force", "update"]);
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(project.path().join("vendor/schemas/json/schema.json")).unwrap(),
+        "schema\n"
+    );
+
+    // Plugin types and their options pass validation
+    let output = acdm(bin.path(), &config_path, &["check"]);
+    assert!(
+        output.status.success(),
+        "Check failed: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    // The request carries the source and its options block
+    let request: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(bin.path().join("request.json")).unwrap())
+            .unwrap();
+    assert_eq!(request["protocol"], 1);
+    assert_eq!(request["name"], "schemas");
+    assert_eq!(request["url"], "artifacts://store/schemas");
+    assert_eq!(request["revision"], "1.x");
+    assert_eq!(request["options"]["channel"], "stable");
+
+    // The resolved version is locked like a commit
+    let lock = fs::read_to_string(project.path().join("acdm.lock")).unwrap();
+    assert!(lock.contains("commit = \"1.4.2\""), "Lock: {}", lock);
+
+    // The plugin chose the paths, so changes have no upstream paths to export to
+    let output = acdm(
+        bin.path(),
+        &config_path,
+        &[
+            "export-changes",
+            "schemas",
+            "--stdout",
+            "--author",
+            "Test User <test@example.com>",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("do not keep their upstream paths"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_plugin_failure_and_bad_hashes_abort_update() {
+    let bin = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    write_config(&config_path);
+
+    install_plugin(
+        bin.path(),
+        "artifacts",
+        "echo 'store unreachable' >&2\nexit 3\n",
+    );
+    let output = acdm(bin.path(), &config_path, &["--force", "update"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("store unreachable"), "Stderr: {}", stderr);
+
+    install_plugin(
+        bin.path(),
+        "artifacts",
+        r#"printf 'schema\n' > "$destination/schema.json"
+printf '{"version": "1.4.2", "files": {"schema.json": "0000"}}'
+"#,
+    );
+    let output = acdm(bin.path(), &config_path, &["--force", "update"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Checksum mismatch"), "Stderr: {}", stderr);
+    assert!(!project.path().join("vendor/schemas").exists());
+}
+
+#[test]
+fn test_plugin_timeouts_and_paths_outside_destination_abort_update() {
+    let bin = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    write_config(&config_path);
+
+    install_plugin(bin.path(), "artifacts", "exec sleep 30\n");
+    let started = std::time::Instant::now();
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env(
+            "PATH",
+            format!(
+                "{}:{}",
+                bin.path().display(),
+                std::env::var("PATH").unwrap_or_default()
+            ),
+        )
+        .env("ACDM_PLUGIN_TIMEOUT", "1")
+        .args(["--quiet", "--force", "--config"])
+        .arg(&config_path)
+        .arg("update")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("timed out after 1 seconds"),
+        "Stderr: {}",
+        stderr
+    );
+
+    // A child left running in the background keeps the output pipes open
+    install_plugin(
+        bin.path(),
+        "artifacts",
+        "sleep 30 &\nprintf '{\"files\": {}}'\n",
+    );
+    let started = std::time::Instant::now();
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env(
+            "PATH",
+            format!(
+                "{}:{}",
+                bin.path().display(),
+                std::env::var("PATH").unwrap_or_default()
+            ),
+        )
+        .env("ACDM_PLUGIN_TIMEOUT", "1")
+        .args(["--quiet", "--force", "--config"])
+        .arg(&config_path)
+        .arg("update")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("timed out after 1 seconds"),
+        "Stderr: {}",
+        stderr
+    );
+
+    // A reported file outside the destination is refused before it is read
+    install_plugin(
+        bin.path(),
+        "artifacts",
+        r#"printf '{"files": {"../../etc/passwd": "0000"}}'
+"#,
+    );
+    let output = acdm(bin.path(), &config_path, &["--force", "update"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("outside its destination"),
+        "Stderr: {}",
+        stderr
+    );
+    assert!(!project.path().join("vendor/schemas").exists());
+}
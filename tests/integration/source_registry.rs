@@ -0,0 +1,273 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+use wrale_acdm::domain::repositories::RepositoryFetcher;
+use wrale_acdm::domain::sources::SourceRegistry;
+use wrale_acdm::domain::{Dependency, DomainError};
+use wrale_acdm::infrastructure::git::GitRepositoryFetcher;
+
+use super::common::{sha256, tar_gz};
+
+// Helper function to run acdm against a configuration file
+fn acdm(config_path: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("acdm")
+        .unwrap()
+        .arg("--quiet")
+        .arg("--config")
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_add_writes_type_options_block() {
+    let project = tempdir().unwrap();
+    let archive = tar_gz(&[("release-1.0/docs/guide.md", "guide\n")]);
+    let checksum = sha256(&archive);
+    fs::write(project.path().join("release-1.0.tar.gz"), &archive).unwrap();
+    let config_path = project.path().join("acdm.toml");
+
+    let output = acdm(&config_path, &["init"]);
+    assert!(output.status.success());
+
+    let sha256_option = format!("sha256={}", checksum);
+    let output = acdm(
+        &config_path,
+        &[
+            "--force",
+            "add",
+            "release-1.0.tar.gz",
+            "--name",
+            "release",
+            "--target",
+            "vendor/release",
+            "--type",
+            "archive",
+            "--option",
+            &sha256_option,
+            "--option",
+            "strip_components=1",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "Add failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Options land in the block of the type, keeping their TOML types, and
+    // `--rev` is dropped since archives have no revisions
+    let config = fs::read_to_string(&config_path).unwrap();
+    assert!(config.contains("[sources.archive]"), "Config: {}", config);
+    assert!(
+        config.contains("strip_components = 1"),
+        "Config: {}",
+        config
+    );
+    assert!(!config.contains("rev ="), "Config: {}", config);
+
+    let output = acdm(&config_path, &["--force", "update"]);
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project.path().join("vendor/release/docs/guide.md").exists());
+}
+
+#[test]
+fn test_add_rejects_invalid_options() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+
+    let output = acdm(&config_path, &["init"]);
+    assert!(output.status.success());
+
+    // Options a type does not know
+    let output = acdm(
+        &config_path,
+        &[
+            "--force",
+            "add",
+            "https://github.com/example/repo.git",
+            "--name",
+            "repo",
+            "--target",
+            "vendor/repo",
+            "--option",
+            "depth=1",
+        ],
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unknown option 'depth' for git sources"),
+        "Stderr: {}",
+        stderr
+    );
+
+    // Types nobody registered
+    let output = acdm(
+        &config_path,
+        &[
+            "--force",
+            "add",
+            "svn://example.com/repo",
+            "--name",
+            "repo",
+            "--target",
+            "vendor/repo",
+            "--type",
+            "svn",
+        ],
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unsupported source type 'svn'"),
+        "Stderr: {}",
+        stderr
+    );
+
+    // Nothing was saved
+    let config = fs::read_to_string(&config_path).unwrap();
+    assert!(!config.contains("name = \"repo\""), "Config: {}", config);
+}
+
+#[test]
+fn test_check_reports_unsupported_type() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        r#"[[sources]]
+repo = "svn://example.com/repo"
+name = "repo"
+type = "svn"
+sparse_paths = []
+target = "vendor/repo"
+"#,
+    )
+    .unwrap();
+
+    let output = acdm(&config_path, &["check"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Unsupported source type 'svn' (supported: archive, git, http, path)"),
+        "Output: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_check_reports_unknown_keys_and_foreign_blocks() {
+    let project = tempdir().unwrap();
+    let config_path = project.path().join("acdm.toml");
+    let source = r#"[[sources]]
+repo = "git@github.com:example/repo.git"
+name = "repo"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/repo"
+ssh_key = "~/.ssh/deploy_key"
+"#;
+
+    // A misspelled key
+    fs::write(
+        &config_path,
+        format!("{}sprase_paths = [\"docs/**\"]\n", source),
+    )
+    .unwrap();
+    let output = acdm(&config_path, &["check"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("unknown key 'sprase_paths' in source 'repo'"),
+        "Output: {}",
+        stdout
+    );
+
+    // Options of another type
+    fs::write(
+        &config_path,
+        format!("{}\n[sources.archive]\nstrip_components = 1\n", source),
+    )
+    .unwrap();
+    let output = acdm(&config_path, &["check"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout
+            .contains("options [sources.archive] of source 'repo' do not apply to its type 'git'"),
+        "Output: {}",
+        stdout
+    );
+
+    // The source itself, SSH key included, is fine
+    fs::write(&config_path, source).unwrap();
+    let output = acdm(&config_path, &["check"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("unknown key"), "Output: {}", stdout);
+}
+
+/// A backend extracting and resolving on its own terms
+struct StampBackend;
+
+impl RepositoryFetcher for StampBackend {
+    fn fetch(&self, _url: &str, _revision: &str, _temp_path: &Path) -> Result<(), DomainError> {
+        Ok(())
+    }
+
+    fn extract_paths(
+        &self,
+        _repo_path: &Path,
+        _patterns: &[String],
+        target_path: &Path,
+    ) -> Result<(), DomainError> {
+        fs::write(target_path.join("stamp.txt"), "stamp\n").unwrap();
+        Ok(())
+    }
+
+    fn resolve_remote_revision(
+        &self,
+        _url: &str,
+        revision: &str,
+    ) -> Result<Option<String>, DomainError> {
+        Ok(Some(format!("stamped-{}", revision)))
+    }
+}
+
+#[test]
+fn test_registry_dispatches_extraction_and_resolution_by_type() {
+    let registry = SourceRegistry::new()
+        .with_backend("git", GitRepositoryFetcher::new())
+        .with_backend("stamp", StampBackend);
+    let dependency = Dependency {
+        name: "stamped".to_string(),
+        repository_url: "stamp://example".to_string(),
+        revision: "v1".to_string(),
+        repository_type: "stamp".to_string(),
+        ..Default::default()
+    };
+
+    let checkout = tempdir().unwrap();
+    let target = tempdir().unwrap();
+    registry
+        .extract_dependency_paths(&dependency, checkout.path(), &[], target.path())
+        .unwrap();
+    assert!(target.path().join("stamp.txt").exists());
+
+    assert_eq!(
+        registry
+            .resolve_remote_dependency(&dependency, "stamp://example")
+            .unwrap(),
+        Some("stamped-v1".to_string())
+    );
+}
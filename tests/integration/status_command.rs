@@ -0,0 +1,104 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+
+use super::common::{commit_file, vendoring_project};
+
+// Helper function to run acdm and return its stdout
+fn acdm(config_path: &Path, args: &[&str]) -> String {
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--config"])
+        .arg(config_path)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "acdm {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_status_reports_missing_and_up_to_date() {
+    let (_upstream, _project, config_path) = vendoring_project();
+
+    let stdout = acdm(&config_path, &["status"]);
+    assert!(stdout.contains("upstream: missing"), "Output: {}", stdout);
+
+    acdm(&config_path, &["--force", "update"]);
+
+    let stdout = acdm(&config_path, &["status", "--detailed"]);
+    assert!(
+        stdout.contains("upstream: up-to-date"),
+        "Output: {}",
+        stdout
+    );
+    assert!(stdout.contains("up-to-date: matches the lock file at "));
+}
+
+#[test]
+fn test_status_reports_local_modifications() {
+    let (_upstream, project, config_path) = vendoring_project();
+    acdm(&config_path, &["--force", "update"]);
+
+    fs::write(
+        project.path().join("vendor/upstream/docs/spec.md"),
+        "hotfix\n",
+    )
+    .unwrap();
+    fs::write(
+        project.path().join("vendor/upstream/docs/extra.md"),
+        "new\n",
+    )
+    .unwrap();
+
+    let stdout = acdm(&config_path, &["status", "--detailed"]);
+    assert!(
+        stdout.contains("upstream: locally-modified"),
+        "Output: {}",
+        stdout
+    );
+    assert!(stdout.contains("M docs/spec.md"), "Output: {}", stdout);
+    assert!(stdout.contains("A docs/extra.md"), "Output: {}", stdout);
+}
+
+#[test]
+fn test_status_reports_config_changes_and_upstream_moves() {
+    let (upstream, _project, config_path) = vendoring_project();
+    acdm(&config_path, &["--force", "update"]);
+
+    // Move the upstream branch
+    commit_file(upstream.path(), "docs/spec.md", "v2\n");
+
+    let stdout = acdm(&config_path, &["status"]);
+    assert!(
+        stdout.contains("upstream: up-to-date"),
+        "Output: {}",
+        stdout
+    );
+
+    let stdout = acdm(&config_path, &["status", "--remote", "--detailed"]);
+    assert!(
+        stdout.contains("upstream: upstream-moved"),
+        "Output: {}",
+        stdout
+    );
+
+    // Change the configuration without updating
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(&config_path, config.replace("docs/**", "docs/*.md")).unwrap();
+
+    let stdout = acdm(&config_path, &["status", "--detailed"]);
+    assert!(
+        stdout.contains("upstream: config-changed"),
+        "Output: {}",
+        stdout
+    );
+    assert!(stdout.contains("(sparse_paths)"), "Output: {}", stdout);
+}
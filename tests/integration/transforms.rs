@@ -0,0 +1,259 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::fs;
+use tempfile::tempdir;
+
+use super::common::{acdm, commit_file, git, init_repo, stderr, stdout};
+use wrale_acdm::domain::repositories::ConfigurationRepository;
+use wrale_acdm::domain::transform::{apply, Transform, TransformAction};
+use wrale_acdm::infrastructure::configuration::TomlConfigurationRepository;
+
+const TRANSFORMS: &str = r#"
+[[sources.transforms]]
+type = "strip-header"
+contains = "Copyright"
+paths = ["src/**"]
+
+[[sources.transforms]]
+type = "line-endings"
+style = "lf"
+
+[[sources.transforms]]
+type = "replace"
+from = "github.com/upstream/lib"
+to = "example.com/vendor/lib"
+paths = ["src/**"]
+
+[[sources.transforms]]
+type = "rename-extension"
+from = "md"
+to = "markdown"
+"#;
+
+const MAIN: &str = "// Copyright (c) Upstream Authors\r\n// Licensed under the MIT license\r\n\r\nimport \"github.com/upstream/lib\"\r\n\r\nrun()\r\n";
+
+#[test]
+fn test_transforms_rewrite_vendored_content() {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file(upstream.path(), "src/main.txt", MAIN);
+    commit_file(upstream.path(), "docs/guide.md", "# Guide\n");
+    let base = git(upstream.path(), &["rev-parse", "HEAD"]);
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/upstream"
+{}"#,
+            upstream.path().display(),
+            TRANSFORMS
+        ),
+    )
+    .unwrap();
+
+    let output = acdm("cli", &config_path, &["update"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let vendored = project.path().join("vendor/upstream");
+    assert_eq!(
+        fs::read_to_string(vendored.join("src/main.txt")).unwrap(),
+        "import \"example.com/vendor/lib\"\n\nrun()\n"
+    );
+    assert!(!vendored.join("docs/guide.md").exists());
+    assert_eq!(
+        fs::read_to_string(vendored.join("docs/guide.markdown")).unwrap(),
+        "# Guide\n"
+    );
+
+    // The lock records the transformed content
+    let output = acdm("cli", &config_path, &["verify"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // and the steps, so changing one fails the CI check until the next update
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        config.replace("style = \"lf\"", "style = \"crlf\""),
+    )
+    .unwrap();
+    let output = acdm("cli", &config_path, &["check", "--ci"]);
+    assert_eq!(output.status.code(), Some(4), "{}", stdout(&output));
+    let output = acdm("cli", &config_path, &["status", "--detailed"]);
+    assert!(
+        stdout(&output).contains("differs from the last update (transforms)"),
+        "{}",
+        stdout(&output)
+    );
+    fs::write(&config_path, config).unwrap();
+
+    // diff reports every step with the files it changed
+    commit_file(upstream.path(), "docs/more.md", "More\n");
+    let output = acdm("cli", &config_path, &["diff"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(
+        text.contains(
+            "transform: strip headers containing 'Copyright' in src/** (1 files)\n    src/main.txt\n"
+        ),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(
+            "transform: rename .md to .markdown (2 files)\n    docs/guide.md\n    docs/more.md\n"
+        ),
+        "{}",
+        text
+    );
+    assert!(text.contains("A docs/more.markdown"), "{}", text);
+
+    let output = acdm("cli", &config_path, &["--format", "json", "diff"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let steps = json["data"][0]["transforms"].as_array().unwrap();
+    assert_eq!(steps.len(), 4);
+    assert_eq!(
+        steps[2]["step"],
+        "replace 'github.com/upstream/lib' with 'example.com/vendor/lib' in src/**"
+    );
+    assert_eq!(steps[2]["files"], serde_json::json!(["src/main.txt"]));
+
+    // Exported changes use the upstream names of renamed files
+    fs::write(vendored.join("docs/guide.markdown"), "# Guide\n\nFixed\n").unwrap();
+    let output = acdm(
+        "cli",
+        &config_path,
+        &[
+            "export-changes",
+            "upstream",
+            "--stdout",
+            "--author",
+            "A <a@b.c>",
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let series = stdout(&output);
+    assert!(
+        series.contains("diff --git a/docs/guide.md b/docs/guide.md"),
+        "{}",
+        series
+    );
+    assert!(series.contains("+++ b/docs/guide.md"), "{}", series);
+    assert!(
+        series.contains(&format!("base-commit: {}", base)),
+        "{}",
+        series
+    );
+}
+
+#[test]
+fn test_transforms_survive_configuration_round_trip() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "https://github.com/example/repo.git"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/upstream"
+{}"#,
+            TRANSFORMS
+        ),
+    )
+    .unwrap();
+
+    let config_repo = TomlConfigurationRepository::new();
+    let config = config_repo.load(&config_path).unwrap();
+    let transforms = config.dependencies[0].transforms.clone();
+    assert_eq!(transforms.len(), 4);
+    assert_eq!(
+        transforms[3].action,
+        TransformAction::RenameExtension {
+            from: "md".to_string(),
+            to: "markdown".to_string()
+        }
+    );
+
+    config_repo.save(&config, &config_path).unwrap();
+    let reloaded = config_repo.load(&config_path).unwrap();
+    assert_eq!(reloaded.dependencies[0].transforms, transforms);
+}
+
+#[test]
+fn test_strip_header_keeps_shebang_and_unmatched_headers() {
+    let root = tempdir().unwrap();
+    fs::write(
+        root.path().join("script.sh"),
+        "#!/bin/sh\n# Copyright (c) Upstream\n\necho hi\n",
+    )
+    .unwrap();
+    fs::write(
+        root.path().join("lib.c"),
+        "/*\n * Copyright (c) Upstream\n */\n\nint x;\n",
+    )
+    .unwrap();
+    fs::write(root.path().join("other.c"), "/* Just code */\nint y;\n").unwrap();
+    fs::write(
+        root.path().join("main.c"),
+        "// Copyright (c) Upstream\n#include <stdio.h>\n\nint main;\n",
+    )
+    .unwrap();
+    fs::write(
+        root.path().join("lib.rs"),
+        "#![allow(unused)]\n// Copyright (c) Upstream\nfn f() {}\n",
+    )
+    .unwrap();
+
+    let strip = Transform {
+        action: TransformAction::StripHeader {
+            contains: "Copyright".to_string(),
+        },
+        paths: Vec::new(),
+    };
+    let changed = apply(&strip, root.path()).unwrap();
+    assert_eq!(changed.len(), 3);
+    assert_eq!(
+        fs::read_to_string(root.path().join("script.sh")).unwrap(),
+        "#!/bin/sh\necho hi\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.path().join("lib.c")).unwrap(),
+        "int x;\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.path().join("other.c")).unwrap(),
+        "/* Just code */\nint y;\n"
+    );
+
+    // Preprocessor directives and attributes are code, not comments
+    assert_eq!(
+        fs::read_to_string(root.path().join("main.c")).unwrap(),
+        "#include <stdio.h>\n\nint main;\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.path().join("lib.rs")).unwrap(),
+        "#![allow(unused)]\n// Copyright (c) Upstream\nfn f() {}\n"
+    );
+
+    // A step without a text to replace is rejected
+    let invalid = Transform {
+        action: TransformAction::Replace {
+            from: String::new(),
+            to: "x".to_string(),
+        },
+        paths: Vec::new(),
+    };
+    assert!(apply(&invalid, root.path()).is_err());
+}
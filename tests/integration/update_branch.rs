@@ -0,0 +1,163 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use std::fs;
+use tempfile::{tempdir, TempDir};
+
+use super::common::{acdm, for_each_backend, git, init_repo};
+
+fn upstream_repo(file: &str) -> TempDir {
+    let dir = tempdir().unwrap();
+    init_repo(dir.path());
+    fs::write(dir.path().join(file), "content\n").unwrap();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-m", "Initial"]);
+    dir
+}
+
+fn updates_on_a_new_branch(backend: &str) {
+    let alpha = upstream_repo("alpha.txt");
+    let beta = upstream_repo("beta.txt");
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "alpha"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/alpha"
+
+[[sources]]
+repo = "{}"
+name = "beta"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/beta"
+"#,
+            alpha.path().display(),
+            beta.path().display()
+        ),
+    )
+    .unwrap();
+    git(project.path(), &["add", "-A"]);
+    git(project.path(), &["commit", "-m", "Configure acdm"]);
+    let main = git(project.path(), &["rev-parse", "HEAD"]);
+
+    let output = acdm(
+        backend,
+        &config_path,
+        &["update", "--branch", "acdm/update-test"],
+    );
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The user is left on the new branch, one commit per dependency ahead
+    assert_eq!(
+        git(project.path(), &["branch", "--show-current"]),
+        "acdm/update-test"
+    );
+    assert_eq!(git(project.path(), &["rev-parse", "main"]), main);
+    assert_eq!(
+        git(project.path(), &["log", "--format=%s", "main..HEAD"]),
+        "Update dependency beta\nUpdate dependency alpha"
+    );
+    let beta_files = git(
+        project.path(),
+        &["show", "--name-only", "--format=", "HEAD"],
+    );
+    assert!(
+        beta_files.contains("vendor/beta/beta.txt"),
+        "{}",
+        beta_files
+    );
+    assert!(!beta_files.contains("vendor/alpha"), "{}", beta_files);
+    assert!(git(project.path(), &["status", "--porcelain"]).is_empty());
+
+    // Existing branches are refused before anything changes
+    git(project.path(), &["switch", "main"]);
+    let output = acdm(
+        backend,
+        &config_path,
+        &["update", "--branch", "acdm/update-test"],
+    );
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("already exists"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(git(project.path(), &["branch", "--show-current"]), "main");
+    assert!(!project.path().join("vendor").exists());
+
+    // Without a name, the branch is named after the date
+    let output = acdm(backend, &config_path, &["update", "--branch"]);
+    assert!(output.status.success());
+    assert!(git(project.path(), &["branch", "--show-current"]).starts_with("acdm/update-20"));
+}
+
+#[test]
+fn test_update_branch() {
+    for_each_backend(updates_on_a_new_branch);
+}
+
+#[test]
+fn test_update_branch_reports_the_sources_committed_before_a_failure() {
+    let alpha = upstream_repo("alpha.txt");
+    let missing = tempdir().unwrap();
+
+    let project = tempdir().unwrap();
+    init_repo(project.path());
+    let config_path = project.path().join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "alpha"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/alpha"
+
+[[sources]]
+repo = "{}"
+name = "broken"
+rev = "main"
+type = "git"
+sparse_paths = []
+target = "vendor/broken"
+"#,
+            alpha.path().display(),
+            missing.path().join("gone").display()
+        ),
+    )
+    .unwrap();
+    git(project.path(), &["add", "-A"]);
+    git(project.path(), &["commit", "-m", "Configure acdm"]);
+
+    let output = acdm("cli", &config_path, &["update", "--branch", "acdm/partial"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr
+            .contains("Stopped at 'broken' on branch 'acdm/partial', which has commits for alpha"),
+        "{}",
+        stderr
+    );
+    assert_eq!(
+        git(
+            project.path(),
+            &["log", "--format=%s", "main..acdm/partial"]
+        ),
+        "Update dependency alpha"
+    );
+}
@@ -0,0 +1,175 @@
+// Copyright (c) 2025 Wrale LTD <contact@wrale.com>
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+use super::common::{commit_file_with_message, init_repo};
+
+// Helper function to write a configuration pointing at the upstream repository
+fn write_config(project: &Path, upstream: &Path) -> PathBuf {
+    let config_path = project.join("acdm.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"[[sources]]
+repo = "{}"
+name = "upstream"
+rev = "main"
+type = "git"
+sparse_paths = ["docs/**"]
+target = "vendor/upstream"
+"#,
+            upstream.display()
+        ),
+    )
+    .unwrap();
+    config_path
+}
+
+#[test]
+fn test_log_lists_commits_touching_sparse_paths() {
+    let upstream = tempdir().unwrap();
+    init_repo(upstream.path());
+    commit_file_with_message(upstream.path(), "docs/spec.md", "v1\n", "Initial spec");
+
+    let project = tempdir().unwrap();
+    let cache = tempdir().unwrap();
+    let config_path = write_config(project.path(), upstream.path());
+
+    // Vendor the initial content, which records the commit in the lock file
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--force", "--config"])
+        .arg(&config_path)
+        .arg("update")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Update failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let lock = fs::read_to_string(project.path().join("acdm.lock")).unwrap();
+    assert!(lock.contains("commit = "), "Lock file: {}", lock);
+
+    // Move upstream with one relevant and one unrelated commit
+    commit_file_with_message(upstream.path(), "docs/spec.md", "v2\n", "Revise spec");
+    commit_file_with_message(
+        upstream.path(),
+        "src/main.c",
+        "int main;\n",
+        "Unrelated code",
+    );
+
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .env("ACDM_CACHE_DIR", cache.path())
+        .args(["--quiet", "--config"])
+        .arg(&config_path)
+        .args(["log", "upstream", "--markdown"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Log failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("### upstream: "), "Output: {}", stdout);
+    assert!(
+        stdout.contains("Revise spec (Test User, "),
+        "Output: {}",
+        stdout
+    );
+    assert!(!stdout.contains("Unrelated code"), "Output: {}", stdout);
+    assert!(!stdout.contains("Initial spec"), "Output: {}", stdout);
+}
+
+#[test]
+fn test_log_requires_locked_commit() {
+    let project = tempdir().unwrap();
+    let config_path = write_config(project.path(), Path::new("/nonexistent/upstream"));
+
+    let output = Command::cargo_bin("acdm")
+        .unwrap()
+        .args(["--quiet", "--config"])
+        .arg(&config_path)
+        .args(["log", "upstream"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No locked commit"), "Stderr: {}", stderr);
+}
+
+#[test]
+fn test_log_keeps_similar_repository_urls_apart() {
+    // Both paths read `a_b` once punctuation is replaced
+    let upstreams = tempdir().unwrap();
+    let project = tempdir().unwrap();
+    let cache = tempdir().unwrap();
+    let mut config = String::new();
+    for (name, dir) in [("dashed", "a-b"), ("underscored", "a_b")] {
+        let upstream = upstreams.path().join(dir);
+        fs::create_dir_all(&upstream).unwrap();
+        init_repo(&upstream);
+        commit_file_with_message(&upstream, "docs/spec.md", "v1\n", "Initial spec");
+        config.push_str(&format!(
+            "[[sources]]\nrepo = \"{}\"\nname = \"{}\"\nrev = \"main\"\ntype = \"git\"\nsparse_paths = [\"docs/**\"]\ntarget = \"vendor/{}\"\n\n",
+            upstream.display(),
+            name,
+            name
+        ));
+    }
+    let config_path = project.path().join("acdm.toml");
+    fs::write(&config_path, config).unwrap();
+
+    let acdm = |args: &[&str]| {
+        let output = Command::cargo_bin("acdm")
+            .unwrap()
+            .env("ACDM_CACHE_DIR", cache.path())
+            .args(["--quiet", "--force", "--config"])
+            .arg(&config_path)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "acdm {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    acdm(&["update"]);
+
+    for (name, dir) in [("dashed", "a-b"), ("underscored", "a_b")] {
+        let message = format!("Revise {}", name);
+        commit_file_with_message(
+            &upstreams.path().join(dir),
+            "docs/spec.md",
+            "v2\n",
+            &message,
+        );
+    }
+
+    for (name, other) in [("dashed", "underscored"), ("underscored", "dashed")] {
+        let stdout = acdm(&["log", name]);
+        assert!(
+            stdout.contains(&format!("Revise {}", name)),
+            "Output: {}",
+            stdout
+        );
+        assert!(
+            !stdout.contains(&format!("Revise {}", other)),
+            "Output: {}",
+            stdout
+        );
+    }
+    assert_eq!(fs::read_dir(cache.path().join("repos")).unwrap().count(), 2);
+}